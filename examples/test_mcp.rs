@@ -24,6 +24,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }],
         default_timeout: 30,
         tool_timeouts: std::collections::HashMap::new(),
+        builtin_tools: Default::default(),
     };
 
     // Create client
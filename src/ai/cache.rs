@@ -1,34 +1,104 @@
+//! SQLite cache for AI analysis results.
+//!
+//! Entries are keyed by error group (its fingerprint id, or its template for
+//! Drain groups), provider and model, so the same error analyzed by a
+//! different model is never served from cache.
+//! Entries older than the configured TTL are ignored on read and removed by
+//! [`AnalysisCache::prune`].
+
 use crate::types::ErrorAnalysis;
 use crate::Result;
 use rusqlite::{params, Connection};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default time-to-live for cached analyses
+pub const DEFAULT_CACHE_TTL_DAYS: u64 = 30;
+
+/// Summary of the cache contents
+#[derive(Debug, Clone, Default)]
+pub struct CacheStats {
+    /// Total number of cached analyses
+    pub entries: usize,
+    /// Number of entries older than the TTL
+    pub expired: usize,
+    /// Number of entries per provider
+    pub by_provider: Vec<(String, usize)>,
+    /// Size of the database file in bytes
+    pub size_bytes: u64,
+    /// Path of the database file
+    pub path: Option<PathBuf>,
+}
 
 pub struct AnalysisCache {
     conn: Connection,
+    ttl: Option<Duration>,
+    path: Option<PathBuf>,
 }
 
 impl AnalysisCache {
+    /// Open the default cache (`~/.logai/cache/cache.db`)
     pub fn new() -> Result<Self> {
         let cache_dir = Self::cache_dir()?;
-        std::fs::create_dir_all(&cache_dir)?;
+        Self::open(cache_dir.join("cache.db"))
+    }
+
+    /// Open (or create) a cache database at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+        Self::migrate(&conn)?;
+
+        Ok(Self {
+            conn,
+            ttl: Some(Duration::from_secs(DEFAULT_CACHE_TTL_DAYS * 86400)),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Set how long cached analyses stay valid (`None` = forever)
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
 
-        let db_path = cache_dir.join("cache.db");
-        let conn = Connection::open(db_path)?;
+    fn migrate(conn: &Connection) -> Result<()> {
+        // Early versions keyed the table by pattern only and did not store
+        // resources or tool invocations. It is only a cache, so rebuild it.
+        let has_current_schema = {
+            let mut stmt = conn.prepare("PRAGMA table_info(analysis_cache)")?;
+            let columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            columns.is_empty() || columns.iter().any(|c| c == "tool_invocations")
+        };
+
+        if !has_current_schema {
+            log::debug!("Rebuilding analysis cache with the current schema");
+            conn.execute("DROP TABLE analysis_cache", [])?;
+        }
 
         conn.execute(
             "CREATE TABLE IF NOT EXISTS analysis_cache (
-                pattern TEXT PRIMARY KEY,
+                pattern TEXT NOT NULL,
                 provider TEXT NOT NULL,
                 model TEXT NOT NULL,
                 explanation TEXT NOT NULL,
                 root_cause TEXT,
                 suggestions TEXT NOT NULL,
-                created_at INTEGER NOT NULL
+                related_resources TEXT NOT NULL DEFAULT '[]',
+                tool_invocations TEXT NOT NULL DEFAULT '[]',
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (pattern, provider, model)
             )",
             [],
         )?;
 
-        Ok(Self { conn })
+        Ok(())
     }
 
     fn cache_dir() -> Result<PathBuf> {
@@ -38,31 +108,54 @@ impl AnalysisCache {
         Ok(PathBuf::from(home).join(".logai").join("cache"))
     }
 
+    fn now() -> Result<i64> {
+        Ok(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64)
+    }
+
+    /// Oldest `created_at` that is still considered fresh
+    fn ttl_cutoff(&self) -> Result<i64> {
+        match self.ttl {
+            Some(ttl) => Ok(Self::now()? - ttl.as_secs() as i64),
+            None => Ok(i64::MIN),
+        }
+    }
+
     pub fn get(&self, pattern: &str, provider: &str, model: &str) -> Result<Option<ErrorAnalysis>> {
         let mut stmt = self.conn.prepare(
-            "SELECT explanation, root_cause, suggestions 
-             FROM analysis_cache 
-             WHERE pattern = ?1 AND provider = ?2 AND model = ?3",
+            "SELECT explanation, root_cause, suggestions, related_resources, tool_invocations
+             FROM analysis_cache
+             WHERE pattern = ?1 AND provider = ?2 AND model = ?3 AND created_at >= ?4",
         )?;
 
-        let result = stmt.query_row(params![pattern, provider, model], |row| {
-            let explanation: String = row.get(0)?;
-            let root_cause: Option<String> = row.get(1)?;
-            let suggestions_json: String = row.get(2)?;
+        let result = stmt.query_row(
+            params![pattern, provider, model, self.ttl_cutoff()?],
+            |row| {
+                let explanation: String = row.get(0)?;
+                let root_cause: Option<String> = row.get(1)?;
+                let suggestions_json: String = row.get(2)?;
+                let resources_json: String = row.get(3)?;
+                let tools_json: String = row.get(4)?;
 
-            Ok((explanation, root_cause, suggestions_json))
-        });
+                Ok((
+                    explanation,
+                    root_cause,
+                    suggestions_json,
+                    resources_json,
+                    tools_json,
+                ))
+            },
+        );
 
         match result {
-            Ok((explanation, root_cause, suggestions_json)) => {
-                let suggestions = serde_json::from_str(&suggestions_json).unwrap_or_default();
-
+            Ok((explanation, root_cause, suggestions_json, resources_json, tools_json)) => {
                 Ok(Some(ErrorAnalysis {
                     explanation,
                     root_cause,
-                    suggestions,
-                    related_resources: vec![],
-                    tool_invocations: vec![],
+                    suggestions: serde_json::from_str(&suggestions_json).unwrap_or_default(),
+                    related_resources: serde_json::from_str(&resources_json).unwrap_or_default(),
+                    tool_invocations: serde_json::from_str(&tools_json).unwrap_or_default(),
                 }))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -78,14 +171,14 @@ impl AnalysisCache {
         analysis: &ErrorAnalysis,
     ) -> Result<()> {
         let suggestions_json = serde_json::to_string(&analysis.suggestions)?;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
+        let resources_json = serde_json::to_string(&analysis.related_resources)?;
+        let tools_json = serde_json::to_string(&analysis.tool_invocations)?;
 
         self.conn.execute(
-            "INSERT OR REPLACE INTO analysis_cache 
-             (pattern, provider, model, explanation, root_cause, suggestions, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT OR REPLACE INTO analysis_cache
+             (pattern, provider, model, explanation, root_cause, suggestions,
+              related_resources, tool_invocations, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 pattern,
                 provider,
@@ -93,7 +186,9 @@ impl AnalysisCache {
                 &analysis.explanation,
                 &analysis.root_cause,
                 suggestions_json,
-                now
+                resources_json,
+                tools_json,
+                Self::now()?
             ],
         )?;
 
@@ -101,10 +196,7 @@ impl AnalysisCache {
     }
 
     pub fn clear_old(&self, days: i64) -> Result<usize> {
-        let cutoff = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64
-            - (days * 86400);
+        let cutoff = Self::now()? - (days * 86400);
 
         let deleted = self.conn.execute(
             "DELETE FROM analysis_cache WHERE created_at < ?1",
@@ -113,4 +205,192 @@ impl AnalysisCache {
 
         Ok(deleted)
     }
+
+    /// Remove entries older than the TTL
+    pub fn prune(&self) -> Result<usize> {
+        let deleted = self.conn.execute(
+            "DELETE FROM analysis_cache WHERE created_at < ?1",
+            params![self.ttl_cutoff()?],
+        )?;
+
+        Ok(deleted)
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM analysis_cache", [])?)
+    }
+
+    /// Summarize the cache contents
+    pub fn stats(&self) -> Result<CacheStats> {
+        let entries: i64 =
+            self.conn
+                .query_row("SELECT COUNT(*) FROM analysis_cache", [], |row| row.get(0))?;
+
+        let expired: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM analysis_cache WHERE created_at < ?1",
+            params![self.ttl_cutoff()?],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, COUNT(*) FROM analysis_cache
+             GROUP BY provider ORDER BY COUNT(*) DESC",
+        )?;
+        let by_provider = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let size_bytes = self
+            .path
+            .as_ref()
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        Ok(CacheStats {
+            entries: entries as usize,
+            expired: expired as usize,
+            by_provider,
+            size_bytes,
+            path: self.path.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::ToolInvocationSummary;
+    use crate::types::Resource;
+
+    fn sample_analysis() -> ErrorAnalysis {
+        ErrorAnalysis {
+            explanation: "Database unreachable".to_string(),
+            root_cause: Some("Connection pool exhausted".to_string()),
+            suggestions: vec![],
+            related_resources: vec![Resource {
+                title: "Pool tuning".to_string(),
+                url: "https://example.com/pool".to_string(),
+                source: "docs".to_string(),
+            }],
+            tool_invocations: vec![ToolInvocationSummary {
+                tool: "search_code".to_string(),
+                status: "success".to_string(),
+                duration_ms: 12,
+                contributed_to: vec!["root_cause".to_string()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trips_resources_and_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(dir.path().join("cache.db")).unwrap();
+
+        cache
+            .set("pattern", "openai", "gpt-4o", &sample_analysis())
+            .unwrap();
+        let cached = cache.get("pattern", "openai", "gpt-4o").unwrap().unwrap();
+
+        assert_eq!(cached.related_resources.len(), 1);
+        assert_eq!(cached.related_resources[0].title, "Pool tuning");
+        assert_eq!(cached.tool_invocations.len(), 1);
+        assert_eq!(cached.tool_invocations[0].tool, "search_code");
+    }
+
+    #[test]
+    fn test_key_includes_provider_and_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(dir.path().join("cache.db")).unwrap();
+
+        let mut other = sample_analysis();
+        other.explanation = "From claude".to_string();
+
+        cache
+            .set("pattern", "openai", "gpt-4o", &sample_analysis())
+            .unwrap();
+        cache.set("pattern", "claude", "haiku", &other).unwrap();
+
+        assert_eq!(
+            cache
+                .get("pattern", "openai", "gpt-4o")
+                .unwrap()
+                .unwrap()
+                .explanation,
+            "Database unreachable"
+        );
+        assert_eq!(
+            cache
+                .get("pattern", "claude", "haiku")
+                .unwrap()
+                .unwrap()
+                .explanation,
+            "From claude"
+        );
+        assert_eq!(cache.stats().unwrap().entries, 2);
+    }
+
+    #[test]
+    fn test_ttl_expiry_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = AnalysisCache::open(dir.path().join("cache.db"))
+            .unwrap()
+            .with_ttl(Some(Duration::from_secs(3600)));
+
+        cache
+            .set("fresh", "openai", "gpt-4o", &sample_analysis())
+            .unwrap();
+        cache
+            .set("stale", "openai", "gpt-4o", &sample_analysis())
+            .unwrap();
+        cache
+            .conn
+            .execute(
+                "UPDATE analysis_cache SET created_at = created_at - 7200 WHERE pattern = 'stale'",
+                [],
+            )
+            .unwrap();
+
+        assert!(cache.get("fresh", "openai", "gpt-4o").unwrap().is_some());
+        assert!(cache.get("stale", "openai", "gpt-4o").unwrap().is_none());
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.expired, 1);
+
+        assert_eq!(cache.prune().unwrap(), 1);
+        assert_eq!(cache.clear().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_migrates_legacy_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute(
+                "CREATE TABLE analysis_cache (
+                    pattern TEXT PRIMARY KEY,
+                    provider TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    explanation TEXT NOT NULL,
+                    root_cause TEXT,
+                    suggestions TEXT NOT NULL,
+                    created_at INTEGER NOT NULL
+                )",
+                [],
+            )
+            .unwrap();
+        }
+
+        let cache = AnalysisCache::open(&path).unwrap();
+        cache
+            .set("pattern", "openai", "gpt-4o", &sample_analysis())
+            .unwrap();
+        assert!(cache.get("pattern", "openai", "gpt-4o").unwrap().is_some());
+    }
 }
@@ -42,6 +42,13 @@ pub struct AIConfig {
     pub analysis: AnalysisSettings,
     #[serde(default)]
     pub output: OutputSettings,
+    #[serde(default)]
+    pub parser: crate::parser::ParserConfig,
+    #[serde(default)]
+    pub grouping: crate::analyzer::GroupingConfig,
+    /// User-defined log formats (`[[formats]]` tables)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub formats: Vec<crate::parser::FormatDefinition>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -88,6 +95,8 @@ pub struct AnalysisSettings {
     pub max_backoff_ms: u64,
     #[serde(default = "default_enable_cache")]
     pub enable_cache: bool,
+    #[serde(default = "default_cache_ttl_days")]
+    pub cache_ttl_days: u64,
     #[serde(default = "default_truncate_length")]
     pub truncate_length: usize,
 }
@@ -101,6 +110,7 @@ impl Default for AnalysisSettings {
             initial_backoff_ms: default_initial_backoff_ms(),
             max_backoff_ms: default_max_backoff_ms(),
             enable_cache: default_enable_cache(),
+            cache_ttl_days: default_cache_ttl_days(),
             truncate_length: default_truncate_length(),
         }
     }
@@ -130,6 +140,10 @@ fn default_enable_cache() -> bool {
     true
 }
 
+fn default_cache_ttl_days() -> u64 {
+    crate::ai::cache::DEFAULT_CACHE_TTL_DAYS
+}
+
 fn default_truncate_length() -> usize {
     2000
 }
@@ -240,6 +254,11 @@ impl AIConfig {
                     .parse()
                     .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
             }
+            ["analysis", "cache_ttl_days"] => {
+                self.analysis.cache_ttl_days = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid cache TTL value: {}", value))?;
+            }
             ["analysis", "truncate_length"] => {
                 self.analysis.truncate_length = value
                     .parse()
@@ -261,6 +280,63 @@ impl AIConfig {
             ["output", "logs_dir"] => {
                 self.output.logs_dir = Some(value.to_string());
             }
+            ["parser", "default_format"] => {
+                self.parser.default_format = Some(value.to_string());
+            }
+            ["parser", "auto_detect"] => {
+                self.parser.auto_detect = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "enable_multiline"] => {
+                self.parser.enable_multiline = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "max_multiline_lines"] => {
+                self.parser.max_multiline_lines = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid line count: {}", value))?;
+            }
+            ["parser", "parallel_parsing"] => {
+                self.parser.parallel_parsing = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "chunk_size"] => {
+                self.parser.chunk_size = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid chunk size: {}", value))?;
+            }
+            ["parser", "num_threads"] => {
+                self.parser.num_threads = Some(
+                    value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid thread count: {}", value))?,
+                );
+            }
+            ["parser", "fallback_to_current_time"] => {
+                self.parser.fallback_to_current_time = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "extract_metadata"] => {
+                self.parser.extract_metadata = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "skip_invalid_lines"] => {
+                self.parser.skip_invalid_lines = value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid boolean value: {}", value))?;
+            }
+            ["parser", "max_parse_errors"] => {
+                self.parser.max_parse_errors = Some(
+                    value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid error count: {}", value))?,
+                );
+            }
             [provider, "api_key"] => {
                 let config = self
                     .providers
@@ -396,10 +472,18 @@ impl AIConfig {
             initial_backoff_ms: self.analysis.initial_backoff_ms,
             max_backoff_ms: self.analysis.max_backoff_ms,
             enable_cache: self.analysis.enable_cache,
+            cache_ttl_days: self.analysis.cache_ttl_days,
             truncate_length: self.analysis.truncate_length,
         }
     }
 
+    /// Parser settings including the top-level `[[formats]]` tables
+    pub fn parser_config(&self) -> crate::parser::ParserConfig {
+        let mut config = self.parser.clone();
+        config.formats.extend(self.formats.iter().cloned());
+        config
+    }
+
     /// Display configuration in a readable format
     pub fn display(&self) -> String {
         let mut output = String::new();
@@ -455,11 +539,53 @@ impl AIConfig {
             self.analysis.max_backoff_ms
         ));
         output.push_str(&format!("  enable_cache: {}\n", self.analysis.enable_cache));
+        output.push_str(&format!(
+            "  cache_ttl_days: {}\n",
+            self.analysis.cache_ttl_days
+        ));
         output.push_str(&format!(
             "  truncate_length: {}\n\n",
             self.analysis.truncate_length
         ));
 
+        // Parser settings
+        output.push_str("Parser Settings:\n");
+        if let Some(format) = &self.parser.default_format {
+            output.push_str(&format!("  default_format: {}\n", format));
+        }
+        output.push_str(&format!(
+            "  enable_multiline: {}\n",
+            self.parser.enable_multiline
+        ));
+        output.push_str(&format!(
+            "  max_multiline_lines: {}\n",
+            self.parser.max_multiline_lines
+        ));
+        output.push_str(&format!(
+            "  parallel_parsing: {}\n",
+            self.parser.parallel_parsing
+        ));
+        output.push_str(&format!("  chunk_size: {}\n", self.parser.chunk_size));
+        if !self.parser.custom_timestamp_formats.is_empty() {
+            output.push_str(&format!(
+                "  custom_timestamp_formats: {}\n",
+                self.parser.custom_timestamp_formats.join(", ")
+            ));
+        }
+        output.push_str(&format!(
+            "  skip_invalid_lines: {}\n",
+            self.parser.skip_invalid_lines
+        ));
+        if let Some(max) = self.parser.max_parse_errors {
+            output.push_str(&format!("  max_parse_errors: {}\n", max));
+        }
+        let formats = self.parser_config().formats;
+        if !formats.is_empty() {
+            let names: Vec<&str> = formats.iter().map(|f| f.name.as_str()).collect();
+            output.push_str(&format!("  custom formats: {}\n", names.join(", ")));
+        }
+        output.push('\n');
+
         // Providers
         if !self.providers.is_empty() {
             output.push_str("AI Providers:\n");
@@ -580,4 +706,51 @@ mod tests {
         assert!(display.contains("enable_retry: true"));
         assert!(display.contains("max_retries: 3"));
     }
+
+    #[test]
+    fn test_parser_section() {
+        let config: AIConfig = toml::from_str(
+            r#"
+            [parser]
+            chunk_size = 500
+            custom_timestamp_formats = ["%d/%m/%Y %H:%M:%S"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.parser.chunk_size, 500);
+        assert_eq!(config.parser.custom_timestamp_formats.len(), 1);
+        assert!(config.parser.enable_multiline);
+
+        let mut config = AIConfig::default();
+        config.set_value("parser.max_parse_errors", "10").unwrap();
+        config
+            .set_value("parser.enable_multiline", "false")
+            .unwrap();
+        assert_eq!(config.parser.max_parse_errors, Some(10));
+        assert!(!config.parser.enable_multiline);
+        assert!(config.set_value("parser.chunk_size", "lots").is_err());
+        assert!(config.display().contains("Parser Settings:"));
+    }
+
+    #[test]
+    fn test_formats_tables() {
+        let config: AIConfig = toml::from_str(
+            r#"
+            [[formats]]
+            name = "inhouse"
+            grok = "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{GREEDYDATA:message}"
+
+            [[formats]]
+            name = "legacy"
+            pattern = '^<(?P<level>\w+)> (?P<message>.*)$'
+            "#,
+        )
+        .unwrap();
+
+        let parser = config.parser_config();
+        assert_eq!(parser.formats.len(), 2);
+        assert!(parser.format("legacy").is_some());
+        assert!(parser.validate().is_ok());
+        assert!(config.display().contains("custom formats: inhouse, legacy"));
+    }
 }
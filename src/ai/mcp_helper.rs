@@ -1,83 +1,275 @@
-use crate::mcp::{MCPClient, ToolInvocationRecord};
-use crate::types::ErrorGroup;
-use chrono::Utc;
-
-/// Invoke relevant MCP tools based on error content
-pub async fn invoke_relevant_tools(
-    client: &MCPClient,
-    _group: &ErrorGroup,
-) -> Vec<ToolInvocationRecord> {
-    let mut results = Vec::new();
-
-    // Get available tools
-    let available_tools = client.available_tools();
-    log::debug!("MCP: Found {} available tools", available_tools.len());
-
-    // For now, try to invoke simple tools that don't require parameters
-    // In the future, we'll have smarter logic to determine which tools to invoke based on:
-    // - Error patterns (stack traces -> search_code)
-    // - Timestamps (-> check_metrics)
-    // - Error messages (-> search_docs)
-    // - Recurring patterns (-> query_logs)
-
-    for tool in available_tools {
-        // Only invoke simple tools that don't require parameters for now
-        if tool.name == "list_allowed_directories" {
-            log::debug!("MCP: Invoking tool: {}", tool.name);
-            let params = serde_json::json!({});
-
-            match client.invoke_tool(&tool.name, params).await {
-                Ok(result) => {
-                    log::debug!(
-                        "MCP: Tool {} succeeded in {}ms",
-                        tool.name,
-                        result.metadata.duration_ms
-                    );
-                    results.push(ToolInvocationRecord {
-                        tool_name: tool.name.clone(),
-                        invoked_at: Utc::now(),
-                        duration_ms: result.metadata.duration_ms,
-                        result,
-                    });
-                }
-                Err(e) => {
-                    log::warn!("MCP: Failed to invoke tool {}: {}", tool.name, e);
-                }
+//! Model-driven MCP tool calling.
+//!
+//! Providers with native tool calling hand the discovered MCP tool schemas to
+//! the model, execute the calls it requests through a [`ToolSession`], and feed
+//! the results back until the model produces its final analysis or the round
+//! budget runs out.
+
+use crate::mcp::{MCPClient, MCPError, ToolInfo, ToolInvocationSummary};
+use serde_json::{json, Value};
+use std::time::Instant;
+
+/// Maximum number of model turns that may request tool calls
+pub const MAX_TOOL_ROUNDS: usize = 5;
+
+/// Maximum characters of tool output fed back to the model per call
+const MAX_TOOL_OUTPUT_CHARS: usize = 8000;
+
+/// Instructions appended to the analysis prompt when tools are offered
+const TOOL_INSTRUCTIONS: &str = "\n\nYou can call the provided tools to gather more context \
+(for example source code, configuration or related logs) before answering. \
+Only call tools that are likely to help explain this error. \
+When you are done, reply with the JSON object described above and nothing else.";
+
+/// A tool call requested by the model
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// Provider-assigned call id, echoed back with the result
+    pub id: String,
+    /// Name of the tool to invoke
+    pub name: String,
+    /// Arguments as a JSON object
+    pub arguments: Value,
+}
+
+/// Result of a tool call, ready to be sent back to the model
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// Executes tool calls for a single analysis and records what was invoked
+pub struct ToolSession<'a> {
+    client: &'a MCPClient,
+    tools: Vec<&'a ToolInfo>,
+    invocations: Vec<ToolInvocationSummary>,
+}
+
+impl<'a> ToolSession<'a> {
+    /// Create a session exposing every tool discovered by `client`
+    pub fn new(client: &'a MCPClient) -> Self {
+        let mut tools = client.available_tools();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            client,
+            tools,
+            invocations: Vec::new(),
+        }
+    }
+
+    /// Whether there is anything to offer the model
+    pub fn has_tools(&self) -> bool {
+        !self.tools.is_empty()
+    }
+
+    /// Tools offered to the model, sorted by name
+    pub fn tools(&self) -> &[&'a ToolInfo] {
+        &self.tools
+    }
+
+    /// Tool definitions in the OpenAI chat completions format (also used by Ollama)
+    pub fn openai_tools(&self) -> Vec<Value> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": input_schema(tool),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Tool definitions in the Anthropic messages format
+    pub fn claude_tools(&self) -> Vec<Value> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": input_schema(tool),
+                })
+            })
+            .collect()
+    }
+
+    /// Invoke a tool requested by the model and record the invocation.
+    ///
+    /// Failures are returned to the model as error output rather than
+    /// aborting the analysis, so it can recover or answer without the tool.
+    pub async fn execute(&mut self, call: &ToolCall) -> ToolOutput {
+        log::debug!(
+            "MCP: Model requested tool {} ({})",
+            call.name,
+            call.arguments
+        );
+
+        let start = Instant::now();
+        let arguments = if call.arguments.is_null() {
+            json!({})
+        } else {
+            call.arguments.clone()
+        };
+
+        let (status, output) = match self.client.invoke_tool(&call.name, arguments).await {
+            Ok(result) if result.success => {
+                let data = result.data.unwrap_or(Value::Null);
+                let is_error = data
+                    .get("isError")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let status = if is_error { "failure" } else { "success" };
+                (
+                    status,
+                    ToolOutput {
+                        content: result_text(&data),
+                        is_error,
+                    },
+                )
+            }
+            Ok(result) => (
+                "failure",
+                ToolOutput {
+                    content: result
+                        .error
+                        .unwrap_or_else(|| "Tool invocation failed".to_string()),
+                    is_error: true,
+                },
+            ),
+            Err(e) => {
+                let status = match e {
+                    MCPError::ToolTimeout { .. } => "timeout",
+                    _ => "failure",
+                };
+                (
+                    status,
+                    ToolOutput {
+                        content: e.to_string(),
+                        is_error: true,
+                    },
+                )
             }
+        };
+
+        log::debug!("MCP: Tool {} finished with status {}", call.name, status);
+
+        self.invocations.push(ToolInvocationSummary {
+            tool: call.name.clone(),
+            status: status.to_string(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            contributed_to: vec![],
+        });
+
+        ToolOutput {
+            content: truncate_output(output.content),
+            is_error: output.is_error,
         }
     }
 
-    log::debug!("MCP: Invoked {} tools total", results.len());
-    results
+    /// Consume the session, returning the recorded invocations
+    pub fn into_invocations(self) -> Vec<ToolInvocationSummary> {
+        self.invocations
+    }
 }
 
-/// Build enhanced prompt with tool results
-pub fn augment_prompt_with_tools(
-    base_prompt: String,
-    tool_results: &[ToolInvocationRecord],
-) -> String {
-    let mut prompt = base_prompt;
-
-    if !tool_results.is_empty() {
-        prompt.push_str("\n\n## Additional Context from MCP Tools:\n");
-        for result in tool_results {
-            if result.result.success {
-                if let Some(data) = &result.result.data {
-                    prompt.push_str(&format!(
-                        "\n### Tool: {} ({}ms)\n{}\n",
-                        result.tool_name,
-                        result.duration_ms,
-                        serde_json::to_string_pretty(data).unwrap_or_default()
-                    ));
-                }
-            } else if let Some(error) = &result.result.error {
-                prompt.push_str(&format!(
-                    "\n### Tool: {} (failed)\nError: {}\n",
-                    result.tool_name, error
-                ));
-            }
+/// Append tool-use instructions to an analysis prompt
+pub fn with_tool_instructions(prompt: String) -> String {
+    prompt + TOOL_INSTRUCTIONS
+}
+
+/// JSON schema for a tool's input, defaulting to an empty object schema
+pub fn input_schema(tool: &ToolInfo) -> Value {
+    match &tool.parameters {
+        Value::Object(schema) if !schema.is_empty() => tool.parameters.clone(),
+        _ => json!({"type": "object", "properties": {}}),
+    }
+}
+
+/// Flatten an MCP `tools/call` result into text for the model
+fn result_text(data: &Value) -> String {
+    let text: Vec<&str> = data
+        .get("content")
+        .and_then(|c| c.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if text.is_empty() {
+        serde_json::to_string(data).unwrap_or_default()
+    } else {
+        text.join("\n")
+    }
+}
+
+fn truncate_output(mut content: String) -> String {
+    if content.len() > MAX_TOOL_OUTPUT_CHARS {
+        let mut end = MAX_TOOL_OUTPUT_CHARS;
+        while !content.is_char_boundary(end) {
+            end -= 1;
         }
+        content.truncate(end);
+        content.push_str("\n... (truncated)");
     }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    prompt
+    fn tool(parameters: Value) -> ToolInfo {
+        ToolInfo {
+            name: "read_file".to_string(),
+            description: "Read a file".to_string(),
+            parameters,
+            server: "fs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_input_schema_defaults_to_object() {
+        assert_eq!(
+            input_schema(&tool(json!({}))),
+            json!({"type": "object", "properties": {}})
+        );
+
+        let schema = json!({"type": "object", "properties": {"path": {"type": "string"}}});
+        assert_eq!(input_schema(&tool(schema.clone())), schema);
+    }
+
+    #[test]
+    fn test_result_text_joins_text_blocks() {
+        let data = json!({
+            "content": [
+                {"type": "text", "text": "line one"},
+                {"type": "image", "data": "..."},
+                {"type": "text", "text": "line two"}
+            ]
+        });
+        assert_eq!(result_text(&data), "line one\nline two");
+
+        let data = json!({"rows": 3});
+        assert_eq!(result_text(&data), r#"{"rows":3}"#);
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let long = "é".repeat(MAX_TOOL_OUTPUT_CHARS);
+        let truncated = truncate_output(long);
+        assert!(truncated.len() <= MAX_TOOL_OUTPUT_CHARS + 20);
+        assert!(truncated.ends_with("(truncated)"));
+
+        assert_eq!(truncate_output("short".to_string()), "short");
+    }
 }
@@ -80,6 +80,18 @@ pub fn create_provider(
 ) -> Result<Arc<dyn AIProvider>> {
     // Load config file
     let config = AIConfig::load().unwrap_or_default();
+    create_provider_with_config(&config, provider_name, api_key, model, host, region)
+}
+
+/// Like [`create_provider`], with provider settings taken from an already loaded `config`
+pub fn create_provider_with_config(
+    config: &AIConfig,
+    provider_name: &str,
+    api_key: Option<String>,
+    model: Option<String>,
+    host: Option<String>,
+    region: Option<String>,
+) -> Result<Arc<dyn AIProvider>> {
     let provider_config = config.get_provider(provider_name);
 
     match provider_name.to_lowercase().as_str() {
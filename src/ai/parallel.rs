@@ -1,7 +1,8 @@
 //! Parallel AI analysis infrastructure for processing multiple error groups concurrently.
 //!
 //! This module provides the core parallel processing capabilities for AI analysis,
-//! including concurrency control, result ordering, and progress tracking.
+//! including concurrency control, result ordering, response caching, and
+//! progress tracking.
 //!
 //! # Example
 //!
@@ -24,8 +25,13 @@
 //! # }
 //! ```
 
+use crate::ai::cache::{AnalysisCache, DEFAULT_CACHE_TTL_DAYS};
 use crate::ai::progress::ProgressUpdate;
 use crate::ai::provider::AIProvider;
+use crate::ai::retry::RetryableAnalyzer;
+use crate::ai::statistics::AnalysisStatistics;
+use crate::analyzer::fingerprint::is_mined_id;
+use crate::mcp::MCPClient;
 use crate::types::ErrorGroup;
 use crate::Result;
 use std::sync::Arc;
@@ -46,6 +52,8 @@ pub struct AnalysisConfig {
     pub max_backoff_ms: u64,
     /// Enable response caching
     pub enable_cache: bool,
+    /// How long cached responses stay valid, in days (0 = forever)
+    pub cache_ttl_days: u64,
     /// Maximum message length before truncation
     pub truncate_length: usize,
 }
@@ -59,6 +67,7 @@ impl Default for AnalysisConfig {
             initial_backoff_ms: 1000,
             max_backoff_ms: 30000,
             enable_cache: true,
+            cache_ttl_days: DEFAULT_CACHE_TTL_DAYS,
             truncate_length: 2000,
         }
     }
@@ -81,45 +90,130 @@ impl AnalysisConfig {
     }
 }
 
+/// Response cache bound to the model it was populated with
+struct CacheBinding {
+    cache: std::sync::Mutex<AnalysisCache>,
+    model: String,
+}
+
 /// Parallel analyzer for processing error groups concurrently
 pub struct ParallelAnalyzer {
     provider: Arc<dyn AIProvider>,
+    retry: Arc<RetryableAnalyzer>,
     semaphore: Arc<Semaphore>,
-    #[allow(dead_code)]
     config: AnalysisConfig,
+    cache: Option<CacheBinding>,
 }
 
 impl ParallelAnalyzer {
     /// Create a new parallel analyzer
     pub fn new(provider: Arc<dyn AIProvider>, config: AnalysisConfig) -> Self {
         let semaphore = Arc::new(Semaphore::new(config.max_concurrency));
+        let retry = Arc::new(Self::retry_analyzer(&provider, &config));
 
         Self {
             provider,
+            retry,
             semaphore,
             config,
+            cache: None,
         }
     }
 
+    /// Let the provider call tools from the connected MCP servers.
+    ///
+    /// Providers with native tool calling offer the discovered tools to the
+    /// model; others ignore the client and analyze as usual.
+    pub fn with_mcp_client(mut self, client: Arc<MCPClient>) -> Self {
+        self.retry =
+            Arc::new(Self::retry_analyzer(&self.provider, &self.config).with_mcp_client(client));
+        self
+    }
+
+    fn retry_analyzer(
+        provider: &Arc<dyn AIProvider>,
+        config: &AnalysisConfig,
+    ) -> RetryableAnalyzer {
+        // With retries disabled every group gets exactly one attempt
+        let max_retries = if config.enable_retry {
+            config.max_retries
+        } else {
+            0
+        };
+        RetryableAnalyzer::new(
+            Arc::clone(provider),
+            max_retries,
+            config.initial_backoff_ms,
+            config.max_backoff_ms,
+        )
+    }
+
+    /// Consult and populate `cache` for groups analyzed by this provider.
+    ///
+    /// Entries are keyed by error group, provider name and the provider's
+    /// resolved model. The cache is ignored when `enable_cache` is false in the
+    /// analysis config.
+    pub fn with_cache(mut self, cache: AnalysisCache) -> Self {
+        let ttl = match self.config.cache_ttl_days {
+            0 => None,
+            days => Some(std::time::Duration::from_secs(days * 86400)),
+        };
+
+        self.cache = Some(CacheBinding {
+            cache: std::sync::Mutex::new(cache.with_ttl(ttl)),
+            model: self.provider.model().to_string(),
+        });
+        self
+    }
+
     /// Analyze multiple error groups in parallel
     pub async fn analyze_groups<F>(
         &self,
         groups: &mut [ErrorGroup],
         progress_callback: F,
-    ) -> Result<()>
+    ) -> Result<AnalysisStatistics>
     where
         F: Fn(ProgressUpdate) + Send + Sync + 'static,
     {
-        let total = groups.len();
         let start_time = std::time::Instant::now();
+        let mut stats = AnalysisStatistics::new();
+        let cache = self.cache.as_ref().filter(|_| self.config.enable_cache);
+
+        // Serve what we can from the cache; only misses go to the provider
+        let mut pending = Vec::new();
+        for (index, group) in groups.iter_mut().enumerate() {
+            if let Some(binding) = cache {
+                let cached = binding.cache.lock().unwrap().get(
+                    Self::cache_key(group),
+                    self.provider.name(),
+                    &binding.model,
+                );
+                match cached {
+                    Ok(Some(analysis)) => {
+                        group.analysis = Some(analysis);
+                        stats.record_cache_hit();
+                        continue;
+                    }
+                    Ok(None) => stats.record_cache_miss(),
+                    Err(e) => {
+                        log::warn!("Cache lookup failed for group {}: {}", group.id, e);
+                        stats.record_cache_miss();
+                    }
+                }
+            }
+            pending.push(index);
+        }
+
+        let total = pending.len();
         let completed = Arc::new(Mutex::new(0usize));
         let callback = Arc::new(progress_callback);
 
         // Create tasks for each group with index to maintain ordering
         let mut tasks = Vec::new();
 
-        for (index, group) in groups.iter().enumerate() {
-            let provider = Arc::clone(&self.provider);
+        for index in pending {
+            let group = &groups[index];
+            let retry = Arc::clone(&self.retry);
             let semaphore = Arc::clone(&self.semaphore);
             let completed = Arc::clone(&completed);
             let callback = Arc::clone(&callback);
@@ -130,8 +224,10 @@ impl ParallelAnalyzer {
                 // Acquire semaphore permit to limit concurrency
                 let _permit = semaphore.acquire().await.unwrap();
 
-                // Analyze the group
-                let result = provider.analyze(&group_clone).await;
+                // Analyze the group, retrying transient failures
+                let analysis_start = std::time::Instant::now();
+                let (result, attempts) = retry.analyze_with_attempts(&group_clone).await;
+                let duration = analysis_start.elapsed();
 
                 // Update progress
                 let mut count = completed.lock().await;
@@ -143,35 +239,56 @@ impl ParallelAnalyzer {
 
                 callback(ProgressUpdate::new(current, total, pattern, elapsed));
 
-                (index, result)
+                (index, result, duration, attempts)
             });
 
-            tasks.push(task);
+            tasks.push((index, task));
         }
 
         // Wait for all tasks to complete and collect results
         let mut results = Vec::new();
-        for task in tasks {
+        for (index, task) in tasks {
             match task.await {
-                Ok((index, result)) => results.push((index, result)),
+                Ok(result) => results.push(result),
                 Err(e) => {
-                    log::error!("Task failed: {}", e);
+                    // The group stays unanalyzed, so it counts as a failure
+                    let reason = if e.is_panic() {
+                        "Analysis task panicked"
+                    } else {
+                        "Analysis task was cancelled"
+                    };
+                    stats.record_failure(reason.to_string(), 1);
+                    log::error!("Analysis of group {} failed: {}", groups[index].id, e);
                 }
             }
         }
 
         // Sort results by index to maintain original ordering
-        results.sort_by_key(|(index, _)| *index);
+        results.sort_by_key(|(index, ..)| *index);
 
         // Apply results back to groups
-        for (index, result) in results {
+        for (index, result, duration, attempts) in results {
             if let Ok(analysis) = result {
+                stats.record_success_after(duration, attempts);
+                if let Some(binding) = cache {
+                    let group = &groups[index];
+                    if let Err(e) = binding.cache.lock().unwrap().set(
+                        Self::cache_key(group),
+                        self.provider.name(),
+                        &binding.model,
+                        &analysis,
+                    ) {
+                        log::warn!("Failed to cache analysis for group {}: {}", group.id, e);
+                    }
+                }
                 groups[index].analysis = Some(analysis);
             } else if let Err(e) = result {
+                stats.record_failure(Self::failure_reason(&e), attempts);
                 let group = &groups[index];
-                log::debug!(
-                    "Failed to analyze group {} (pattern: {}, count: {}): {}",
+                log::warn!(
+                    "Failed to analyze group {} after {} attempt(s) (pattern: {}, count: {}): {}",
                     group.id,
+                    attempts,
                     group.pattern,
                     group.count,
                     e
@@ -181,7 +298,31 @@ impl ParallelAnalyzer {
             }
         }
 
-        Ok(())
+        // Report wall-clock time; the average stays per request
+        stats.total_duration = start_time.elapsed();
+        stats.calculate_throughput(stats.total_duration);
+
+        Ok(stats)
+    }
+
+    /// What identifies `group` in the response cache.
+    ///
+    /// Fingerprint ids also cover a stack trace's exception and frames, which
+    /// the pattern alone does not (Drain mines it from the first line only).
+    /// Drain template ids change between runs, so those groups, which have
+    /// no stack trace, are cached by their template.
+    fn cache_key(group: &ErrorGroup) -> &str {
+        if is_mined_id(&group.id) {
+            &group.pattern
+        } else {
+            &group.id
+        }
+    }
+
+    /// Short, single-line failure reason suitable for grouping in statistics
+    fn failure_reason(error: &anyhow::Error) -> String {
+        let reason = error.to_string();
+        reason.lines().next().unwrap_or_default().trim().to_string()
     }
 }
 
@@ -37,11 +37,9 @@ impl ProgressUpdate {
         };
 
         let bar_width = 30;
-        let filled = if self.total > 0 {
-            (bar_width * self.current) / self.total
-        } else {
-            0
-        };
+        let filled = (bar_width * self.current)
+            .checked_div(self.total)
+            .unwrap_or(0);
         let empty = bar_width.saturating_sub(filled);
 
         let bar = format!("[{}{}]", "█".repeat(filled), "░".repeat(empty));
@@ -94,8 +94,10 @@ mod tests {
                     extra: std::collections::HashMap::new(),
                 },
                 raw: "".to_string(),
+                source: None,
             }],
             analysis: None,
+            sources: Vec::new(),
         }
     }
 
@@ -151,6 +153,7 @@ mod tests {
                 extra: std::collections::HashMap::new(),
             },
             raw: "".to_string(),
+            source: None,
         });
 
         let prompt = build_enhanced_analysis_prompt(&group, 2000);
@@ -174,6 +177,7 @@ mod tests {
                     extra: std::collections::HashMap::new(),
                 },
                 raw: "".to_string(),
+                source: None,
             });
         }
 
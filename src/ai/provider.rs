@@ -18,6 +18,14 @@ pub trait AIProvider: Send + Sync {
     }
 
     fn name(&self) -> &str;
+
+    /// Model requests are sent to, after defaults are applied.
+    ///
+    /// Part of the response cache key; providers without a choice of model
+    /// can keep the default, their name.
+    fn model(&self) -> &str {
+        self.name()
+    }
 }
 
 pub struct NoAI;
@@ -1,4 +1,5 @@
 use crate::ai::json_extractor::EnhancedJsonExtractor;
+use crate::ai::mcp_helper::{self, ToolCall, ToolSession, MAX_TOOL_ROUNDS};
 use crate::ai::prompts::build_enhanced_analysis_prompt;
 use crate::ai::provider::AIProvider;
 use crate::types::{ErrorAnalysis, ErrorGroup};
@@ -6,8 +7,13 @@ use crate::Result;
 use anyhow::anyhow;
 use async_trait::async_trait;
 use aws_sdk_bedrockruntime::config::ProvideCredentials;
-use aws_sdk_bedrockruntime::types::{ContentBlock, ConversationRole, Message};
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, Message, Tool, ToolConfiguration, ToolInputSchema,
+    ToolResultBlock, ToolResultContentBlock, ToolResultStatus, ToolSpecification,
+};
 use aws_sdk_bedrockruntime::Client as BedrockClient;
+use aws_smithy_types::{Document, Number};
+use std::collections::HashMap;
 use thiserror::Error;
 
 /// Bedrock-specific errors
@@ -162,6 +168,27 @@ impl BedrockProvider {
                 anyhow!("Failed to build message: {}", e)
             })?;
 
+        let message = self.converse(vec![message], None).await?;
+
+        let content = message
+            .content()
+            .first()
+            .ok_or_else(|| anyhow!("No content in message"))?;
+
+        let text = match content.as_text() {
+            Ok(t) => t,
+            Err(_) => return Err(anyhow!("Content is not text")),
+        };
+
+        Ok(text.to_string())
+    }
+
+    /// Send a conversation to the Converse API and return the reply message
+    async fn converse(
+        &self,
+        messages: Vec<Message>,
+        tool_config: Option<ToolConfiguration>,
+    ) -> Result<Message> {
         log::debug!(
             "Sending request to Bedrock API (max_tokens: {}, temperature: {})",
             self.max_tokens,
@@ -172,7 +199,8 @@ impl BedrockProvider {
             .client
             .converse()
             .model_id(&self.model_id)
-            .messages(message)
+            .set_messages(Some(messages))
+            .set_tool_config(tool_config)
             .inference_config(
                 aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
                     .max_tokens(self.max_tokens)
@@ -189,49 +217,124 @@ impl BedrockProvider {
 
         log::debug!("Received response from Bedrock API");
 
-        // Extract text from response
         let output = response
             .output()
             .ok_or_else(|| anyhow!("No output in Bedrock response"))?;
 
-        let message = match output.as_message() {
-            Ok(msg) => msg,
-            Err(_) => return Err(anyhow!("Response is not a message")),
-        };
+        match output.as_message() {
+            Ok(msg) => Ok(msg.clone()),
+            Err(_) => Err(anyhow!("Response is not a message")),
+        }
+    }
 
-        let content = message
-            .content()
-            .first()
-            .ok_or_else(|| anyhow!("No content in message"))?;
+    /// Run the analysis as a conversation in which the model may call MCP tools
+    async fn analyze_with_session(
+        &self,
+        group: &ErrorGroup,
+        session: &mut ToolSession<'_>,
+    ) -> Result<ErrorAnalysis> {
+        let tool_config = Self::tool_configuration(session)?;
+        let prompt =
+            mcp_helper::with_tool_instructions(build_enhanced_analysis_prompt(group, 2000));
+        let mut messages = vec![Message::builder()
+            .role(ConversationRole::User)
+            .content(ContentBlock::Text(prompt))
+            .build()
+            .map_err(|e| anyhow!("Failed to build message: {}", e))?];
+
+        for round in 0..=MAX_TOOL_ROUNDS {
+            let reply = self
+                .converse(messages.clone(), Some(tool_config.clone()))
+                .await?;
+
+            let calls: Vec<ToolCall> = reply
+                .content()
+                .iter()
+                .filter_map(|block| block.as_tool_use().ok())
+                .map(|tool_use| ToolCall {
+                    id: tool_use.tool_use_id().to_string(),
+                    name: tool_use.name().to_string(),
+                    arguments: document_to_json(tool_use.input()),
+                })
+                .collect();
+
+            if calls.is_empty() {
+                let text: String = reply
+                    .content()
+                    .iter()
+                    .filter_map(|block| block.as_text().ok())
+                    .map(String::as_str)
+                    .collect();
+                return self.parse_response(group, &text);
+            }
+            if round == MAX_TOOL_ROUNDS {
+                break;
+            }
+            messages.push(reply);
+
+            let mut results = Vec::new();
+            for call in &calls {
+                let output = session.execute(call).await;
+                let status = if output.is_error {
+                    ToolResultStatus::Error
+                } else {
+                    ToolResultStatus::Success
+                };
+                let result = ToolResultBlock::builder()
+                    .tool_use_id(&call.id)
+                    .content(ToolResultContentBlock::Text(output.content))
+                    .status(status)
+                    .build()
+                    .map_err(|e| anyhow!("Failed to build tool result: {}", e))?;
+                results.push(ContentBlock::ToolResult(result));
+            }
 
-        let text = match content.as_text() {
-            Ok(t) => t,
-            Err(_) => return Err(anyhow!("Content is not text")),
-        };
+            // Converse cannot disable tools, so ask for the answer explicitly
+            if round + 1 == MAX_TOOL_ROUNDS {
+                results.push(ContentBlock::Text(
+                    "No more tool calls are available. Reply with the final JSON analysis now."
+                        .to_string(),
+                ));
+            }
 
-        Ok(text.to_string())
-    }
-}
+            messages.push(
+                Message::builder()
+                    .role(ConversationRole::User)
+                    .set_content(Some(results))
+                    .build()
+                    .map_err(|e| anyhow!("Failed to build message: {}", e))?,
+            );
+        }
 
-#[async_trait]
-impl AIProvider for BedrockProvider {
-    async fn analyze(&self, group: &ErrorGroup) -> Result<ErrorAnalysis> {
-        log::debug!(
-            "Analyzing error group: {} (count: {})",
-            group.pattern,
-            group.count
-        );
+        Err(anyhow!(
+            "Bedrock kept requesting tools after {} rounds",
+            MAX_TOOL_ROUNDS
+        ))
+    }
 
-        // Build the analysis prompt
-        let prompt = build_enhanced_analysis_prompt(group, 2000);
-        log::debug!("Built analysis prompt ({} chars)", prompt.len());
+    /// Describe the session's MCP tools for the Converse API
+    fn tool_configuration(session: &ToolSession<'_>) -> Result<ToolConfiguration> {
+        let mut builder = ToolConfiguration::builder();
+
+        for tool in session.tools() {
+            let schema = mcp_helper::input_schema(tool);
+            let spec = ToolSpecification::builder()
+                .name(&tool.name)
+                .description(&tool.description)
+                .input_schema(ToolInputSchema::Json(json_to_document(&schema)))
+                .build()
+                .map_err(|e| anyhow!("Failed to build tool specification: {}", e))?;
+            builder = builder.tools(Tool::ToolSpec(spec));
+        }
 
-        // Call Bedrock API
-        let response_text = self.call_api(prompt).await?;
-        log::debug!("Received response ({} chars)", response_text.len());
+        builder
+            .build()
+            .map_err(|e| anyhow!("Failed to build tool configuration: {}", e))
+    }
 
-        // Extract and parse JSON from response
-        let json_str = match EnhancedJsonExtractor::extract(&response_text) {
+    /// Extract and parse the JSON analysis from a response
+    fn parse_response(&self, group: &ErrorGroup, response_text: &str) -> Result<ErrorAnalysis> {
+        let json_str = match EnhancedJsonExtractor::extract(response_text) {
             Ok(json) => {
                 log::debug!("Extracted JSON ({} chars)", json.len());
                 json
@@ -250,10 +353,10 @@ impl AIProvider for BedrockProvider {
             }
         };
 
-        let analysis: ErrorAnalysis = match serde_json::from_str(&json_str) {
+        match serde_json::from_str(&json_str) {
             Ok(analysis) => {
                 log::debug!("Successfully parsed ErrorAnalysis");
-                analysis
+                Ok(analysis)
             }
             Err(e) => {
                 log::debug!(
@@ -262,16 +365,102 @@ impl AIProvider for BedrockProvider {
                     e
                 );
                 log::debug!("Extracted JSON string:\n{}", json_str);
-                return Err(anyhow!("Failed to parse JSON into ErrorAnalysis: {}", e));
+                Err(anyhow!("Failed to parse JSON into ErrorAnalysis: {}", e))
+            }
+        }
+    }
+}
+
+/// Convert a JSON value into a Smithy document
+fn json_to_document(value: &serde_json::Value) -> Document {
+    match value {
+        serde_json::Value::Null => Document::Null,
+        serde_json::Value::Bool(b) => Document::Bool(*b),
+        serde_json::Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                Document::Number(Number::PosInt(u))
+            } else if let Some(i) = n.as_i64() {
+                Document::Number(Number::NegInt(i))
+            } else {
+                Document::Number(Number::Float(n.as_f64().unwrap_or_default()))
             }
+        }
+        serde_json::Value::String(s) => Document::String(s.clone()),
+        serde_json::Value::Array(items) => {
+            Document::Array(items.iter().map(json_to_document).collect())
+        }
+        serde_json::Value::Object(map) => Document::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_to_document(v)))
+                .collect::<HashMap<_, _>>(),
+        ),
+    }
+}
+
+/// Convert a Smithy document into a JSON value
+fn document_to_json(document: &Document) -> serde_json::Value {
+    match document {
+        Document::Null => serde_json::Value::Null,
+        Document::Bool(b) => serde_json::Value::Bool(*b),
+        Document::Number(Number::PosInt(u)) => serde_json::Value::from(*u),
+        Document::Number(Number::NegInt(i)) => serde_json::Value::from(*i),
+        Document::Number(Number::Float(f)) => serde_json::Value::from(*f),
+        Document::String(s) => serde_json::Value::String(s.clone()),
+        Document::Array(items) => items.iter().map(document_to_json).collect(),
+        Document::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), document_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[async_trait]
+impl AIProvider for BedrockProvider {
+    async fn analyze(&self, group: &ErrorGroup) -> Result<ErrorAnalysis> {
+        log::debug!(
+            "Analyzing error group: {} (count: {})",
+            group.pattern,
+            group.count
+        );
+
+        // Build the analysis prompt
+        let prompt = build_enhanced_analysis_prompt(group, 2000);
+        log::debug!("Built analysis prompt ({} chars)", prompt.len());
+
+        // Call Bedrock API
+        let response_text = self.call_api(prompt).await?;
+        log::debug!("Received response ({} chars)", response_text.len());
+
+        self.parse_response(group, &response_text)
+    }
+
+    async fn analyze_with_tools(
+        &self,
+        group: &ErrorGroup,
+        mcp_client: Option<&crate::mcp::MCPClient>,
+    ) -> Result<ErrorAnalysis> {
+        let Some(client) = mcp_client else {
+            return self.analyze(group).await;
         };
 
+        let mut session = ToolSession::new(client);
+        if !session.has_tools() {
+            return self.analyze(group).await;
+        }
+
+        let mut analysis = self.analyze_with_session(group, &mut session).await?;
+        analysis.tool_invocations = session.into_invocations();
         Ok(analysis)
     }
 
     fn name(&self) -> &str {
         "bedrock"
     }
+
+    fn model(&self) -> &str {
+        &self.model_id
+    }
 }
 
 #[cfg(test)]
@@ -337,6 +526,18 @@ mod tests {
         assert!(error.to_string().contains("AWS credentials not found"));
     }
 
+    #[test]
+    fn test_document_round_trip() {
+        let value = serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"],
+            "limits": [1, -2, 0.5, true, null]
+        });
+
+        assert_eq!(document_to_json(&json_to_document(&value)), value);
+    }
+
     #[test]
     fn test_configuration_validation() {
         // Test valid model IDs
@@ -1,3 +1,4 @@
+use crate::ai::mcp_helper::{self, ToolCall, ToolSession, MAX_TOOL_ROUNDS};
 use crate::ai::prompts::build_analysis_prompt;
 use crate::ai::provider::AIProvider;
 use crate::types::{ErrorAnalysis, ErrorGroup, Suggestion};
@@ -7,33 +8,55 @@ use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+
 pub struct ClaudeProvider {
     client: Client,
     api_key: String,
     model: String,
+    base_url: String,
 }
 
 #[derive(Serialize)]
-struct ClaudeRequest {
+struct ClaudeRequest<'a> {
     model: String,
     max_tokens: u32,
-    messages: Vec<Message>,
+    messages: &'a [Message],
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: Vec<Content>,
 }
 
-#[derive(Deserialize)]
-struct ClaudeResponse {
-    content: Vec<Content>,
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Content {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        is_error: bool,
+    },
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Deserialize)]
-struct Content {
-    text: String,
+struct ClaudeResponse {
+    content: Vec<Content>,
 }
 
 impl ClaudeProvider {
@@ -42,22 +65,107 @@ impl ClaudeProvider {
             client: Client::new(),
             api_key,
             model: model.unwrap_or_else(|| "claude-3-5-haiku-20241022".to_string()),
+            base_url: DEFAULT_BASE_URL.to_string(),
         }
     }
 
+    /// Use a different Anthropic-compatible endpoint
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
     async fn call_api(&self, prompt: String) -> Result<String> {
+        let message = Message {
+            role: "user".to_string(),
+            content: vec![Content::Text { text: prompt }],
+        };
+        let content = self.send_messages(&[message], vec![], None).await?;
+
+        Self::text_of(&content).ok_or_else(|| anyhow!("No response from Claude"))
+    }
+
+    /// Run the analysis as a conversation in which the model may call MCP tools
+    async fn analyze_with_session(
+        &self,
+        group: &ErrorGroup,
+        session: &mut ToolSession<'_>,
+    ) -> Result<ErrorAnalysis> {
+        let prompt = mcp_helper::with_tool_instructions(build_analysis_prompt(group));
+        let mut messages = vec![Message {
+            role: "user".to_string(),
+            content: vec![Content::Text { text: prompt }],
+        }];
+
+        for round in 0..=MAX_TOOL_ROUNDS {
+            // On the last round the model has to answer without further tools
+            let tool_choice =
+                (round == MAX_TOOL_ROUNDS).then(|| serde_json::json!({"type": "none"}));
+            let content = self
+                .send_messages(&messages, session.claude_tools(), tool_choice)
+                .await?;
+
+            let calls: Vec<ToolCall> = content
+                .iter()
+                .filter_map(|block| match block {
+                    Content::ToolUse { id, name, input } => Some(ToolCall {
+                        id: id.clone(),
+                        name: name.clone(),
+                        arguments: input.clone(),
+                    }),
+                    _ => None,
+                })
+                .collect();
+
+            if calls.is_empty() {
+                let text =
+                    Self::text_of(&content).ok_or_else(|| anyhow!("No response from Claude"))?;
+                return self.parse_response(&text);
+            }
+
+            messages.push(Message {
+                role: "assistant".to_string(),
+                content,
+            });
+
+            let mut results = Vec::new();
+            for call in &calls {
+                let output = session.execute(call).await;
+                results.push(Content::ToolResult {
+                    tool_use_id: call.id.clone(),
+                    content: output.content,
+                    is_error: output.is_error,
+                });
+            }
+            messages.push(Message {
+                role: "user".to_string(),
+                content: results,
+            });
+        }
+
+        Err(anyhow!(
+            "Claude kept requesting tools after {} rounds",
+            MAX_TOOL_ROUNDS
+        ))
+    }
+
+    async fn send_messages(
+        &self,
+        messages: &[Message],
+        tools: Vec<serde_json::Value>,
+        tool_choice: Option<serde_json::Value>,
+    ) -> Result<Vec<Content>> {
         let request = ClaudeRequest {
             model: self.model.clone(),
             max_tokens: 1024,
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt,
-            }],
+            messages,
+            tools,
+            tool_choice,
         };
 
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(format!("{}/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .header("Content-Type", "application/json")
@@ -72,12 +180,20 @@ impl ClaudeProvider {
         }
 
         let claude_response: ClaudeResponse = response.json().await?;
+        Ok(claude_response.content)
+    }
+
+    /// Concatenated text blocks of a response, if any
+    fn text_of(content: &[Content]) -> Option<String> {
+        let text: Vec<&str> = content
+            .iter()
+            .filter_map(|block| match block {
+                Content::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
 
-        claude_response
-            .content
-            .first()
-            .map(|c| c.text.clone())
-            .ok_or_else(|| anyhow!("No response from Claude"))
+        (!text.is_empty()).then(|| text.join(""))
     }
 
     fn parse_response(&self, response: &str) -> Result<ErrorAnalysis> {
@@ -147,43 +263,25 @@ impl AIProvider for ClaudeProvider {
         group: &ErrorGroup,
         mcp_client: Option<&crate::mcp::MCPClient>,
     ) -> Result<ErrorAnalysis> {
-        // If no MCP client, fall back to regular analysis
         let Some(client) = mcp_client else {
             return self.analyze(group).await;
         };
 
-        // Build base prompt
-        let base_prompt = build_analysis_prompt(group);
-
-        // Invoke relevant MCP tools and collect results
-        let tool_results = crate::ai::mcp_helper::invoke_relevant_tools(client, group).await;
-
-        // Augment prompt with tool results
-        let prompt = crate::ai::mcp_helper::augment_prompt_with_tools(base_prompt, &tool_results);
-
-        // Call AI with enhanced prompt
-        let response = self.call_api(prompt).await?;
-        let mut analysis = self.parse_response(&response)?;
-
-        // Add tool invocation summaries
-        analysis.tool_invocations = tool_results
-            .into_iter()
-            .map(|r| crate::mcp::ToolInvocationSummary {
-                tool: r.tool_name,
-                status: if r.result.success {
-                    "success".to_string()
-                } else {
-                    "failure".to_string()
-                },
-                duration_ms: r.duration_ms,
-                contributed_to: vec![],
-            })
-            .collect();
+        let mut session = ToolSession::new(client);
+        if !session.has_tools() {
+            return self.analyze(group).await;
+        }
 
+        let mut analysis = self.analyze_with_session(group, &mut session).await?;
+        analysis.tool_invocations = session.into_invocations();
         Ok(analysis)
     }
 
     fn name(&self) -> &str {
         "claude"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
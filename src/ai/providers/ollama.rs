@@ -1,4 +1,5 @@
 use crate::ai::json_extractor::EnhancedJsonExtractor;
+use crate::ai::mcp_helper::{self, ToolCall, ToolSession, MAX_TOOL_ROUNDS};
 use crate::ai::prompts::build_enhanced_analysis_prompt;
 use crate::ai::provider::AIProvider;
 use crate::types::{ErrorAnalysis, ErrorGroup, Suggestion};
@@ -28,6 +29,45 @@ struct OllamaResponse {
     response: String,
 }
 
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: String,
+    messages: &'a [ChatMessage],
+    stream: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ChatToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatToolCall {
+    function: ChatFunction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ChatFunction {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: ChatMessage,
+}
+
 impl OllamaProvider {
     pub fn new(host: Option<String>, model: Option<String>) -> Self {
         Self {
@@ -74,6 +114,97 @@ impl OllamaProvider {
         Ok(ollama_response.response)
     }
 
+    /// Run the analysis through `/api/chat`, letting the model call MCP tools
+    async fn analyze_with_session(
+        &self,
+        group: &ErrorGroup,
+        session: &mut ToolSession<'_>,
+    ) -> Result<ErrorAnalysis> {
+        let prompt =
+            mcp_helper::with_tool_instructions(build_enhanced_analysis_prompt(group, 2000));
+        let mut messages = vec![ChatMessage {
+            role: "user".to_string(),
+            content: prompt,
+            tool_calls: vec![],
+            tool_name: None,
+        }];
+
+        for round in 0..=MAX_TOOL_ROUNDS {
+            // On the last round tools are withheld and a JSON answer is forced
+            let final_round = round == MAX_TOOL_ROUNDS;
+            let request = ChatRequest {
+                model: self.model.clone(),
+                messages: &messages,
+                stream: false,
+                tools: if final_round {
+                    vec![]
+                } else {
+                    session.openai_tools()
+                },
+                format: final_round.then(|| "json".to_string()),
+            };
+            let reply = self.send_chat(&request).await?;
+
+            if reply.tool_calls.is_empty() {
+                return self.parse_response(&reply.content);
+            }
+
+            // Ollama does not assign call ids, so the index stands in for one
+            let calls: Vec<ToolCall> = reply
+                .tool_calls
+                .iter()
+                .enumerate()
+                .map(|(index, call)| ToolCall {
+                    id: format!("call_{}_{}", round, index),
+                    name: call.function.name.clone(),
+                    arguments: call.function.arguments.clone(),
+                })
+                .collect();
+            messages.push(reply);
+
+            for call in &calls {
+                let output = session.execute(call).await;
+                messages.push(ChatMessage {
+                    role: "tool".to_string(),
+                    content: output.content,
+                    tool_calls: vec![],
+                    tool_name: Some(call.name.clone()),
+                });
+            }
+        }
+
+        Err(anyhow!(
+            "Ollama kept requesting tools after {} rounds",
+            MAX_TOOL_ROUNDS
+        ))
+    }
+
+    async fn send_chat(&self, request: &ChatRequest<'_>) -> Result<ChatMessage> {
+        let url = format!("{}/api/chat", self.host);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(request)
+            .timeout(std::time::Duration::from_secs(120))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Ollama API request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow!("Ollama API error {}: {}", status, error_text));
+        }
+
+        let chat_response: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse Ollama API response: {}", e))?;
+
+        Ok(chat_response.message)
+    }
+
     fn parse_response(&self, response: &str) -> Result<ErrorAnalysis> {
         // Check if response is empty
         if response.trim().is_empty() {
@@ -146,43 +277,25 @@ impl AIProvider for OllamaProvider {
         group: &ErrorGroup,
         mcp_client: Option<&crate::mcp::MCPClient>,
     ) -> Result<ErrorAnalysis> {
-        // If no MCP client, fall back to regular analysis
         let Some(client) = mcp_client else {
             return self.analyze(group).await;
         };
 
-        // Build base prompt
-        let base_prompt = build_enhanced_analysis_prompt(group, 2000);
-
-        // Invoke relevant MCP tools and collect results
-        let tool_results = crate::ai::mcp_helper::invoke_relevant_tools(client, group).await;
-
-        // Augment prompt with tool results
-        let prompt = crate::ai::mcp_helper::augment_prompt_with_tools(base_prompt, &tool_results);
-
-        // Call AI with enhanced prompt
-        let response = self.call_api(prompt).await?;
-        let mut analysis = self.parse_response(&response)?;
-
-        // Add tool invocation summaries
-        analysis.tool_invocations = tool_results
-            .into_iter()
-            .map(|r| crate::mcp::ToolInvocationSummary {
-                tool: r.tool_name,
-                status: if r.result.success {
-                    "success".to_string()
-                } else {
-                    "failure".to_string()
-                },
-                duration_ms: r.duration_ms,
-                contributed_to: vec![],
-            })
-            .collect();
+        let mut session = ToolSession::new(client);
+        if !session.has_tools() {
+            return self.analyze(group).await;
+        }
 
+        let mut analysis = self.analyze_with_session(group, &mut session).await?;
+        analysis.tool_invocations = session.into_invocations();
         Ok(analysis)
     }
 
     fn name(&self) -> &str {
         "ollama"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
@@ -1,3 +1,4 @@
+use crate::ai::mcp_helper::{self, ToolCall, ToolSession, MAX_TOOL_ROUNDS};
 use crate::ai::prompts::build_analysis_prompt;
 use crate::ai::provider::AIProvider;
 use crate::types::{ErrorAnalysis, ErrorGroup, Suggestion};
@@ -7,23 +8,65 @@ use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
 pub struct OpenAIProvider {
     client: Client,
     api_key: String,
     model: String,
+    base_url: String,
 }
 
 #[derive(Serialize)]
-struct OpenAIRequest {
+struct OpenAIRequest<'a> {
     model: String,
-    messages: Vec<Message>,
+    messages: &'a [Message],
     temperature: f32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<&'static str>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct Message {
     role: String,
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ApiToolCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ApiToolCall {
+    id: String,
+    #[serde(rename = "type", default = "function_type")]
+    kind: String,
+    function: FunctionCall,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FunctionCall {
+    name: String,
+    /// JSON-encoded arguments
+    arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+impl Message {
+    fn text(role: &str, content: String) -> Self {
+        Self {
+            role: role.to_string(),
+            content: Some(content),
+            tool_calls: vec![],
+            tool_call_id: None,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -42,22 +85,93 @@ impl OpenAIProvider {
             client: Client::new(),
             api_key,
             model: model.unwrap_or_else(|| "gpt-4o-mini".to_string()),
+            base_url: DEFAULT_BASE_URL.to_string(),
         }
     }
 
+    /// Use an OpenAI-compatible endpoint instead of api.openai.com
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
     async fn call_api(&self, prompt: String) -> Result<String> {
+        let message = self
+            .send_messages(&[Message::text("user", prompt)], vec![], None)
+            .await?;
+
+        message
+            .content
+            .ok_or_else(|| anyhow!("No response from OpenAI"))
+    }
+
+    /// Run the analysis as a conversation in which the model may call MCP tools
+    async fn analyze_with_session(
+        &self,
+        group: &ErrorGroup,
+        session: &mut ToolSession<'_>,
+    ) -> Result<ErrorAnalysis> {
+        let prompt = mcp_helper::with_tool_instructions(build_analysis_prompt(group));
+        let mut messages = vec![Message::text("user", prompt)];
+
+        for round in 0..=MAX_TOOL_ROUNDS {
+            // On the last round the model has to answer without further tools
+            let tool_choice = (round == MAX_TOOL_ROUNDS).then_some("none");
+            let reply = self
+                .send_messages(&messages, session.openai_tools(), tool_choice)
+                .await?;
+
+            if reply.tool_calls.is_empty() {
+                let content = reply
+                    .content
+                    .ok_or_else(|| anyhow!("No response from OpenAI"))?;
+                return self.parse_response(&content);
+            }
+
+            let calls: Vec<ToolCall> = reply
+                .tool_calls
+                .iter()
+                .map(|call| ToolCall {
+                    id: call.id.clone(),
+                    name: call.function.name.clone(),
+                    arguments: serde_json::from_str(&call.function.arguments)
+                        .unwrap_or(serde_json::Value::Null),
+                })
+                .collect();
+            messages.push(reply);
+
+            for call in &calls {
+                let output = session.execute(call).await;
+                messages.push(Message {
+                    tool_call_id: Some(call.id.clone()),
+                    ..Message::text("tool", output.content)
+                });
+            }
+        }
+
+        Err(anyhow!(
+            "OpenAI kept requesting tools after {} rounds",
+            MAX_TOOL_ROUNDS
+        ))
+    }
+
+    async fn send_messages(
+        &self,
+        messages: &[Message],
+        tools: Vec<serde_json::Value>,
+        tool_choice: Option<&'static str>,
+    ) -> Result<Message> {
         let request = OpenAIRequest {
             model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt,
-            }],
+            messages,
             temperature: 0.3,
+            tools,
+            tool_choice,
         };
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(format!("{}/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
@@ -74,8 +188,9 @@ impl OpenAIProvider {
 
         openai_response
             .choices
-            .first()
-            .map(|choice| choice.message.content.clone())
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
             .ok_or_else(|| anyhow!("No response from OpenAI"))
     }
 
@@ -146,43 +261,25 @@ impl AIProvider for OpenAIProvider {
         group: &ErrorGroup,
         mcp_client: Option<&crate::mcp::MCPClient>,
     ) -> Result<ErrorAnalysis> {
-        // If no MCP client, fall back to regular analysis
         let Some(client) = mcp_client else {
             return self.analyze(group).await;
         };
 
-        // Build base prompt
-        let base_prompt = build_analysis_prompt(group);
-
-        // Invoke relevant MCP tools and collect results
-        let tool_results = crate::ai::mcp_helper::invoke_relevant_tools(client, group).await;
-
-        // Augment prompt with tool results
-        let prompt = crate::ai::mcp_helper::augment_prompt_with_tools(base_prompt, &tool_results);
-
-        // Call AI with enhanced prompt
-        let response = self.call_api(prompt).await?;
-        let mut analysis = self.parse_response(&response)?;
-
-        // Add tool invocation summaries
-        analysis.tool_invocations = tool_results
-            .into_iter()
-            .map(|r| crate::mcp::ToolInvocationSummary {
-                tool: r.tool_name,
-                status: if r.result.success {
-                    "success".to_string()
-                } else {
-                    "failure".to_string()
-                },
-                duration_ms: r.duration_ms,
-                contributed_to: vec![], // TODO: Track which suggestions came from which tools
-            })
-            .collect();
+        let mut session = ToolSession::new(client);
+        if !session.has_tools() {
+            return self.analyze(group).await;
+        }
 
+        let mut analysis = self.analyze_with_session(group, &mut session).await?;
+        analysis.tool_invocations = session.into_invocations();
         Ok(analysis)
     }
 
     fn name(&self) -> &str {
         "openai"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
@@ -26,6 +26,7 @@
 //! #     severity: logai::types::Severity::Error,
 //! #     entries: vec![],
 //! #     analysis: None,
+//! #     sources: Vec::new(),
 //! # };
 //! let analyzer = RetryableAnalyzer::new(
 //!     provider,
@@ -40,6 +41,7 @@
 //! ```
 
 use crate::ai::provider::AIProvider;
+use crate::mcp::MCPClient;
 use crate::types::{ErrorAnalysis, ErrorGroup};
 use crate::Result;
 use std::sync::Arc;
@@ -48,6 +50,7 @@ use std::time::Duration;
 /// Analyzer that wraps an AI provider with retry logic
 pub struct RetryableAnalyzer {
     provider: Arc<dyn AIProvider>,
+    mcp_client: Option<Arc<MCPClient>>,
     max_retries: usize,
     initial_backoff_ms: u64,
     max_backoff_ms: u64,
@@ -63,18 +66,40 @@ impl RetryableAnalyzer {
     ) -> Self {
         Self {
             provider,
+            mcp_client: None,
             max_retries,
             initial_backoff_ms,
             max_backoff_ms,
         }
     }
 
+    /// Let the provider call tools from `client` during analysis
+    pub fn with_mcp_client(mut self, client: Arc<MCPClient>) -> Self {
+        self.mcp_client = Some(client);
+        self
+    }
+
     /// Analyze an error group with retry logic
     pub async fn analyze(&self, group: &ErrorGroup) -> Result<ErrorAnalysis> {
+        self.analyze_with_attempts(group).await.0
+    }
+
+    /// Analyze an error group with retry logic, also returning the number of
+    /// attempts made
+    pub async fn analyze_with_attempts(
+        &self,
+        group: &ErrorGroup,
+    ) -> (Result<ErrorAnalysis>, usize) {
         let mut last_error = None;
+        let mut attempts = 0;
 
         for attempt in 0..=self.max_retries {
-            match self.provider.analyze(group).await {
+            attempts = attempt + 1;
+            match self
+                .provider
+                .analyze_with_tools(group, self.mcp_client.as_deref())
+                .await
+            {
                 Ok(analysis) => {
                     if attempt > 0 {
                         log::info!(
@@ -83,7 +108,7 @@ impl RetryableAnalyzer {
                             attempt
                         );
                     }
-                    return Ok(analysis);
+                    return (Ok(analysis), attempts);
                 }
                 Err(e) => {
                     last_error = Some(e);
@@ -114,7 +139,10 @@ impl RetryableAnalyzer {
             }
         }
 
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Analysis failed after retries")))
+        (
+            Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Analysis failed after retries"))),
+            attempts,
+        )
     }
 
     /// Determine if an error should be retried
@@ -235,6 +263,7 @@ mod tests {
             severity: Severity::Error,
             entries: vec![],
             analysis: None,
+            sources: Vec::new(),
         }
     }
 
@@ -288,6 +317,23 @@ mod tests {
         assert_eq!(*fail_count.lock().unwrap(), 7); // 3 attempts (0, 1, 2)
     }
 
+    #[tokio::test]
+    async fn test_attempts_are_reported() {
+        let provider = Arc::new(MockProvider::new(2, "503 service unavailable"));
+        let analyzer = RetryableAnalyzer::new(provider, 3, 1, 10);
+
+        let (result, attempts) = analyzer.analyze_with_attempts(&create_test_group()).await;
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+
+        let provider = Arc::new(MockProvider::new(5, "403 forbidden"));
+        let analyzer = RetryableAnalyzer::new(provider, 3, 1, 10);
+
+        let (result, attempts) = analyzer.analyze_with_attempts(&create_test_group()).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
     #[test]
     fn test_backoff_calculation() {
         let provider = Arc::new(MockProvider::new(0, ""));
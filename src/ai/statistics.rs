@@ -46,6 +46,14 @@ impl AnalysisStatistics {
         self.update_duration(duration);
     }
 
+    /// Record a successful analysis that needed `attempts` tries
+    pub fn record_success_after(&mut self, duration: Duration, attempts: usize) {
+        self.record_success(duration);
+        if attempts > 1 {
+            *self.retry_counts.entry(attempts).or_insert(0) += 1;
+        }
+    }
+
     /// Record a failed analysis
     pub fn record_failure(&mut self, reason: String, attempts: usize) {
         self.failed += 1;
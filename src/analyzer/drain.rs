@@ -0,0 +1,216 @@
+//! Online log template mining with a fixed-depth parse tree (Drain).
+//!
+//! Messages are routed by token count and then by their first few tokens to a
+//! small list of clusters; a message joins the most similar cluster when
+//! enough of its tokens match, and positions where the cluster's messages
+//! differ become `<*>`. Templates therefore generalize as messages arrive:
+//! `Connection to db-1 failed after 30 ms` and `Connection to db-2 failed
+//! after 45 ms` give `Connection to <*> failed after <*> ms`.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Placeholder for a variable token
+pub const WILDCARD: &str = "<*>";
+
+/// Children kept per tree node before further tokens share a wildcard child
+const MAX_CHILDREN: usize = 100;
+
+/// Values that are always variable, masked before mining
+fn masks() -> &'static Regex {
+    static MASKS: OnceLock<Regex> = OnceLock::new();
+    MASKS.get_or_init(|| {
+        Regex::new(
+            r#"(?x)
+            [\w.+-]+@[\w-]+(?:\.[\w-]+)+                                  # Emails
+            |[a-z][\w+.-]*://\S+                                           # URLs
+            |\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b  # UUIDs
+            |\b\d{1,3}(?:\.\d{1,3}){3}(?::\d+)?\b                          # IPv4 addresses
+            |\b0x[0-9a-fA-F]+\b                                            # Hex numbers
+            |\b[0-9a-f]{8,}\b                                              # Hashes
+            |'[^'\s]*'|"[^"\s]*"|`[^`\s]*`                                 # Quoted identifiers
+            |[-+]?\b\d+(?:\.\d+)?(?:ns|us|µs|ms|s|m|h|d|b|kb|mb|gb|%)?\b  # Numbers and durations
+            "#,
+        )
+        .unwrap()
+    })
+}
+
+/// Result of adding a message to the miner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mined {
+    /// Cluster the message joined
+    pub cluster: usize,
+    /// The cluster's template after the message was added
+    pub template: String,
+    /// The cluster's template before, when the message changed it
+    pub previous: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: HashMap<String, Node>,
+    clusters: Vec<usize>,
+}
+
+/// Online template miner
+#[derive(Debug)]
+pub struct Drain {
+    /// Tokens used to route a message to its leaf
+    prefix_tokens: usize,
+    similarity: f64,
+    templates: Vec<Vec<String>>,
+    /// Routing tree, keyed first by token count
+    root: HashMap<usize, Node>,
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self::new(4, 0.5)
+    }
+}
+
+impl Drain {
+    /// Create a miner whose tree has `depth` levels (at least 3: the root,
+    /// the token count and one token) that merges messages whose tokens are
+    /// at least `similarity` (0.0-1.0) alike
+    pub fn new(depth: usize, similarity: f64) -> Self {
+        Self {
+            prefix_tokens: depth.max(3) - 2,
+            similarity: similarity.clamp(0.0, 1.0),
+            templates: Vec::new(),
+            root: HashMap::new(),
+        }
+    }
+
+    fn tokenize(message: &str) -> Vec<String> {
+        masks()
+            .replace_all(message, WILDCARD)
+            .split_whitespace()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Add a message and return the template of the cluster it joined
+    pub fn add(&mut self, message: &str) -> Mined {
+        let tokens = Self::tokenize(message);
+
+        let mut node = self.root.entry(tokens.len()).or_default();
+        for token in tokens.iter().take(self.prefix_tokens) {
+            let key = if token.contains(|c: char| c.is_ascii_digit()) {
+                WILDCARD
+            } else {
+                token.as_str()
+            };
+            let key = if node.children.contains_key(key) || node.children.len() < MAX_CHILDREN {
+                key
+            } else {
+                WILDCARD
+            };
+            node = node.children.entry(key.to_string()).or_default();
+        }
+
+        let best = node
+            .clusters
+            .iter()
+            .map(|&id| (id, Self::similarity(&self.templates[id], &tokens)))
+            .filter(|&(_, similarity)| similarity >= self.similarity)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        match best {
+            Some((cluster, _)) => {
+                let template = &mut self.templates[cluster];
+                let before = template.join(" ");
+                for (slot, token) in template.iter_mut().zip(&tokens) {
+                    if slot != token {
+                        *slot = WILDCARD.to_string();
+                    }
+                }
+                let after = template.join(" ");
+                Mined {
+                    cluster,
+                    previous: (after != before).then_some(before),
+                    template: after,
+                }
+            }
+            None => {
+                let cluster = self.templates.len();
+                node.clusters.push(cluster);
+                let template = tokens.join(" ");
+                self.templates.push(tokens);
+                Mined {
+                    cluster,
+                    template,
+                    previous: None,
+                }
+            }
+        }
+    }
+
+    /// The current template of `cluster`
+    pub fn template(&self, cluster: usize) -> Option<String> {
+        self.templates.get(cluster).map(|tokens| tokens.join(" "))
+    }
+
+    /// Share of positions where the template has the same token; wildcards
+    /// match anything
+    fn similarity(template: &[String], tokens: &[String]) -> f64 {
+        if tokens.is_empty() {
+            return 1.0;
+        }
+        let same = template
+            .iter()
+            .zip(tokens)
+            .filter(|(slot, token)| slot == token || slot.as_str() == WILDCARD)
+            .count();
+        same as f64 / tokens.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masks_variable_tokens() {
+        assert_eq!(
+            Drain::tokenize(
+                "User bob@example.com got 'ord-7' in 123ms from 10.0.0.1:8080 (sha 9f86d081884c7d65)"
+            )
+            .join(" "),
+            "User <*> got <*> in <*> from <*> (sha <*>)"
+        );
+    }
+
+    #[test]
+    fn test_templates_generalize() {
+        let mut drain = Drain::default();
+
+        let first = drain.add("Connection to db-primary failed after 30 ms");
+        assert_eq!(
+            first.template,
+            "Connection to db-primary failed after <*> ms"
+        );
+
+        let second = drain.add("Connection to db-replica failed after 45 ms");
+        assert_eq!(second.cluster, first.cluster);
+        assert_eq!(second.template, "Connection to <*> failed after <*> ms");
+        assert_eq!(second.previous.as_deref(), Some(first.template.as_str()));
+
+        let third = drain.add("Connection to cache failed after 5 ms");
+        assert_eq!(third.cluster, first.cluster);
+        assert_eq!(third.previous, None);
+
+        let other = drain.add("Disk /var is full");
+        assert_ne!(other.cluster, first.cluster);
+        assert_ne!(
+            drain.add("Payment declined for card ending 4242").cluster,
+            first.cluster
+        );
+        assert_eq!(
+            drain.template(first.cluster).unwrap(),
+            "Connection to <*> failed after <*> ms"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Filters applied to parsed entries before they are grouped
+
+use crate::types::{LogEntry, Severity};
+use crate::Result;
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// Keeps entries inside a time range that match every predicate.
+///
+/// Entries without a timestamp are dropped when a time range is set.
+#[derive(Debug, Clone, Default)]
+pub struct EntryFilter {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    predicates: Vec<Predicate>,
+}
+
+impl EntryFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep entries at or after `since`
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Keep entries at or before `until`
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Keep entries matching `predicate` (and all previously added ones)
+    pub fn with_predicate(mut self, predicate: Predicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    /// Whether every entry passes
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none() && self.predicates.is_empty()
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        if self.since.is_some() || self.until.is_some() {
+            let Some(timestamp) = entry.timestamp else {
+                return false;
+            };
+            if self.since.is_some_and(|since| timestamp < since)
+                || self.until.is_some_and(|until| timestamp > until)
+            {
+                return false;
+            }
+        }
+        self.predicates.iter().all(|p| p.matches(entry))
+    }
+
+    /// The entries that match
+    pub fn apply(&self, entries: Vec<LogEntry>) -> Vec<LogEntry> {
+        entries.into_iter().filter(|e| self.matches(e)).collect()
+    }
+}
+
+/// Parse a `--since`/`--until` time relative to `now`.
+///
+/// Accepts durations before `now` (`30s`, `15m`, `2h`, `7d`, `1w`), RFC 3339
+/// timestamps, `YYYY-MM-DD[ HH:MM[:SS]]` and a time of day (`14:02`) on
+/// `now`'s date. Times without an offset are UTC, like log timestamps
+/// without one.
+pub fn parse_time(text: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let text = text.trim();
+
+    if let Some(duration) = parse_duration(text) {
+        return Ok(now - duration);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(Utc.from_utc_datetime(&dt));
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN)));
+    }
+    for format in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(text, format) {
+            return Ok(Utc.from_utc_datetime(&now.date_naive().and_time(time)));
+        }
+    }
+
+    Err(anyhow!(
+        "Invalid time '{}' (expected e.g. 2h, 2025-11-17 14:02, 14:02 or an RFC 3339 timestamp)",
+        text
+    ))
+}
+
+fn parse_duration(text: &str) -> Option<Duration> {
+    let unit = text.chars().last()?;
+    let amount: i64 = text[..text.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        's' => Duration::try_seconds(amount),
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+/// Comparison in a [`Predicate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// Regex match (`~`)
+    Matches,
+    /// Regex non-match (`!~`)
+    NotMatches,
+}
+
+impl Operator {
+    /// Two-character operators first so `>=` is not read as `>`
+    const SYMBOLS: [(&'static str, Operator); 8] = [
+        ("!=", Operator::Ne),
+        (">=", Operator::Ge),
+        ("<=", Operator::Le),
+        ("!~", Operator::NotMatches),
+        ("=", Operator::Eq),
+        (">", Operator::Gt),
+        ("<", Operator::Lt),
+        ("~", Operator::Matches),
+    ];
+}
+
+/// A `--where` condition on an entry field, e.g. `service=checkout`,
+/// `status>=500` or `message~timeout`.
+///
+/// Fields are `message`, `severity` (or `level`), `file`, `line`,
+/// `function`, `thread`, `source` and `raw`; other names refer to metadata
+/// extra fields. Numbers compare numerically, severities by level
+/// (`severity>=warn` keeps warnings and errors), and other values as text.
+/// A missing field only satisfies `!=` and `!~`.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    field: String,
+    operator: Operator,
+    value: String,
+    regex: Option<Regex>,
+}
+
+impl FromStr for Predicate {
+    type Err = crate::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let start = text
+            .find(['=', '!', '<', '>', '~'])
+            .ok_or_else(|| anyhow!("Invalid filter '{}' (expected FIELD OP VALUE)", text))?;
+        let (symbol, operator) = Operator::SYMBOLS
+            .iter()
+            .find(|(symbol, _)| text[start..].starts_with(symbol))
+            .ok_or_else(|| anyhow!("Invalid operator in filter '{}'", text))?;
+
+        let field = text[..start].trim();
+        if field.is_empty() {
+            return Err(anyhow!("Missing field name in filter '{}'", text));
+        }
+        let value = text[start + symbol.len()..].trim().to_string();
+        let regex = match operator {
+            Operator::Matches | Operator::NotMatches => Some(
+                Regex::new(&value)
+                    .map_err(|e| anyhow!("Invalid regex in filter '{}': {}", text, e))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            field: field.to_string(),
+            operator: *operator,
+            value,
+            regex,
+        })
+    }
+}
+
+impl Predicate {
+    fn field_value(&self, entry: &LogEntry) -> Option<String> {
+        let metadata = &entry.metadata;
+        match self.field.as_str() {
+            "message" => Some(entry.message.clone()),
+            "severity" | "level" => Some(format!("{:?}", entry.severity)),
+            "file" => metadata.file.clone(),
+            "line" => metadata.line.map(|line| line.to_string()),
+            "function" => metadata.function.clone(),
+            "thread" => metadata.thread.clone(),
+            "source" => entry.source.clone(),
+            "raw" => Some(entry.raw.clone()),
+            field => metadata.extra.get(field).cloned(),
+        }
+    }
+
+    fn compare(&self, actual: &str) -> Option<Ordering> {
+        if matches!(self.field.as_str(), "severity" | "level") {
+            // Lower rank is more severe, so reverse to make errors greatest
+            let wanted = self.value.parse::<Severity>().ok()?;
+            let actual = actual.parse::<Severity>().unwrap_or(Severity::Unknown);
+            return Some(wanted.rank().cmp(&actual.rank()));
+        }
+        match (actual.parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(actual), Ok(wanted)) => actual.partial_cmp(&wanted),
+            _ => Some(actual.cmp(self.value.as_str())),
+        }
+    }
+
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        let Some(actual) = self.field_value(entry) else {
+            return matches!(self.operator, Operator::Ne | Operator::NotMatches);
+        };
+        if let Some(regex) = &self.regex {
+            return regex.is_match(&actual) == (self.operator == Operator::Matches);
+        }
+
+        let Some(ordering) = self.compare(&actual) else {
+            return self.operator == Operator::Ne;
+        };
+        match self.operator {
+            Operator::Eq => ordering.is_eq(),
+            Operator::Ne => ordering.is_ne(),
+            Operator::Gt => ordering.is_gt(),
+            Operator::Ge => ordering.is_ge(),
+            Operator::Lt => ordering.is_lt(),
+            Operator::Le => ordering.is_le(),
+            Operator::Matches | Operator::NotMatches => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LogMetadata;
+    use std::collections::HashMap;
+
+    fn entry(severity: Severity, message: &str, extra: &[(&str, &str)]) -> LogEntry {
+        LogEntry {
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 11, 17, 14, 10, 0).unwrap()),
+            severity,
+            message: message.to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: extra
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<HashMap<_, _>>(),
+            },
+            raw: message.to_string(),
+            source: None,
+        }
+    }
+
+    fn matches(predicate: &str, entry: &LogEntry) -> bool {
+        predicate.parse::<Predicate>().unwrap().matches(entry)
+    }
+
+    #[test]
+    fn test_predicates() {
+        let e = entry(
+            Severity::Warning,
+            "Upstream timeout after 30s",
+            &[("service", "checkout"), ("status", "504")],
+        );
+
+        assert!(matches("service=checkout", &e));
+        assert!(!matches("service != checkout", &e));
+        assert!(matches("status>=500", &e));
+        assert!(matches("status<600", &e));
+        assert!(!matches("status>504", &e));
+        assert!(matches("message~timeout", &e));
+        assert!(matches("message!~(?i)refused", &e));
+        assert!(matches("severity>=warn", &e));
+        assert!(!matches("level=error", &e));
+
+        // Missing fields only satisfy negations
+        assert!(!matches("host=web-1", &e));
+        assert!(matches("host!=web-1", &e));
+
+        assert!("status".parse::<Predicate>().is_err());
+        assert!("=500".parse::<Predicate>().is_err());
+        assert!("message~(".parse::<Predicate>().is_err());
+    }
+
+    #[test]
+    fn test_time_range() {
+        let now = Utc.with_ymd_and_hms(2025, 11, 17, 15, 0, 0).unwrap();
+        assert_eq!(
+            parse_time("2h", now).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 17, 13, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time("14:02", now).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 17, 14, 2, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time("2025-11-16", now).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 16, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_time("2025-11-17T14:30:00+01:00", now).unwrap(),
+            Utc.with_ymd_and_hms(2025, 11, 17, 13, 30, 0).unwrap()
+        );
+        assert!(parse_time("yesterday", now).is_err());
+
+        let e = entry(Severity::Error, "boom", &[]);
+        let filter = EntryFilter::new()
+            .since(parse_time("14:02", now).unwrap())
+            .until(parse_time("14:30", now).unwrap());
+        assert!(filter.matches(&e));
+        assert!(!EntryFilter::new()
+            .since(parse_time("30m", now).unwrap())
+            .matches(&e));
+
+        let mut untimed = e.clone();
+        untimed.timestamp = None;
+        assert!(!filter.matches(&untimed));
+        assert!(EntryFilter::new().matches(&untimed));
+    }
+}
@@ -1,11 +1,96 @@
+use super::drain::Drain;
+use super::fingerprint::Fingerprint;
+use super::stack::StackTrace;
 use crate::types::{ErrorGroup, LogEntry, Severity};
 use crate::Result;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Sample entries kept per group when grouping a stream (`investigate`,
+/// `watch`)
+pub const DEFAULT_MAX_SAMPLES: usize = 100;
+
+/// How messages are turned into group patterns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupingStrategy {
+    /// Replace dynamic values matched by a fixed set of regexes
+    #[default]
+    Regex,
+    /// Mine templates online with Drain (see [`Drain`])
+    Drain,
+}
+
+impl std::str::FromStr for GroupingStrategy {
+    type Err = crate::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "regex" => Ok(Self::Regex),
+            "drain" => Ok(Self::Drain),
+            _ => Err(anyhow::anyhow!(
+                "Unknown grouping strategy '{}' (expected regex or drain)",
+                name
+            )),
+        }
+    }
+}
+
+/// Configuration for error grouping.
+///
+/// Read from the `[grouping]` section of `~/.logai/config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroupingConfig {
+    /// How group patterns are derived from messages
+    pub strategy: GroupingStrategy,
+    /// Number of top stack frames in a stack trace fingerprint
+    pub stack_frames: usize,
+    /// Frame prefixes of application code (e.g. `com.shop.`, `app/`); when
+    /// empty, well-known framework and library frames are skipped instead
+    pub in_app_prefixes: Vec<String>,
+    /// Depth of the Drain parse tree (the first `depth - 2` tokens route a
+    /// message)
+    pub drain_depth: usize,
+    /// Share of tokens (0.0-1.0) a message must share with a Drain template
+    /// to join it
+    pub drain_similarity: f64,
+}
+
+impl Default for GroupingConfig {
+    fn default() -> Self {
+        Self {
+            strategy: GroupingStrategy::Regex,
+            stack_frames: 5,
+            in_app_prefixes: Vec::new(),
+            drain_depth: 4,
+            drain_similarity: 0.5,
+        }
+    }
+}
+
+/// Outcome of adding an entry to a set of groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupUpdate {
+    /// A new group was created with the given id
+    Created(String),
+    /// An existing group with the given id was updated
+    Updated(String),
+}
 
 pub struct ErrorGrouper {
     // Regex to normalize dynamic values (IDs, numbers, URLs, etc.)
     normalizer: Regex,
+    // Maximum entries kept per group (all unless set); `count` still reflects
+    // every occurrence
+    max_samples: usize,
+    // Least severe entries that are grouped
+    min_severity: Severity,
+    config: GroupingConfig,
+    // Template miner, with the Drain strategy
+    drain: Option<Mutex<Drain>>,
 }
 
 impl Default for ErrorGrouper {
@@ -38,9 +123,37 @@ impl ErrorGrouper {
                 |\bexec-\d+\b                                                      # Thread IDs
                 "
             ).unwrap(),
+            max_samples: usize::MAX,
+            min_severity: Severity::Warning,
+            config: GroupingConfig::default(),
+            drain: None,
         }
     }
 
+    /// Use `config` for patterns and stack trace fingerprints
+    pub fn with_config(mut self, config: GroupingConfig) -> Self {
+        self.drain = (config.strategy == GroupingStrategy::Drain)
+            .then(|| Mutex::new(Drain::new(config.drain_depth, config.drain_similarity)));
+        self.config = config;
+        self
+    }
+
+    /// Group entries at `min_severity` and above (errors and warnings by
+    /// default)
+    pub fn with_min_severity(mut self, min_severity: Severity) -> Self {
+        self.min_severity = min_severity;
+        self
+    }
+
+    /// Set how many sample entries each group keeps.
+    ///
+    /// Groups keep their earliest entries plus the most recent one, so memory
+    /// grows with the number of groups rather than the number of entries.
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
     /// Normalize a message by replacing dynamic values with placeholders
     fn normalize_message(&self, message: &str) -> String {
         self.normalizer
@@ -48,73 +161,192 @@ impl ErrorGrouper {
             .to_string()
     }
 
-    /// Generate a unique ID for an error pattern
-    fn generate_id(pattern: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// What identifies the error in `entry`.
+    ///
+    /// Entries with a stack trace (in the message or a `stack` metadata
+    /// field) are identified by exception type and top in-app frames, so the
+    /// same bug groups together whatever its message says and the same
+    /// exception from different call sites does not. Other entries are
+    /// identified by their normalized message.
+    fn fingerprint(&self, entry: &LogEntry, pattern: &str) -> Fingerprint {
+        let trace = entry
+            .message
+            .contains('\n')
+            .then(|| StackTrace::parse(&entry.message))
+            .flatten()
+            .or_else(|| {
+                entry
+                    .metadata
+                    .extra
+                    .iter()
+                    .filter(|(key, _)| key.to_lowercase().contains("stack"))
+                    .find_map(|(_, value)| StackTrace::parse(value))
+            });
+
+        match trace {
+            Some(trace) if self.config.stack_frames > 0 => Fingerprint {
+                pattern: String::new(),
+                frames: trace.in_app_frames(&self.config.in_app_prefixes, self.config.stack_frames),
+                exception: trace.exception,
+                mined: false,
+            },
+            _ => self.pattern_fingerprint(pattern),
+        }
+    }
 
-        let mut hasher = DefaultHasher::new();
-        pattern.hash(&mut hasher);
-        format!("err-{:x}", hasher.finish())
+    /// What identifies an entry without a stack trace
+    fn pattern_fingerprint(&self, pattern: &str) -> Fingerprint {
+        if self.drain.is_some() {
+            Fingerprint::template(pattern)
+        } else {
+            Fingerprint::new(pattern)
+        }
     }
 
     pub fn group(&self, entries: Vec<LogEntry>) -> Result<Vec<ErrorGroup>> {
         let mut groups: HashMap<String, ErrorGroup> = HashMap::new();
 
         for entry in entries {
-            // Only group errors and warnings
-            if !matches!(entry.severity, Severity::Error | Severity::Warning) {
-                continue;
+            self.add_entry(&mut groups, entry);
+        }
+
+        Ok(Self::into_sorted(groups))
+    }
+
+    /// Turn incrementally built groups into the sorted result of `group`
+    pub fn into_sorted(groups: HashMap<String, ErrorGroup>) -> Vec<ErrorGroup> {
+        let mut result: Vec<ErrorGroup> = groups.into_values().collect();
+        Self::sort_groups(&mut result);
+        result
+    }
+
+    /// Add a single entry to an existing set of groups.
+    ///
+    /// Used by callers that receive entries incrementally (e.g. `watch` and
+    /// streaming `investigate`). Returns `None` when the entry is below the
+    /// grouper's minimum severity.
+    pub fn add_entry(
+        &self,
+        groups: &mut HashMap<String, ErrorGroup>,
+        entry: LogEntry,
+    ) -> Option<GroupUpdate> {
+        if !entry.severity.at_least(self.min_severity) {
+            return None;
+        }
+
+        let pattern = match &self.drain {
+            Some(drain) => {
+                // Stack frames are left to the stack trace fingerprint
+                let first_line = entry.message.lines().next().unwrap_or("");
+                let mined = drain.lock().unwrap().add(first_line);
+                if let Some(previous) = &mined.previous {
+                    self.rename_group(groups, previous, &mined.template);
+                }
+                mined.template
             }
+            None => self.normalize_message(&entry.message),
+        };
+        let id = self.fingerprint(&entry, &pattern).id();
 
-            let pattern = self.normalize_message(&entry.message);
-            let id = Self::generate_id(&pattern);
-
-            groups
-                .entry(id.clone())
-                .and_modify(|group| {
-                    group.count += 1;
-                    if let Some(ts) = entry.timestamp {
-                        if ts > group.last_seen {
-                            group.last_seen = ts;
-                        }
-                        if ts < group.first_seen {
-                            group.first_seen = ts;
-                        }
-                    }
-                    group.entries.push(entry.clone());
-                })
-                .or_insert_with(|| {
-                    let timestamp = entry.timestamp.unwrap_or_else(chrono::Utc::now);
-                    ErrorGroup {
-                        id,
-                        pattern,
-                        count: 1,
-                        first_seen: timestamp,
-                        last_seen: timestamp,
-                        severity: entry.severity,
-                        entries: vec![entry],
-                        analysis: None,
-                    }
-                });
+        if let Some(group) = groups.get_mut(&id) {
+            group.count += 1;
+            self.record(group, entry);
+            return Some(GroupUpdate::Updated(id));
         }
 
-        let mut result: Vec<ErrorGroup> = groups.into_values().collect();
+        let timestamp = entry.timestamp.unwrap_or_else(chrono::Utc::now);
+        let sources = entry.source.iter().cloned().collect();
+        groups.insert(
+            id.clone(),
+            ErrorGroup {
+                id: id.clone(),
+                pattern,
+                count: 1,
+                first_seen: timestamp,
+                last_seen: timestamp,
+                severity: entry.severity,
+                entries: vec![entry],
+                analysis: None,
+                sources,
+            },
+        );
+        Some(GroupUpdate::Created(id))
+    }
+
+    /// Update `group`'s sources, time range and samples with `entry`
+    fn record(&self, group: &mut ErrorGroup, entry: LogEntry) {
+        if let Some(source) = &entry.source {
+            if !group.sources.contains(source) {
+                group.sources.push(source.clone());
+            }
+        }
+        if let Some(ts) = entry.timestamp {
+            if ts > group.last_seen {
+                group.last_seen = ts;
+            }
+            if ts < group.first_seen {
+                group.first_seen = ts;
+            }
+        }
+        self.keep_sample(&mut group.entries, entry);
+    }
+
+    /// Add `entry` to a group's samples
+    fn keep_sample(&self, samples: &mut Vec<LogEntry>, entry: LogEntry) {
+        if samples.len() < self.max_samples {
+            samples.push(entry);
+        } else if let Some(last) = samples.last_mut() {
+            // Keep the latest occurrence in the last slot
+            *last = entry;
+        }
+    }
 
-        // Sort by severity (Error first) then by count (most frequent first)
-        result.sort_by(|a, b| {
-            let severity_order = |s: &Severity| match s {
-                Severity::Error => 0,
-                Severity::Warning => 1,
-                _ => 2,
-            };
+    /// Rename the groups of a Drain template that generalized.
+    ///
+    /// Stack trace groups keep their id and only take the new pattern; the
+    /// template's own group moves to its new id, merging into a group that
+    /// already has that id.
+    fn rename_group(&self, groups: &mut HashMap<String, ErrorGroup>, from: &str, to: &str) {
+        for group in groups.values_mut() {
+            if group.pattern == from {
+                group.pattern = to.to_string();
+            }
+        }
+
+        let Some(mut group) = groups.remove(&Fingerprint::template(from).id()) else {
+            return;
+        };
+        let id = Fingerprint::template(to).id();
+
+        match groups.get_mut(&id) {
+            Some(existing) => {
+                existing.count += group.count;
+                existing.first_seen = existing.first_seen.min(group.first_seen);
+                existing.last_seen = existing.last_seen.max(group.last_seen);
+                for source in group.sources {
+                    if !existing.sources.contains(&source) {
+                        existing.sources.push(source);
+                    }
+                }
+                for entry in group.entries {
+                    self.keep_sample(&mut existing.entries, entry);
+                }
+            }
+            None => {
+                group.id = id.clone();
+                groups.insert(id, group);
+            }
+        }
+    }
 
-            severity_order(&a.severity)
-                .cmp(&severity_order(&b.severity))
+    /// Sort groups by severity (Error first) then by count (most frequent first)
+    pub fn sort_groups(groups: &mut [ErrorGroup]) {
+        groups.sort_by(|a, b| {
+            a.severity
+                .rank()
+                .cmp(&b.severity.rank())
                 .then_with(|| b.count.cmp(&a.count))
         });
-
-        Ok(result)
     }
 }
 
@@ -154,6 +386,7 @@ mod tests {
                     extra: HashMap::new(),
                 },
                 raw: "".to_string(),
+                source: None,
             },
             LogEntry {
                 timestamp: Some(chrono::Utc::now()),
@@ -167,6 +400,7 @@ mod tests {
                     extra: HashMap::new(),
                 },
                 raw: "".to_string(),
+                source: None,
             },
         ];
 
@@ -174,4 +408,330 @@ mod tests {
         assert_eq!(groups.len(), 1);
         assert_eq!(groups[0].count, 2);
     }
+
+    #[test]
+    fn test_add_entry_incremental() {
+        let grouper = ErrorGrouper::new();
+        let mut groups = HashMap::new();
+
+        let entry = |severity: Severity, message: &str| LogEntry {
+            timestamp: Some(chrono::Utc::now()),
+            severity,
+            message: message.to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: "".to_string(),
+            source: None,
+        };
+
+        let first = grouper.add_entry(
+            &mut groups,
+            entry(Severity::Error, "Timeout after 30000 ms"),
+        );
+        let id = match first {
+            Some(GroupUpdate::Created(id)) => id,
+            other => panic!("expected new group, got {:?}", other),
+        };
+
+        let second = grouper.add_entry(
+            &mut groups,
+            entry(Severity::Error, "Timeout after 45000 ms"),
+        );
+        assert_eq!(second, Some(GroupUpdate::Updated(id.clone())));
+        assert_eq!(groups[&id].count, 2);
+
+        assert_eq!(
+            grouper.add_entry(&mut groups, entry(Severity::Info, "Started")),
+            None
+        );
+        assert_eq!(groups.len(), 1);
+
+        let errors_only = ErrorGrouper::new().with_min_severity(Severity::Error);
+        assert_eq!(
+            errors_only.add_entry(&mut groups, entry(Severity::Warning, "Slow query")),
+            None
+        );
+        let with_info = ErrorGrouper::new().with_min_severity(Severity::Info);
+        assert!(with_info
+            .add_entry(&mut groups, entry(Severity::Info, "Started"))
+            .is_some());
+        assert_eq!(
+            with_info.add_entry(&mut groups, entry(Severity::Debug, "Tick")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keeps_every_entry_by_default() {
+        let entries = (0..DEFAULT_MAX_SAMPLES + 50)
+            .map(|i| LogEntry {
+                timestamp: Some(chrono::Utc::now()),
+                severity: Severity::Error,
+                message: format!("Request {} failed", 100000 + i),
+                metadata: LogMetadata {
+                    file: None,
+                    line: None,
+                    function: None,
+                    thread: None,
+                    extra: HashMap::new(),
+                },
+                raw: "".to_string(),
+                source: None,
+            })
+            .collect();
+
+        let groups = ErrorGrouper::new().group(entries).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].entries.len(), DEFAULT_MAX_SAMPLES + 50);
+    }
+
+    #[test]
+    fn test_add_entry_caps_samples() {
+        let grouper = ErrorGrouper::new().with_max_samples(3);
+        let mut groups = HashMap::new();
+
+        for i in 0..10 {
+            grouper.add_entry(
+                &mut groups,
+                LogEntry {
+                    timestamp: Some(chrono::Utc::now()),
+                    severity: Severity::Error,
+                    message: format!("Request {} failed", 100000 + i),
+                    metadata: LogMetadata {
+                        file: None,
+                        line: None,
+                        function: None,
+                        thread: None,
+                        extra: HashMap::new(),
+                    },
+                    raw: "".to_string(),
+                    source: Some(format!("/var/log/app.log.{}", i % 2)),
+                },
+            );
+        }
+
+        let groups = ErrorGrouper::into_sorted(groups);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].count, 10);
+
+        let messages: Vec<_> = groups[0].entries.iter().map(|e| &e.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Request 100000 failed",
+                "Request 100001 failed",
+                "Request 100009 failed"
+            ]
+        );
+        assert_eq!(
+            groups[0].sources,
+            ["/var/log/app.log.0", "/var/log/app.log.1"]
+        );
+    }
+
+    #[test]
+    fn test_stack_trace_fingerprint() {
+        let entry = |message: &str, frame: &str| LogEntry {
+            timestamp: Some(chrono::Utc::now()),
+            severity: Severity::Error,
+            message: format!(
+                "java.lang.NullPointerException: {}\n\
+                 \tat com.shop.{}(Orders.java:42)\n\
+                 \tat org.springframework.web.servlet.FrameworkServlet.service(Servlet.java:1)",
+                message, frame
+            ),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: "".to_string(),
+            source: None,
+        };
+
+        let groups = ErrorGrouper::new()
+            .group(vec![
+                entry("order is null", "Orders.load"),
+                entry("customer of order is null", "Orders.load"),
+                entry("order is null", "Invoices.send"),
+            ])
+            .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].count, 2);
+
+        // With only framework frames in-app, the traces look the same
+        let config = GroupingConfig {
+            in_app_prefixes: vec!["org.springframework.".to_string()],
+            ..GroupingConfig::default()
+        };
+        let groups = ErrorGrouper::new()
+            .with_config(config)
+            .group(vec![
+                entry("order is null", "Orders.load"),
+                entry("order is null", "Invoices.send"),
+            ])
+            .unwrap();
+        assert_eq!(groups.len(), 1);
+    }
+
+    #[test]
+    fn test_parsed_traces_keep_exception() {
+        use crate::parser::{LogParser, PlainTextParser, StackTraceParser};
+        use std::sync::Arc;
+
+        let log = "\
+2025-11-17 10:00:00 ERROR Unhandled exception in /orders/17
+Traceback (most recent call last):
+  File \"/app/shop/views.py\", line 10, in get
+    order = load(order_id)
+  File \"/app/shop/orders.py\", line 42, in load
+    return orders[order_id]
+KeyError: 'customer'
+2025-11-17 10:00:01 ERROR [main] Request failed
+java.lang.NullPointerException: order is null
+\tat com.shop.Orders.load(Orders.java:42)
+\tat org.springframework.web.servlet.FrameworkServlet.service(FrameworkServlet.java:1)
+2025-11-17 10:00:02 INFO Recovered";
+        let lines: Vec<String> = log.lines().map(String::from).collect();
+        let parser = StackTraceParser::new(Arc::new(PlainTextParser::new()));
+        let groups = ErrorGrouper::new()
+            .group(parser.parse_lines(&lines).unwrap())
+            .unwrap();
+
+        let ids: Vec<&str> = groups.iter().map(|g| g.id.as_str()).collect();
+        let python = Fingerprint {
+            pattern: String::new(),
+            exception: Some("KeyError".to_string()),
+            frames: vec![
+                "/app/shop/orders.py:load".to_string(),
+                "/app/shop/views.py:get".to_string(),
+            ],
+            mined: false,
+        };
+        let java = Fingerprint {
+            pattern: String::new(),
+            exception: Some("java.lang.NullPointerException".to_string()),
+            frames: vec!["com.shop.Orders.load".to_string()],
+            mined: false,
+        };
+        assert_eq!(groups.len(), 2);
+        assert!(ids.contains(&python.id().as_str()));
+        assert!(ids.contains(&java.id().as_str()));
+    }
+
+    #[test]
+    fn test_drain_strategy() {
+        let entry = |message: &str| LogEntry {
+            timestamp: Some(chrono::Utc::now()),
+            severity: Severity::Error,
+            message: message.to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: "".to_string(),
+            source: None,
+        };
+        let config = GroupingConfig {
+            strategy: "drain".parse().unwrap(),
+            ..GroupingConfig::default()
+        };
+
+        let groups = ErrorGrouper::new()
+            .with_config(config)
+            .group(vec![
+                entry("Connection to db-primary failed after 30 ms"),
+                entry("Connection to db-replica failed after 45 ms"),
+                entry("Connection to cache failed after 5 ms"),
+                entry("User alice@example.com locked out"),
+                entry("User bob@example.com locked out"),
+            ])
+            .unwrap();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].pattern, "Connection to <*> failed after <*> ms");
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(
+            groups[0].id,
+            Fingerprint::template("Connection to <*> failed after <*> ms").id()
+        );
+        assert_eq!(groups[1].pattern, "User <*> locked out");
+        assert!("tree".parse::<GroupingStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_drain_renames_groups() {
+        let entry = |message: &str| LogEntry {
+            timestamp: Some(chrono::Utc::now()),
+            severity: Severity::Error,
+            message: message.to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: "".to_string(),
+            source: None,
+        };
+        let drain = |similarity: f64| {
+            ErrorGrouper::new().with_config(GroupingConfig {
+                strategy: GroupingStrategy::Drain,
+                drain_similarity: similarity,
+                ..GroupingConfig::default()
+            })
+        };
+
+        // Stack trace groups keep their id but follow the template
+        let trace = |order: &str| {
+            entry(&format!(
+                "java.lang.IllegalStateException: order {} is closed\n\
+                 \tat com.shop.Orders.close(Orders.java:42)",
+                order
+            ))
+        };
+        let groups = drain(0.5)
+            .group(vec![trace("alpha"), trace("beta")])
+            .unwrap();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].id.starts_with("err-v2-"));
+        assert_eq!(
+            groups[0].pattern,
+            "java.lang.IllegalStateException: order <*> is closed"
+        );
+
+        // Merged groups keep their earliest samples and the latest one
+        let grouper = drain(0.9).with_max_samples(2);
+        let mut groups = HashMap::new();
+        for message in [
+            "Job b failed",
+            "Job b failed",
+            "Job a failed",
+            "Job a failed",
+        ] {
+            grouper.add_entry(&mut groups, entry(message));
+        }
+        let mut e = entry("Job a failed");
+        e.raw = "latest".to_string();
+        grouper.add_entry(&mut groups, e);
+        assert_eq!(groups.len(), 2);
+
+        grouper.rename_group(&mut groups, "Job a failed", "Job b failed");
+        let groups = ErrorGrouper::into_sorted(groups);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].count, 5);
+        assert_eq!(groups[0].entries.len(), 2);
+        assert_eq!(groups[0].entries[1].raw, "latest");
+    }
 }
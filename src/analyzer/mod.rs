@@ -1,4 +1,13 @@
+pub mod drain;
+pub mod filter;
+pub mod fingerprint;
 pub mod grouper;
+pub mod stack;
+
+pub use filter::{EntryFilter, Predicate};
+pub use fingerprint::Fingerprint;
+pub use grouper::{GroupingConfig, GroupingStrategy};
+pub use stack::StackTrace;
 
 use crate::types::{ErrorGroup, LogEntry};
 use crate::Result;
@@ -20,4 +29,13 @@ impl Analyzer {
         let grouper = grouper::ErrorGrouper::new();
         grouper.group(entries)
     }
+
+    /// Group the entries that pass `filter`
+    pub fn analyze_filtered(
+        &self,
+        entries: Vec<LogEntry>,
+        filter: &EntryFilter,
+    ) -> Result<Vec<ErrorGroup>> {
+        self.analyze(filter.apply(entries))
+    }
 }
@@ -0,0 +1,271 @@
+//! Exception types and frames read back from stack traces, for fingerprinting
+
+use crate::parser::stack_trace::frame_patterns;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Frames of common frameworks, runtimes and standard libraries, skipped
+/// when no in-app prefixes are configured
+const LIBRARY_PREFIXES: &[&str] = &[
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "kotlin.",
+    "kotlinx.",
+    "scala.",
+    "org.springframework.",
+    "org.apache.",
+    "io.netty.",
+    "reactor.",
+    "node:",
+    "internal/",
+    "<frozen",
+    "runtime.",
+    "net/http.",
+    "std::",
+    "core::",
+    "alloc::",
+    "tokio::",
+    "System.",
+    "Microsoft.",
+];
+
+/// Path segments of installed third-party packages
+const LIBRARY_PATHS: &[&str] = &[
+    "node_modules/",
+    "site-packages/",
+    "dist-packages/",
+    "usr/lib/",
+];
+
+/// What a stack trace says about where an error was thrown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackTrace {
+    /// Exception or error type (`java.lang.NullPointerException`, `KeyError`,
+    /// `panic`)
+    pub exception: Option<String>,
+    /// Normalized frames, innermost (where the error was thrown) first
+    pub frames: Vec<String>,
+}
+
+struct Patterns {
+    exception: Regex,
+    /// Compiler-generated suffixes: Rust symbol hashes, Java lambda and
+    /// anonymous class numbers
+    generated: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        exception: Regex::new(
+            r"\b((?:[A-Za-z_$][\w$]*\.)*[A-Z]?[\w$]*?(?:Exception|Error|Throwable|Fault|Interrupt))\b",
+        )
+        .unwrap(),
+        generated: Regex::new(r"::h[0-9a-f]{16}$|\$\d+|\$\$Lambda\$[\w/$]*|/0x[0-9a-f]+").unwrap(),
+    })
+}
+
+impl StackTrace {
+    /// Read the stack trace in `text`, or `None` when it has no frames
+    pub fn parse(text: &str) -> Option<Self> {
+        let shared = frame_patterns();
+        let patterns = patterns();
+        let mut frames = Vec::new();
+        let mut message_lines = Vec::new();
+        let mut python = false;
+        let mut go = false;
+        let mut panic = false;
+
+        for line in text.lines() {
+            if let Some(caps) = shared.dotted.captures(line) {
+                frames.push(caps[1].to_string());
+            } else if let Some(caps) = shared.javascript.captures(line) {
+                frames.push(match caps.get(1) {
+                    Some(function) => format!("{} ({})", function.as_str(), &caps[2]),
+                    None => caps[2].to_string(),
+                });
+            } else if let Some(caps) = shared.python.captures(line) {
+                python = true;
+                frames.push(format!("{}:{}", &caps[1], &caps[2]));
+            } else if let Some(caps) = shared.rust.captures(line) {
+                frames.push(caps[1].to_string());
+            } else if go && shared.go.is_match(line) {
+                // Drop the call arguments but keep a `(*T)` receiver
+                let function = line.rfind('(').map_or(line, |end| &line[..end]);
+                frames.push(function.to_string());
+            } else if shared.goroutine.is_match(line) {
+                go = true;
+            } else if !line.starts_with('\t') && !line.trim_start().starts_with("at ") {
+                if line.starts_with("panic:") || line.contains("panicked at") {
+                    panic = true;
+                }
+                message_lines.push(line);
+            }
+        }
+
+        if frames.is_empty() {
+            return None;
+        }
+
+        // Python prints the innermost frame and the exception last
+        if python {
+            frames.reverse();
+            message_lines.reverse();
+        }
+        let exception = if panic {
+            Some("panic".to_string())
+        } else {
+            // Prefer the trace's own exception line over the log message
+            message_lines
+                .iter()
+                .filter(|line| shared.header.is_match(line))
+                .chain(&message_lines)
+                .find_map(|line| {
+                    patterns
+                        .exception
+                        .captures(line)
+                        .map(|caps| caps[1].to_string())
+                })
+        };
+
+        let frames = frames
+            .into_iter()
+            .map(|frame| patterns.generated.replace_all(&frame, "").into_owned())
+            .collect();
+        Some(Self { exception, frames })
+    }
+
+    /// The top `limit` frames of application code.
+    ///
+    /// With `in_app_prefixes`, frames starting with one of them are kept;
+    /// otherwise well-known framework and library frames are dropped. When
+    /// no frame is left, the top frames are used as they are.
+    pub fn in_app_frames(&self, in_app_prefixes: &[String], limit: usize) -> Vec<String> {
+        let in_app: Vec<String> = self
+            .frames
+            .iter()
+            .filter(|frame| {
+                let frame = frame.trim_start_matches("./").trim_start_matches('/');
+                if in_app_prefixes.is_empty() {
+                    !LIBRARY_PREFIXES.iter().any(|p| frame.starts_with(p))
+                        && !LIBRARY_PATHS.iter().any(|p| frame.contains(p))
+                } else {
+                    in_app_prefixes
+                        .iter()
+                        .any(|p| frame.starts_with(p.trim_start_matches('/')))
+                }
+            })
+            .take(limit)
+            .cloned()
+            .collect();
+
+        if in_app.is_empty() {
+            self.frames.iter().take(limit).cloned().collect()
+        } else {
+            in_app
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_java_and_csharp() {
+        let trace = StackTrace::parse(
+            "java.lang.NullPointerException: order 42 has no customer\n\
+             \tat com.shop.OrderService.lambda$load$3(OrderService.java:88)\n\
+             \tat java.util.Optional.map(Optional.java:265)\n\
+             \tat com.shop.OrderController.get(OrderController.java:31)",
+        )
+        .unwrap();
+        assert_eq!(
+            trace.exception.as_deref(),
+            Some("java.lang.NullPointerException")
+        );
+        assert_eq!(trace.frames[0], "com.shop.OrderService.lambda$load");
+        assert_eq!(
+            trace.in_app_frames(&[], 5),
+            [
+                "com.shop.OrderService.lambda$load",
+                "com.shop.OrderController.get"
+            ]
+        );
+        assert_eq!(
+            trace.in_app_frames(&["com.shop.OrderController".to_string()], 5),
+            ["com.shop.OrderController.get"]
+        );
+
+        let trace = StackTrace::parse(
+            "System.InvalidOperationException: Sequence contains no elements\n   \
+             at Shop.Orders.Load(Int32 id) in /src/Orders.cs:line 12",
+        )
+        .unwrap();
+        assert_eq!(
+            trace.exception.as_deref(),
+            Some("System.InvalidOperationException")
+        );
+        assert_eq!(trace.frames, ["Shop.Orders.Load"]);
+    }
+
+    #[test]
+    fn test_parse_python_and_javascript() {
+        let trace = StackTrace::parse(
+            "Traceback (most recent call last):\n  \
+             File \"/app/shop/views.py\", line 10, in get\n  \
+             File \"/usr/lib/python3/site-packages/requests/api.py\", line 5, in get\n  \
+             File \"/app/shop/orders.py\", line 42, in load\n\
+             KeyError: 'customer'",
+        )
+        .unwrap();
+        assert_eq!(trace.exception.as_deref(), Some("KeyError"));
+        assert_eq!(
+            trace.in_app_frames(&[], 5),
+            ["/app/shop/orders.py:load", "/app/shop/views.py:get"]
+        );
+
+        let trace = StackTrace::parse(
+            "TypeError: Cannot read properties of undefined\n    \
+             at loadOrder (/app/src/orders.js:42:7)\n    \
+             at /app/node_modules/express/lib/router.js:10:3",
+        )
+        .unwrap();
+        assert_eq!(trace.exception.as_deref(), Some("TypeError"));
+        assert_eq!(
+            trace.in_app_frames(&[], 5),
+            ["loadOrder (/app/src/orders.js)"]
+        );
+    }
+
+    #[test]
+    fn test_parse_go_and_rust() {
+        let trace = StackTrace::parse(
+            "panic: runtime error: index out of range\n\n\
+             goroutine 1 [running]:\n\
+             main.(*Orders).load(0xc000010000)\n\
+             \t/app/orders.go:42 +0x1d\n\
+             main.main()\n\
+             \t/app/main.go:10 +0x25",
+        )
+        .unwrap();
+        assert_eq!(trace.exception.as_deref(), Some("panic"));
+        assert_eq!(trace.frames, ["main.(*Orders).load", "main.main"]);
+
+        let trace = StackTrace::parse(
+            "thread 'main' panicked at src/orders.rs:42:5\n\
+             stack backtrace:\n   \
+             0: std::panicking::begin_panic::h0123456789abcdef\n   \
+             1: shop::orders::load::hfedcba9876543210\n             \
+             at ./src/orders.rs:42:5",
+        )
+        .unwrap();
+        assert_eq!(trace.exception.as_deref(), Some("panic"));
+        assert_eq!(trace.in_app_frames(&[], 5), ["shop::orders::load"]);
+
+        assert!(StackTrace::parse("ERROR Payment failed for order 42").is_none());
+    }
+}
@@ -23,7 +23,8 @@ pub enum Commands {
         #[arg(default_value = "-")]
         files: Vec<String>,
 
-        /// Log format (auto, json, apache, nginx, syslog, plain)
+        /// Log format (auto, json, logfmt, apache, nginx, syslog, plain, ecs, bunyan, pino, clef,
+        /// gcp, cri, docker, journald, or a [[formats]] name)
         #[arg(long, default_value = "auto")]
         log_format: String,
 
@@ -31,6 +32,23 @@ pub enum Commands {
         #[arg(long)]
         no_multiline: bool,
 
+        /// Parser configuration file (defaults to the [parser] section of the config)
+        #[arg(long)]
+        parser_config: Option<String>,
+
+        /// Read directories recursively
+        #[arg(long, short = 'r')]
+        recursive: bool,
+
+        /// Only read files matching this glob from directories and archives (repeatable,
+        /// e.g. '**/*.log*')
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Skip files and directories matching this glob (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
         /// Show parsing statistics
         #[arg(long)]
         stats: bool,
@@ -63,7 +81,7 @@ pub enum Commands {
         #[arg(long, short = 'f', default_value = "html")]
         format: String,
 
-        /// Save output to file
+        /// Save output to file (any format) instead of stdout or the reports directory
         #[arg(long, short = 'o')]
         output: Option<String>,
 
@@ -71,10 +89,28 @@ pub enum Commands {
         #[arg(long, default_value = "0")]
         limit: usize,
 
-        /// Filter by severity (error, warn, info)
+        /// Minimum severity to group (error, warn, info, debug, trace; default: warn)
         #[arg(long)]
         severity: Option<String>,
 
+        /// Only entries at or after this time (e.g. 2h, 14:02, 2025-11-17 14:02)
+        #[arg(long, value_name = "TIME")]
+        since: Option<String>,
+
+        /// Only entries at or before this time
+        #[arg(long, value_name = "TIME")]
+        until: Option<String>,
+
+        /// How messages are grouped: regex (normalize known dynamic values) or drain (mine
+        /// templates); defaults to grouping.strategy in the config
+        #[arg(long, value_name = "STRATEGY")]
+        grouping: Option<String>,
+
+        /// Only entries matching a field condition (repeatable, e.g. service=checkout,
+        /// status>=500, message~timeout)
+        #[arg(long = "where", value_name = "PREDICATE")]
+        filters: Vec<String>,
+
         /// Disable MCP tools integration
         #[arg(long)]
         no_mcp: bool,
@@ -86,12 +122,50 @@ pub enum Commands {
         /// Maximum concurrent AI analysis requests (1-100)
         #[arg(long)]
         concurrency: Option<usize>,
+
+        /// Do not save this run to history
+        #[arg(long)]
+        no_history: bool,
+
+        /// History database to save the run to (default: ~/.logai/history.db)
+        #[arg(long, value_name = "PATH")]
+        history_db: Option<String>,
     },
 
     /// Watch and analyze logs in real-time
     Watch {
         /// Log file to watch (use '-' for stdin)
+        #[arg(default_value = "-")]
         file: String,
+
+        /// Log format (auto, json, logfmt, apache, nginx, syslog, plain, ecs, bunyan, pino, clef,
+        /// gcp, cri, docker, journald, or a [[formats]] name)
+        #[arg(long, default_value = "auto")]
+        log_format: String,
+
+        /// Disable multi-line log handling (e.g., stack traces)
+        #[arg(long)]
+        no_multiline: bool,
+
+        /// Process existing file content before following new lines
+        #[arg(long)]
+        from_start: bool,
+
+        /// Polling interval in milliseconds
+        #[arg(long, default_value = "500")]
+        interval: u64,
+
+        /// Rate window in seconds used for spike detection
+        #[arg(long, default_value = "10")]
+        window: u64,
+
+        /// Report a spike when a group's rate exceeds its baseline by this factor
+        #[arg(long, default_value = "3.0")]
+        spike_factor: f64,
+
+        /// Maximum number of error groups to show
+        #[arg(long, default_value = "10")]
+        limit: usize,
     },
 
     /// Manage configuration
@@ -100,6 +174,28 @@ pub enum Commands {
         action: ConfigAction,
     },
 
+    /// Browse past investigate runs
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+
+        /// History database to read (default: ~/.logai/history.db)
+        #[arg(long, global = true, value_name = "PATH")]
+        history_db: Option<String>,
+    },
+
+    /// Manage the AI response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Run logai as an MCP server
+    Mcp {
+        #[command(subcommand)]
+        action: McpAction,
+    },
+
     /// Clean up generated reports
     Clean {
         /// Skip confirmation prompt
@@ -120,3 +216,84 @@ pub enum ConfigAction {
     /// Show current configuration
     Show,
 }
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List past runs (newest first)
+    List {
+        /// Maximum number of runs to list (0 = all)
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+    /// Re-render a past run
+    Show {
+        /// Run id (see `logai history list`)
+        id: i64,
+
+        /// Output format (terminal, json, html)
+        #[arg(long, short = 'f', default_value = "terminal")]
+        format: String,
+
+        /// Maximum number of error groups to show
+        #[arg(long, default_value = "10")]
+        limit: usize,
+
+        /// Save output to file (any format) instead of stdout or the reports directory
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+    },
+    /// List the runs in which an error group was seen
+    Group {
+        /// Error group id (e.g. err-v2-a0e6ac0f68eb384e)
+        id: String,
+    },
+    /// Delete a past run
+    Delete {
+        /// Run id
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Show cache statistics
+    Stats,
+    /// Remove all cached responses
+    Clear,
+    /// Remove cached responses older than the TTL
+    Prune {
+        /// Override the configured TTL (days)
+        #[arg(long)]
+        days: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum McpAction {
+    /// Serve parse_logs, group_errors, analyze_group and get_run as MCP tools
+    Serve {
+        /// Serve streamable HTTP on this address instead of stdio (e.g. 127.0.0.1:8808)
+        #[arg(long)]
+        http: Option<String>,
+
+        /// AI provider for analyze_group (openai, claude, gemini, ollama, bedrock, none)
+        #[arg(long, default_value = "none")]
+        ai: String,
+
+        /// AI model to use
+        #[arg(long)]
+        model: Option<String>,
+
+        /// API key for AI provider
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Ollama host (default: http://localhost:11434)
+        #[arg(long)]
+        ollama_host: Option<String>,
+
+        /// AWS region for Bedrock
+        #[arg(long)]
+        region: Option<String>,
+    },
+}
@@ -0,0 +1,73 @@
+//! Cache command implementation.
+//!
+//! This module contains the business logic for the `cache` command,
+//! which inspects and maintains the AI response cache.
+
+use crate::{
+    ai::{AIConfig, AnalysisCache},
+    cli::CacheAction,
+    Result,
+};
+use std::time::Duration;
+
+/// Cache command implementation
+pub struct CacheCommand;
+
+impl CacheCommand {
+    /// Execute the cache command
+    pub fn execute(action: CacheAction) -> Result<()> {
+        let config = AIConfig::load().unwrap_or_default();
+        let ttl_days = config.analysis.cache_ttl_days;
+
+        match action {
+            CacheAction::Stats => Self::show_stats(&Self::open(ttl_days)?),
+            CacheAction::Clear => Self::clear(&Self::open(ttl_days)?),
+            CacheAction::Prune { days } => Self::prune(&Self::open(days.unwrap_or(ttl_days))?),
+        }
+    }
+
+    fn open(ttl_days: u64) -> Result<AnalysisCache> {
+        let ttl = (ttl_days > 0).then(|| Duration::from_secs(ttl_days * 86400));
+        Ok(AnalysisCache::new()?.with_ttl(ttl))
+    }
+
+    /// Print cache statistics
+    pub fn show_stats(cache: &AnalysisCache) -> Result<()> {
+        let stats = cache.stats()?;
+
+        println!("💾 Analysis Cache");
+        if let Some(path) = &stats.path {
+            println!("   Location: {}", path.display());
+        }
+        println!("   Size: {:.1} KB", stats.size_bytes as f64 / 1024.0);
+        println!("   Entries: {}", stats.entries);
+        println!("   Expired: {}", stats.expired);
+
+        if !stats.by_provider.is_empty() {
+            println!("\n   By provider:");
+            for (provider, count) in &stats.by_provider {
+                println!("     {}: {}", provider, count);
+            }
+        }
+
+        if stats.expired > 0 {
+            println!("\n💡 Remove expired entries with: logai cache prune");
+        }
+
+        Ok(())
+    }
+
+    /// Remove all cached responses
+    pub fn clear(cache: &AnalysisCache) -> Result<()> {
+        let deleted = cache.clear()?;
+        println!("✅ Removed {} cached analyses", deleted);
+        Ok(())
+    }
+
+    /// Remove cached responses older than the cache TTL
+    pub fn prune(cache: &AnalysisCache) -> Result<()> {
+        let deleted = cache.prune()?;
+        println!("✅ Pruned {} expired cached analyses", deleted);
+        Ok(())
+    }
+}
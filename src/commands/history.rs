@@ -0,0 +1,153 @@
+//! History command implementation.
+//!
+//! This module contains the business logic for the `history` command,
+//! which lists past investigate runs and re-renders their results.
+
+use crate::{
+    analyzer::fingerprint::is_mined_id,
+    cli::HistoryAction,
+    commands::InvestigateCommand,
+    storage::{RunHistory, RunRecord},
+    Result,
+};
+
+/// History command implementation
+pub struct HistoryCommand;
+
+impl HistoryCommand {
+    /// Execute the history command on the database at `path`
+    /// (`~/.logai/history.db` when not set)
+    pub fn execute(action: HistoryAction, path: Option<&str>) -> Result<()> {
+        let mut history = match path {
+            Some(path) => RunHistory::open(path)?,
+            None => RunHistory::new()?,
+        };
+        match action {
+            HistoryAction::List { limit } => Self::list_runs(&history, limit),
+            HistoryAction::Show {
+                id,
+                format,
+                limit,
+                output,
+            } => Self::show_run(&history, id, &format, limit, output.as_deref()),
+            HistoryAction::Group { id } => Self::group_runs(&history, &id),
+            HistoryAction::Delete { id } => Self::delete_run(&mut history, id),
+        }
+    }
+
+    /// Print a summary line for each stored run
+    pub fn list_runs(history: &RunHistory, limit: usize) -> Result<()> {
+        print!("{}", Self::format_runs(&history.list_runs(limit)?));
+        Ok(())
+    }
+
+    /// The `history list` output for `runs`
+    pub fn format_runs(runs: &[RunRecord]) -> String {
+        if runs.is_empty() {
+            return "No runs recorded yet. Run `logai investigate` to create one.\n".to_string();
+        }
+
+        let mut text = String::from("🗂️  Investigate history\n\n");
+        for run in runs {
+            text.push_str(&Self::format_run(run));
+            text.push('\n');
+        }
+        text.push_str("\n💡 Re-render a run with: logai history show <id>\n");
+        text
+    }
+
+    /// Re-render a stored run without re-parsing or re-analyzing, writing
+    /// it to `output` when given
+    pub fn show_run(
+        history: &RunHistory,
+        id: i64,
+        format: &str,
+        limit: usize,
+        output: Option<&str>,
+    ) -> Result<()> {
+        let run = history
+            .get_run(id)?
+            .ok_or_else(|| anyhow::anyhow!("Run #{} not found in history", id))?;
+
+        eprintln!("{}\n", Self::format_run(&run));
+
+        let groups = history.load_groups(id)?;
+        if groups.is_empty() {
+            println!("No errors or warnings found in logs.");
+            return Ok(());
+        }
+
+        let config = crate::ai::AIConfig::load().unwrap_or_default();
+        InvestigateCommand::display_output(&groups, format, limit, output, &config)
+    }
+
+    /// Print the runs in which an error group was seen
+    pub fn group_runs(history: &RunHistory, group_id: &str) -> Result<()> {
+        if is_mined_id(group_id) {
+            return Err(anyhow::anyhow!(
+                "{} is a Drain template id, which depends on the logs it was mined from and \
+                 is not comparable across runs; use --grouping regex to track groups in history",
+                group_id
+            ));
+        }
+
+        let occurrences = history.group_occurrences(group_id)?;
+
+        if occurrences.is_empty() {
+            println!("Error group {} not found in history", group_id);
+            return Ok(());
+        }
+
+        println!("🔁 {} seen in {} run(s)\n", group_id, occurrences.len());
+        for occurrence in &occurrences {
+            println!(
+                "#{:<4} {}  {} occurrences",
+                occurrence.run_id,
+                occurrence
+                    .created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+                occurrence.count
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Delete a stored run and its groups
+    pub fn delete_run(history: &mut RunHistory, id: i64) -> Result<()> {
+        if history.delete_run(id)? {
+            println!("✅ Deleted run #{}", id);
+        } else {
+            println!("Run #{} not found in history", id);
+        }
+        Ok(())
+    }
+
+    fn format_run(run: &RunRecord) -> String {
+        let ai = match (&run.ai_provider, &run.model) {
+            (Some(provider), Some(model)) => format!("{} ({})", provider, model),
+            (Some(provider), None) => provider.clone(),
+            _ => "none".to_string(),
+        };
+
+        format!(
+            "#{:<4} {}  {} | parser: {} | {} lines, {} entries | {} groups ({} occurrences) | ai: {}",
+            run.id,
+            run.created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            run.files.join(", "),
+            if run.parser.is_empty() {
+                "-"
+            } else {
+                &run.parser
+            },
+            run.stats.total_lines,
+            run.stats.parsed_entries,
+            run.group_count,
+            run.total_occurrences,
+            ai
+        )
+    }
+}
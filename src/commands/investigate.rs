@@ -5,14 +5,23 @@
 
 use crate::{
     ai,
-    analyzer::Analyzer,
+    analyzer::{
+        filter,
+        grouper::{ErrorGrouper, DEFAULT_MAX_SAMPLES},
+        EntryFilter,
+    },
+    mcp::ToolRegistry,
     output::{html::HtmlFormatter, terminal::TerminalFormatter, OutputFormatter},
-    parser::detector::FormatDetector,
-    types::LogEntry,
+    parser::{
+        detector::FormatDetector, encoding::LossyLineReader, input, ConfiguredParser, EntryStream,
+        ParallelParser, ParserConfig,
+    },
+    types::{ErrorGroup, LogEntry, Severity},
     Result,
 };
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
 use std::sync::Arc;
 
 /// Options for the investigate command
@@ -31,6 +40,93 @@ pub struct InvestigateOptions {
     pub no_mcp: bool,
     pub mcp_config: Option<String>,
     pub concurrency: Option<usize>,
+    pub no_history: bool,
+    /// History database to save the run to (`~/.logai/history.db` when not set)
+    pub history_db: Option<String>,
+    pub no_cache: bool,
+    pub parser_config: Option<String>,
+    /// Read directories recursively
+    pub recursive: bool,
+    /// Globs of files to read from directories and archives
+    pub include: Vec<String>,
+    /// Globs of files and directories to skip
+    pub exclude: Vec<String>,
+    /// Minimum severity to group (`warn` when not set)
+    pub severity: Option<String>,
+    /// File to write the output to
+    pub output: Option<String>,
+    /// Only entries at or after this time (`2h`, `14:02`, RFC 3339, ...)
+    pub since: Option<String>,
+    /// Only entries at or before this time
+    pub until: Option<String>,
+    /// `--where` predicates entries must match
+    pub filters: Vec<String>,
+    /// Grouping strategy (`regex` or `drain`), overriding the config
+    pub grouping: Option<String>,
+}
+
+/// How inputs are parsed
+pub(crate) struct ParseSettings<'a> {
+    pub(crate) log_format: &'a str,
+    pub(crate) no_multiline: bool,
+    pub(crate) config: &'a ParserConfig,
+}
+
+/// Destination for parsed entries.
+///
+/// Errors are grouped as they arrive and only a bounded window of recent
+/// entries is kept (for `query_logs`), so memory does not grow with input size.
+struct EntrySink {
+    grouper: ErrorGrouper,
+    filter: EntryFilter,
+    groups: HashMap<String, ErrorGroup>,
+    recent: VecDeque<LogEntry>,
+    keep_recent: usize,
+    total_lines: usize,
+    parsed_entries: usize,
+    parse_errors: usize,
+    /// Parsed entries spanning several lines (e.g. stack traces)
+    multiline_entries: usize,
+    /// Parsed entries dropped by the filter
+    filtered_out: usize,
+    /// Names of the parsers used (one per input read)
+    parsers: Vec<String>,
+}
+
+impl EntrySink {
+    fn new(grouper: ErrorGrouper, filter: EntryFilter, keep_recent: usize) -> Self {
+        Self {
+            grouper,
+            filter,
+            groups: HashMap::new(),
+            recent: VecDeque::new(),
+            keep_recent,
+            total_lines: 0,
+            parsed_entries: 0,
+            parse_errors: 0,
+            multiline_entries: 0,
+            filtered_out: 0,
+            parsers: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, entry: LogEntry) {
+        self.parsed_entries += 1;
+        if entry.raw.contains('\n') {
+            self.multiline_entries += 1;
+        }
+        if !self.filter.matches(&entry) {
+            self.filtered_out += 1;
+            return;
+        }
+        if self.keep_recent > 0 {
+            if self.recent.len() == self.keep_recent {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(entry.clone());
+        }
+        self.grouper.add_entry(&mut self.groups, entry);
+    }
 }
 
 /// Investigate command implementation
@@ -54,101 +150,212 @@ impl InvestigateCommand {
             no_mcp,
             mcp_config,
             concurrency,
+            no_history,
+            history_db,
+            no_cache,
+            parser_config,
+            recursive,
+            include,
+            exclude,
+            severity,
+            output,
+            since,
+            until,
+            filters,
+            grouping,
         } = opts;
 
-        let mut all_entries = Vec::new();
-        let mut total_lines = 0;
-        let mut parse_errors = 0;
+        let min_severity = match severity.as_deref() {
+            Some(name) => name.parse()?,
+            None => Severity::Warning,
+        };
+        let entry_filter = Self::entry_filter(since.as_deref(), until.as_deref(), &filters)?;
+
+        // Read the config file once; CLI flags override its settings below
+        let config = ai::AIConfig::load().unwrap_or_default();
+
+        let parser_config = Self::load_parser_config(parser_config.as_deref(), &config)?;
+        let filter = input::InputFilter::new(&include, &exclude)?.recursive(recursive);
+
+        // Load MCP settings first: the built-in query_logs tool needs recent
+        // entries kept while parsing
+        let mcp_config = if !no_mcp && ai_provider != "none" {
+            Self::load_mcp_config(mcp_config.as_deref())
+                .map_err(|e| log::warn!("Failed to load MCP config: {}", e))
+                .ok()
+        } else {
+            None
+        };
+        let keep_recent = mcp_config
+            .as_ref()
+            .filter(|config| config.builtin_tools.enabled)
+            .map_or(0, |config| config.builtin_tools.max_log_entries);
+
+        let settings = ParseSettings {
+            log_format: &log_format,
+            no_multiline,
+            config: &parser_config,
+        };
+        let mut grouping_config = config.grouping.clone();
+        if let Some(strategy) = grouping.as_deref() {
+            grouping_config.strategy = strategy.parse()?;
+        }
+        let grouper = ErrorGrouper::new()
+            .with_max_samples(DEFAULT_MAX_SAMPLES)
+            .with_min_severity(min_severity)
+            .with_config(grouping_config);
+        let mut sink = EntrySink::new(grouper, entry_filter, keep_recent);
         let parse_start = std::time::Instant::now();
 
         eprintln!("📖 Parsing logs...");
 
-        // Read logs from all files
-        for file_path in files {
-            let (entries, file_stats) = if file_path == "-" {
-                Self::read_logs_from_stdin(&log_format, no_multiline)?
+        // Stream logs from all files into the grouper, rotated files oldest first
+        let mut inputs = files.clone();
+        input::sort_rotated(&mut inputs);
+        for file_path in &inputs {
+            if file_path == "-" {
+                Self::read_logs_from_stdin(&settings, &mut sink)?;
             } else {
-                let path = std::path::Path::new(&file_path);
+                let path = Path::new(file_path);
                 if path.is_dir() {
-                    Self::read_logs_from_directory(&file_path, &log_format, no_multiline)?
+                    Self::read_logs_from_directory(file_path, &filter, &settings, &mut sink)?;
                 } else {
-                    Self::read_logs_from_file(&file_path, &log_format, no_multiline)?
+                    Self::read_logs_from_file(file_path, &filter, &settings, &mut sink)?;
                 }
-            };
-            total_lines += file_stats.0;
-            parse_errors += file_stats.1;
-            all_entries.extend(entries);
+            }
         }
 
         let parse_duration = parse_start.elapsed();
-
-        if all_entries.is_empty() {
+        let EntrySink {
+            groups,
+            recent,
+            total_lines,
+            parsed_entries,
+            parse_errors,
+            multiline_entries,
+            filtered_out,
+            mut parsers,
+            ..
+        } = sink;
+        let mut seen = std::collections::HashSet::new();
+        parsers.retain(|parser| seen.insert(parser.clone()));
+
+        // Provider and model are recorded once analysis has resolved them
+        let mut history_run = (!no_history).then(|| crate::storage::NewRun {
+            files: files.clone(),
+            parser: parsers.join(","),
+            stats: crate::parser::ParsingStatistics {
+                total_lines,
+                parsed_entries,
+                parse_errors,
+                multiline_entries,
+                duration_ms: parse_duration.as_millis() as u64,
+            },
+            ai_provider: None,
+            model: None,
+        });
+
+        if parsed_entries == 0 {
             eprintln!("✅ Parsed {} lines, found 0 log entries\n", total_lines);
+            Self::save_history(history_run.as_ref(), history_db.as_deref(), &[]);
             println!("No log entries found.");
             return Ok(());
         }
 
         // Display parsing summary
         if stats {
-            Self::display_parsing_stats(
-                total_lines,
-                all_entries.len(),
-                parse_errors,
-                parse_duration,
-            );
+            Self::display_parsing_stats(total_lines, parsed_entries, parse_errors, parse_duration);
         } else {
-            eprintln!("✅ Parsed {} log entries\n", all_entries.len());
+            eprintln!("✅ Parsed {} log entries\n", parsed_entries);
+        }
+        if filtered_out > 0 {
+            eprintln!(
+                "🔎 {} of {} entries match the filters\n",
+                parsed_entries - filtered_out,
+                parsed_entries
+            );
         }
 
-        // Analyze logs
-        let analyzer = Analyzer::new();
-        let mut groups = analyzer.analyze(all_entries)?;
+        let mut groups = ErrorGrouper::into_sorted(groups);
 
         if groups.is_empty() {
+            Self::save_history(history_run.as_ref(), history_db.as_deref(), &groups);
             println!("No errors or warnings found in logs.");
             return Ok(());
         }
 
         // Initialize MCP client if enabled
-        let _mcp_client = if !no_mcp && ai_provider != "none" {
-            Self::initialize_mcp_client(mcp_config.as_deref())
-                .await
-                .ok()
-        } else {
-            None
+        let mcp_client = match mcp_config {
+            Some(config) => {
+                let local_tools = ToolRegistry::builtin(&config.builtin_tools, recent.into());
+                Self::initialize_mcp_client(config, local_tools).await.ok()
+            }
+            None => None,
         };
 
         // AI analysis if enabled
         if ai_provider != "none" {
-            Self::run_ai_analysis(
+            let (provider, model) = Self::run_ai_analysis(
                 &mut groups,
+                &config,
                 &ai_provider,
                 model,
                 api_key,
                 ollama_host,
                 region,
                 concurrency,
+                no_cache,
                 stats,
+                mcp_client,
             )
             .await?;
+            if let Some(run) = history_run.as_mut() {
+                run.ai_provider = Some(provider);
+                run.model = Some(model);
+            }
         }
 
+        Self::save_history(history_run.as_ref(), history_db.as_deref(), &groups);
+
         // Priority: CLI flag > config > fallback to html
-        let config = crate::ai::AIConfig::load().unwrap_or_default();
         let output_format = if format != "html" {
             // CLI flag was explicitly set to something other than default
             format
         } else {
             // Use config or fallback to html
-            config.output.format.unwrap_or_else(|| "html".to_string())
+            config
+                .output
+                .format
+                .clone()
+                .unwrap_or_else(|| "html".to_string())
         };
 
         // Format and display output
-        Self::display_output(&groups, &output_format, limit)?;
+        Self::display_output(&groups, &output_format, limit, output.as_deref(), &config)?;
 
         Ok(())
     }
 
+    /// Persist the run to history; failures are reported but never fatal
+    fn save_history(
+        run: Option<&crate::storage::NewRun>,
+        path: Option<&str>,
+        groups: &[crate::types::ErrorGroup],
+    ) {
+        let Some(run) = run else {
+            return;
+        };
+
+        let history = match path {
+            Some(path) => crate::storage::RunHistory::open(path),
+            None => crate::storage::RunHistory::new(),
+        };
+        match history.and_then(|mut h| h.save_run(run, groups)) {
+            Ok(id) => eprintln!("💾 Saved as run #{} (logai history show {})\n", id, id),
+            Err(e) => eprintln!("⚠️  Warning: Failed to save run history: {}", e),
+        }
+    }
+
     fn display_parsing_stats(
         total_lines: usize,
         parsed_entries: usize,
@@ -168,113 +375,176 @@ impl InvestigateCommand {
         eprintln!();
     }
 
+    /// Analyze `groups`, returning the name and model of the provider used
     #[allow(clippy::too_many_arguments)]
     async fn run_ai_analysis(
         groups: &mut [crate::types::ErrorGroup],
+        ai_config: &ai::AIConfig,
         ai_provider: &str,
         model: Option<String>,
         api_key: Option<String>,
         ollama_host: Option<String>,
         region: Option<String>,
         concurrency: Option<usize>,
+        no_cache: bool,
         stats: bool,
-    ) -> Result<()> {
-        let provider_name = ai_provider.to_string();
+        mcp_client: Option<crate::mcp::MCPClient>,
+    ) -> Result<(String, String)> {
         eprintln!("🤖 Analyzing error groups...");
 
-        // Load configuration from file and merge with CLI flags
-        let ai_config = ai::AIConfig::load().unwrap_or_default();
+        let provider = ai::create_provider_with_config(
+            ai_config,
+            ai_provider,
+            api_key,
+            model,
+            ollama_host,
+            region,
+        )?;
+        let provider_name = provider.name().to_string();
+        let model_name = provider.model().to_string();
 
-        // Get the actual model being used (from CLI or config)
-        let model_display = if let Some(ref m) = model {
-            m.clone()
-        } else {
-            // Try to get model from config for the provider
-            ai_config
-                .providers
-                .get(ai_provider)
-                .and_then(|p| p.model.clone())
-                .unwrap_or_else(|| "default".to_string())
-        };
+        let config = Self::analysis_config(ai_config, concurrency, no_cache);
 
-        let provider = ai::create_provider(ai_provider, api_key, model, ollama_host, region)?;
+        let actual_concurrency = config.max_concurrency;
+        let cache_enabled = config.enable_cache;
+        let mut parallel_analyzer = ai::ParallelAnalyzer::new(provider, config);
 
-        let mut config = ai_config.get_analysis_config();
-        // Priority: CLI param > Config value > Default (already in config)
-        if let Some(cli_concurrency) = concurrency {
-            config.max_concurrency = cli_concurrency;
+        if let Some(client) = mcp_client.filter(|c| !c.available_tools().is_empty()) {
+            parallel_analyzer = parallel_analyzer.with_mcp_client(std::sync::Arc::new(client));
         }
-        // else: config.max_concurrency already has the value from config file or default
 
-        let actual_concurrency = config.max_concurrency;
-        let parallel_analyzer = ai::ParallelAnalyzer::new(provider, config);
+        if cache_enabled {
+            match ai::AnalysisCache::new() {
+                Ok(cache) => {
+                    parallel_analyzer = parallel_analyzer.with_cache(cache);
+                }
+                Err(e) => eprintln!("⚠️  Warning: Response cache unavailable: {}", e),
+            }
+        }
 
         // Create progress callback
-        let analysis_start = std::time::Instant::now();
         let progress_callback = move |update: ai::ProgressUpdate| {
             eprint!("\r\x1b[K{}", update.format_terminal());
             std::io::Write::flush(&mut std::io::stderr()).unwrap();
         };
 
         // Run parallel analysis
-        parallel_analyzer
+        let analysis_stats = parallel_analyzer
             .analyze_groups(groups, progress_callback)
             .await?;
 
         // Clear progress line and show completion
         eprint!("\r\x1b[K");
-        let analysis_duration = analysis_start.elapsed();
-        let throughput = groups.len() as f64 / analysis_duration.as_secs_f64();
+        eprintln!("✅ Analyzed {} error groups", groups.len());
 
         if stats {
-            eprintln!("✅ Analyzed {} error groups\n", groups.len());
-            eprintln!("📊 Analysis Statistics:");
-            eprintln!("  AI Provider: {}", provider_name);
-            eprintln!("  Model: {}", model_display);
-            eprintln!("  Error groups: {}", groups.len());
-            eprintln!("  Duration: {:.2}s", analysis_duration.as_secs_f64());
-            eprintln!("  Throughput: {:.2} groups/sec", throughput);
-            eprintln!("  Concurrency: {}", actual_concurrency);
-            eprintln!();
+            eprintln!(
+                "\n🤖 AI Provider: {} (model: {}, concurrency: {})",
+                provider_name, model_name, actual_concurrency
+            );
+            eprintln!("{}", analysis_stats.format_summary());
         } else {
-            eprintln!("✅ Analyzed {} error groups\n", groups.len());
+            if analysis_stats.cache_hits > 0 || analysis_stats.cache_misses > 0 {
+                eprintln!(
+                    "💾 Cache: {} hits, {} misses",
+                    analysis_stats.cache_hits, analysis_stats.cache_misses
+                );
+            }
+            if analysis_stats.failed > 0 {
+                eprintln!(
+                    "⚠️  Warning: AI analysis failed for {} of {} error groups (use --stats for details)",
+                    analysis_stats.failed,
+                    groups.len()
+                );
+            }
+            eprintln!();
         }
 
-        Ok(())
+        Ok((provider_name, model_name))
+    }
+
+    /// Build the filter for `--since`, `--until` and `--where`
+    pub fn entry_filter(
+        since: Option<&str>,
+        until: Option<&str>,
+        predicates: &[String],
+    ) -> Result<EntryFilter> {
+        let now = chrono::Utc::now();
+        let mut filter = EntryFilter::new();
+        if let Some(since) = since {
+            filter = filter.since(filter::parse_time(since, now)?);
+        }
+        if let Some(until) = until {
+            filter = filter.until(filter::parse_time(until, now)?);
+        }
+        for predicate in predicates {
+            filter = filter.with_predicate(predicate.parse()?);
+        }
+        Ok(filter)
+    }
+
+    /// Analysis settings from the config file, overridden by CLI flags
+    pub fn analysis_config(
+        ai_config: &ai::AIConfig,
+        concurrency: Option<usize>,
+        no_cache: bool,
+    ) -> ai::AnalysisConfig {
+        let mut config = ai_config.get_analysis_config();
+        // Priority: CLI param > Config value > Default (already in config)
+        if let Some(cli_concurrency) = concurrency {
+            config.max_concurrency = cli_concurrency;
+        }
+        if no_cache {
+            config.enable_cache = false;
+        }
+        config
     }
 
-    fn display_output(
+    /// Print `groups` in `format`, or write them to `output` when given.
+    ///
+    /// HTML reports go to the reports directory in `config` unless `output`
+    /// is set. Terminal output written to a file has its colors removed.
+    pub(crate) fn display_output(
         groups: &[crate::types::ErrorGroup],
         format: &str,
         limit: usize,
+        output: Option<&str>,
+        config: &ai::AIConfig,
     ) -> Result<()> {
         match format {
             "terminal" => {
                 let formatter = TerminalFormatter::new(limit);
-                let output = formatter.format(groups)?;
-                print!("{}", output);
+                let text = formatter.format(groups)?;
+                match output {
+                    Some(path) => Self::write_output(path, &Self::strip_colors(&text))?,
+                    None => print!("{}", text),
+                }
             }
             "json" => {
                 let json = serde_json::to_string_pretty(groups)?;
-                println!("{}", json);
+                match output {
+                    Some(path) => Self::write_output(path, &format!("{}\n", json))?,
+                    None => println!("{}", json),
+                }
             }
             "html" => {
                 let formatter = HtmlFormatter::new(limit);
-                let output = formatter.format(groups)?;
-
-                // Generate filename based on timestamp
-                let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
-                let filename = format!("logai-report-{}.html", timestamp);
-
-                // Get report path from config or use default
-                let config = crate::ai::AIConfig::load().unwrap_or_default();
-                let report_dir = config.output.path.unwrap_or_else(|| "reports".to_string());
-
-                // Create directory if it doesn't exist
-                std::fs::create_dir_all(&report_dir)?;
-
-                let filepath = std::path::Path::new(&report_dir).join(&filename);
-                std::fs::write(&filepath, output)?;
+                let report = formatter.format(groups)?;
+
+                let filepath = match output {
+                    Some(path) => std::path::PathBuf::from(path),
+                    None => {
+                        // Generate filename based on timestamp
+                        let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+                        let filename = format!("logai-report-{}.html", timestamp);
+
+                        // Get report path from config or use default
+                        let report_dir = config.output.path.as_deref().unwrap_or("reports");
+                        Path::new(&report_dir).join(&filename)
+                    }
+                };
+                Self::create_parent_dir(&filepath)?;
+                std::fs::write(&filepath, report)?;
 
                 let canonical_path = filepath.canonicalize()?;
                 let file_url = format!("file://{}", canonical_path.display());
@@ -293,96 +563,182 @@ impl InvestigateCommand {
         Ok(())
     }
 
+    fn write_output(path: &str, content: &str) -> Result<()> {
+        Self::create_parent_dir(Path::new(path))?;
+        std::fs::write(path, content)
+            .map_err(|e| anyhow::anyhow!("Failed to write output to '{}': {}", path, e))?;
+        eprintln!("📄 Output saved to {}", path);
+        Ok(())
+    }
+
+    fn create_parent_dir(path: &Path) -> Result<()> {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => Ok(std::fs::create_dir_all(dir)?),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove ANSI color codes
+    fn strip_colors(text: &str) -> String {
+        static ANSI: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        ANSI.get_or_init(|| regex::Regex::new(r"\x1b\[[0-9;]*m").unwrap())
+            .replace_all(text, "")
+            .into_owned()
+    }
+
+    /// Read a log file, decompressing it, or the log files in an archive
     fn read_logs_from_file(
         path: &str,
-        log_format: &str,
-        no_multiline: bool,
-    ) -> Result<(Vec<LogEntry>, (usize, usize))> {
-        let file = File::open(path)
+        filter: &input::InputFilter,
+        settings: &ParseSettings,
+        sink: &mut EntrySink,
+    ) -> Result<()> {
+        if input::is_archive(path) {
+            return Self::read_logs_from_archive(path, filter, settings, sink);
+        }
+
+        let file = input::open(Path::new(path))
             .map_err(|e| anyhow::anyhow!("Failed to open file '{}': {}", path, e))?;
+        Self::read_logs_from_reader(file, path, settings, sink)
+    }
 
-        let reader = BufReader::new(file);
-        let lines: Vec<String> = reader
-            .lines()
-            .collect::<std::io::Result<_>>()
-            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", path, e))?;
+    fn read_logs_from_archive(
+        path: &str,
+        filter: &input::InputFilter,
+        settings: &ParseSettings,
+        sink: &mut EntrySink,
+    ) -> Result<()> {
+        let file_count = input::read_archive(Path::new(path), filter, |name, reader| {
+            let source = format!("{}!/{}", path, name);
+            Self::read_logs_from_reader(reader, &source, settings, sink)
+        })
+        .map_err(|e| anyhow::anyhow!("Failed to read archive '{}': {}", path, e))?;
 
-        if lines.is_empty() {
-            eprintln!("⚠️  Warning: File '{}' is empty", path);
-            return Ok((Vec::new(), (0, 0)));
+        if file_count > 0 {
+            eprintln!("📦 Processed {} log file(s) from '{}'", file_count, path);
+        } else {
+            eprintln!("⚠️  Warning: No log files found in '{}'", path);
         }
 
-        let total_lines = lines.len();
-        let parser = Self::create_parser(log_format, no_multiline, &lines[0]);
-        let entries = Self::parse_with_parser(&parser, &lines, no_multiline)?;
-        let parse_errors = total_lines.saturating_sub(entries.len());
+        Ok(())
+    }
 
-        if parse_errors > 0 {
-            eprintln!(
+    /// Parse the (already decompressed) content of the input named `path`
+    fn read_logs_from_reader<R: Read>(
+        reader: R,
+        path: &str,
+        settings: &ParseSettings,
+        sink: &mut EntrySink,
+    ) -> Result<()> {
+        let lines: Box<dyn Iterator<Item = std::io::Result<String>>> =
+            if settings.config.utf8_recovery {
+                Box::new(LossyLineReader::new(reader))
+            } else {
+                Box::new(BufReader::new(reader).lines())
+            };
+        let parse_errors = Self::read_lines(lines, Some(path), settings, sink)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", path, e))?;
+
+        match parse_errors {
+            None => eprintln!("⚠️  Warning: File '{}' is empty", path),
+            Some(0) => {}
+            Some(parse_errors) => eprintln!(
                 "⚠️  Warning: Failed to parse {} lines in '{}'",
                 parse_errors, path
-            );
+            ),
         }
 
-        Ok((entries, (total_lines, parse_errors)))
+        Ok(())
     }
 
-    fn read_logs_from_stdin(
-        log_format: &str,
-        no_multiline: bool,
-    ) -> Result<(Vec<LogEntry>, (usize, usize))> {
-        let stdin = std::io::stdin();
-        let lines: Vec<String> = stdin.lock().lines().collect::<std::io::Result<_>>()?;
+    fn read_logs_from_stdin(settings: &ParseSettings, sink: &mut EntrySink) -> Result<()> {
+        let stdin = input::decompress(std::io::stdin().lock())?;
+        if settings.config.utf8_recovery {
+            Self::read_lines(LossyLineReader::new(stdin), None, settings, sink)?;
+        } else {
+            Self::read_lines(BufReader::new(stdin).lines(), None, settings, sink)?;
+        }
+        Ok(())
+    }
 
-        if lines.is_empty() {
-            return Ok((Vec::new(), (0, 0)));
+    /// Stream `lines` through a parser into `sink`.
+    ///
+    /// The parser is chosen from the first non-empty line. Chunks are cut only where an
+    /// entry starts, so parallel parsing gives the same entries as sequential
+    /// parsing. `source` is the path the lines were read from, if any.
+    /// Returns the number of lines that could not be parsed, or `None` when
+    /// there were no lines.
+    fn read_lines<I>(
+        mut lines: I,
+        source: Option<&str>,
+        settings: &ParseSettings,
+        sink: &mut EntrySink,
+    ) -> Result<Option<usize>>
+    where
+        I: Iterator<Item = std::io::Result<String>>,
+    {
+        // Leading blank lines say nothing about the format
+        let mut head = Vec::new();
+        while let Some(line) = lines.next().transpose()? {
+            let blank = line.trim().is_empty();
+            head.push(line);
+            if !blank {
+                break;
+            }
         }
+        let Some(sample) = head.last() else {
+            return Ok(None);
+        };
 
-        let total_lines = lines.len();
-        let parser = Self::create_parser(log_format, no_multiline, &lines[0]);
-        let entries = Self::parse_with_parser(&parser, &lines, no_multiline)?;
-        let parse_errors = total_lines.saturating_sub(entries.len());
+        let config = settings.config;
+        let parser = Self::create_parser(settings, sample, source);
+        let lines = head.into_iter().map(Ok).chain(lines);
+        let mut stream = if config.parallel_parsing {
+            ParallelParser::with_threshold(
+                Arc::clone(&parser),
+                config.chunk_size,
+                config.num_threads,
+                config.streaming_threshold_bytes(),
+            )
+            .with_multiline(!settings.no_multiline)
+            .stream(lines)
+        } else {
+            EntryStream::new(Arc::clone(&parser), lines, !settings.no_multiline)
+                .with_chunk_size(config.chunk_size)
+        }
+        .with_max_parse_errors(config.error_budget());
+        for entry in stream.by_ref() {
+            let mut entry = entry?;
+            entry.source = source.map(str::to_string);
+            sink.push(entry);
+        }
+
+        let parse_errors = stream.parse_errors();
+        sink.total_lines += stream.total_lines();
+        sink.parse_errors += parse_errors;
+        sink.parsers.push(parser.name().to_string());
 
-        Ok((entries, (total_lines, parse_errors)))
+        Ok(Some(parse_errors))
     }
 
     fn read_logs_from_directory(
         dir_path: &str,
-        log_format: &str,
-        no_multiline: bool,
-    ) -> Result<(Vec<LogEntry>, (usize, usize))> {
-        use std::fs;
-
-        let mut all_entries = Vec::new();
+        filter: &input::InputFilter,
+        settings: &ParseSettings,
+        sink: &mut EntrySink,
+    ) -> Result<()> {
         let mut file_count = 0;
-        let mut total_lines = 0;
-        let mut total_errors = 0;
-
-        let entries = fs::read_dir(dir_path)
-            .map_err(|e| anyhow::anyhow!("Failed to read directory '{}': {}", dir_path, e))?;
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+        // Log files, including rotated and compressed ones, and archives,
+        // unless include patterns choose otherwise
+        let paths = input::walk(Path::new(dir_path), filter)?;
 
-            if !path.is_file() {
-                continue;
-            }
-
-            if let Some(ext) = path.extension() {
-                if ext == "log" {
-                    if let Some(path_str) = path.to_str() {
-                        match Self::read_logs_from_file(path_str, log_format, no_multiline) {
-                            Ok((entries, (lines, errors))) => {
-                                all_entries.extend(entries);
-                                total_lines += lines;
-                                total_errors += errors;
-                                file_count += 1;
-                            }
-                            Err(e) => {
-                                eprintln!("⚠️  Warning: Failed to read '{}': {}", path_str, e);
-                            }
-                        }
+        for path in &paths {
+            if let Some(path_str) = path.to_str() {
+                match Self::read_logs_from_file(path_str, filter, settings, sink) {
+                    Ok(()) => file_count += 1,
+                    Err(e) => {
+                        eprintln!("⚠️  Warning: Failed to read '{}': {}", path_str, e);
                     }
                 }
             }
@@ -394,24 +750,58 @@ impl InvestigateCommand {
                 file_count, dir_path
             );
         } else {
-            eprintln!("⚠️  Warning: No .log files found in '{}'", dir_path);
+            eprintln!("⚠️  Warning: No log files found in '{}'", dir_path);
         }
 
-        Ok((all_entries, (total_lines, total_errors)))
+        Ok(())
+    }
+
+    /// Create the parser for `log_format`, wrapped to apply `config`.
+    ///
+    /// With `auto_detect` off, `auto` means the configured `default_format`.
+    /// `source` is the path of the input, used for container log metadata.
+    pub(crate) fn create_parser(
+        settings: &ParseSettings,
+        first_line: &str,
+        source: Option<&str>,
+    ) -> Arc<dyn crate::parser::LogParser> {
+        let config = settings.config;
+        let log_format = match &config.default_format {
+            Some(default) if settings.log_format == "auto" && !config.auto_detect => {
+                default.as_str()
+            }
+            _ => settings.log_format,
+        };
+        let no_multiline = settings.no_multiline || !config.enable_multiline;
+        let parser =
+            Self::create_format_parser(log_format, no_multiline, first_line, config, source);
+        Arc::new(ConfiguredParser::new(parser, config))
     }
 
-    fn create_parser(
+    fn create_format_parser(
         log_format: &str,
         no_multiline: bool,
         first_line: &str,
+        config: &ParserConfig,
+        source: Option<&str>,
     ) -> Arc<dyn crate::parser::LogParser> {
         use crate::parser::formats::{
-            ApacheParser, JsonParser, NginxParser, PlainTextParser, SyslogParser,
+            ApacheParser, ContainerParser, ContainerRuntime, CustomParser, JournaldParser,
+            JsonParser, LogfmtParser, NginxParser, PlainTextParser, SyslogParser,
         };
         use crate::parser::StackTraceParser;
 
+        if let Some(definition) = config.format(log_format) {
+            // Definitions were checked by `ParserConfig::validate`
+            match CustomParser::new(definition) {
+                Ok(parser) if no_multiline => return Arc::new(parser),
+                Ok(parser) => return Arc::new(StackTraceParser::new(Arc::new(parser))),
+                Err(e) => eprintln!("⚠️  Warning: {}", e),
+            }
+        }
+
         match log_format {
-            "auto" => FormatDetector::detect(first_line),
+            "auto" => FormatDetector::detect_with_formats(first_line, &config.formats, source),
             "json" => {
                 if no_multiline {
                     Arc::new(JsonParser::new())
@@ -419,9 +809,37 @@ impl InvestigateCommand {
                     Arc::new(StackTraceParser::new(Arc::new(JsonParser::new())))
                 }
             }
+            preset if JsonParser::with_preset(preset).is_some() => {
+                let parser = JsonParser::with_preset(preset).unwrap();
+                if no_multiline {
+                    Arc::new(parser)
+                } else {
+                    Arc::new(StackTraceParser::new(Arc::new(parser)))
+                }
+            }
+            "logfmt" => {
+                if no_multiline {
+                    Arc::new(LogfmtParser::new())
+                } else {
+                    Arc::new(StackTraceParser::new(Arc::new(LogfmtParser::new())))
+                }
+            }
+            "cri" | "docker" => {
+                let runtime = if log_format == "cri" {
+                    ContainerRuntime::Cri
+                } else {
+                    ContainerRuntime::Docker
+                };
+                let parser = ContainerParser::new(runtime);
+                match source {
+                    Some(path) => Arc::new(parser.with_source_path(path)),
+                    None => Arc::new(parser),
+                }
+            }
             "apache" => Arc::new(ApacheParser::new()),
             "nginx" => Arc::new(NginxParser::new()),
             "syslog" => Arc::new(SyslogParser::new()),
+            "journald" => Arc::new(JournaldParser::new()),
             "plain" => {
                 if no_multiline {
                     Arc::new(PlainTextParser::new())
@@ -431,12 +849,12 @@ impl InvestigateCommand {
             }
             _ => {
                 eprintln!("⚠️  Unknown format '{}', using auto-detection", log_format);
-                FormatDetector::detect(first_line)
+                FormatDetector::detect_with_formats(first_line, &config.formats, source)
             }
         }
     }
 
-    fn parse_with_parser(
+    pub(crate) fn parse_with_parser(
         parser: &Arc<dyn crate::parser::LogParser>,
         lines: &[String],
         no_multiline: bool,
@@ -454,8 +872,20 @@ impl InvestigateCommand {
         }
     }
 
-    async fn initialize_mcp_client(config_path: Option<&str>) -> Result<crate::mcp::MCPClient> {
-        use crate::mcp::{MCPClient, MCPConfig};
+    /// Parser settings from `path`, or from the `[parser]` section of the
+    /// main config file already loaded into `ai_config`
+    fn load_parser_config(path: Option<&str>, ai_config: &ai::AIConfig) -> Result<ParserConfig> {
+        let config = match path {
+            Some(path) => ParserConfig::from_file(std::path::Path::new(path))
+                .map_err(|e| anyhow::anyhow!("Failed to load parser config '{}': {}", path, e))?,
+            None => ai_config.parser_config(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn load_mcp_config(config_path: Option<&str>) -> Result<crate::mcp::MCPConfig> {
+        use crate::mcp::MCPConfig;
 
         let config = if let Some(path) = config_path {
             let content = std::fs::read_to_string(path)?;
@@ -475,13 +905,25 @@ impl InvestigateCommand {
             }
         };
 
-        let mut client = MCPClient::new(config)?;
+        Ok(config)
+    }
+
+    async fn initialize_mcp_client(
+        config: crate::mcp::MCPConfig,
+        local_tools: ToolRegistry,
+    ) -> Result<crate::mcp::MCPClient> {
+        let mut client = crate::mcp::MCPClient::new(config)?;
         client.connect().await?;
 
         if client.is_connected() {
             client.discover_tools().await?;
+        }
+        client.register_local_tools(local_tools);
+
+        if !client.available_tools().is_empty() {
             println!(
-                "🔧 MCP tools enabled ({} servers connected)\n",
+                "🔧 MCP tools enabled ({} tools, {} servers connected)\n",
+                client.available_tools().len(),
                 client.connected_servers().len()
             );
         }
@@ -0,0 +1,67 @@
+//! MCP command implementation.
+//!
+//! This module contains the business logic for the `mcp` command,
+//! which runs logai as an MCP server over stdio or HTTP.
+
+use crate::{ai, cli::McpAction, mcp::MCPServer, Result};
+use std::sync::Arc;
+
+/// MCP command implementation
+pub struct McpCommand;
+
+impl McpCommand {
+    /// Execute the mcp command
+    pub async fn execute(action: McpAction) -> Result<()> {
+        match action {
+            McpAction::Serve {
+                http,
+                ai: ai_provider,
+                model,
+                api_key,
+                ollama_host,
+                region,
+            } => {
+                let config = ai::AIConfig::load().unwrap_or_default();
+
+                // Fall back to the configured provider, as investigate does
+                let ai_provider = if ai_provider == "none" {
+                    config
+                        .ai
+                        .provider
+                        .clone()
+                        .unwrap_or_else(|| "none".to_string())
+                } else {
+                    ai_provider
+                };
+
+                let mut server = MCPServer::new().with_parser_config(config.parser_config());
+                if ai_provider != "none" {
+                    let provider =
+                        ai::create_provider(&ai_provider, api_key, model, ollama_host, region)?;
+                    server = server
+                        .with_provider(provider)
+                        .with_analysis_config(config.get_analysis_config());
+                }
+                let server = Arc::new(server);
+
+                // stdout carries the protocol in stdio mode, so status goes to stderr
+                match http {
+                    Some(addr) => {
+                        let listener = tokio::net::TcpListener::bind(&addr)
+                            .await
+                            .map_err(|e| anyhow::anyhow!("Failed to bind '{}': {}", addr, e))?;
+                        eprintln!(
+                            "🔌 MCP server listening on http://{}/mcp",
+                            listener.local_addr()?
+                        );
+                        server.serve_http(listener).await
+                    }
+                    None => {
+                        eprintln!("🔌 MCP server running on stdio");
+                        server.serve_stdio().await
+                    }
+                }
+            }
+        }
+    }
+}
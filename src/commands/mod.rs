@@ -3,10 +3,18 @@
 //! This module contains the business logic for each CLI command,
 //! separated from the CLI parsing layer for better testability.
 
+pub mod cache;
 pub mod clean;
 pub mod config;
+pub mod history;
 pub mod investigate;
+pub mod mcp;
+pub mod watch;
 
+pub use cache::CacheCommand;
 pub use clean::CleanCommand;
 pub use config::ConfigCommand;
+pub use history::HistoryCommand;
 pub use investigate::{InvestigateCommand, InvestigateOptions};
+pub use mcp::McpCommand;
+pub use watch::{WatchCommand, WatchOptions};
@@ -0,0 +1,764 @@
+//! Watch command implementation.
+//!
+//! This module contains the business logic for the `watch` command, which
+//! follows a log file (or stdin) like `tail -F`, incrementally groups new
+//! errors and refreshes a live summary when something interesting happens.
+
+use crate::{
+    analyzer::grouper::{ErrorGrouper, GroupUpdate, DEFAULT_MAX_SAMPLES},
+    commands::{investigate::ParseSettings, InvestigateCommand},
+    output::{terminal::TerminalFormatter, OutputFormatter},
+    parser::LogParser,
+    types::ErrorGroup,
+    Result,
+};
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{self, IsTerminal, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Maximum number of lines buffered before they are parsed, even if the
+/// source never goes quiet
+const MAX_PENDING_LINES: usize = 1000;
+
+/// Bytes read from a followed file at a time
+const READ_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Smoothing factor for the per-group rate baseline
+const BASELINE_ALPHA: f64 = 0.3;
+
+/// Minimum occurrences in a window before a rate change counts as a spike
+const MIN_SPIKE_COUNT: usize = 5;
+
+/// Options for the watch command
+pub struct WatchOptions {
+    pub file: String,
+    pub log_format: String,
+    pub no_multiline: bool,
+    pub from_start: bool,
+    pub interval_ms: u64,
+    pub window_secs: u64,
+    pub spike_factor: f64,
+    pub limit: usize,
+}
+
+/// Watch command implementation
+pub struct WatchCommand;
+
+impl WatchCommand {
+    /// Execute the watch command
+    pub async fn execute(opts: WatchOptions) -> Result<()> {
+        let WatchOptions {
+            file,
+            log_format,
+            no_multiline,
+            from_start,
+            interval_ms,
+            window_secs,
+            spike_factor,
+            limit,
+        } = opts;
+
+        let interval = Duration::from_millis(interval_ms.max(10));
+        let window = Duration::from_secs(window_secs.max(1));
+
+        let mut source = if file == "-" {
+            LineSource::stdin()
+        } else {
+            let path = Path::new(&file);
+            if !path.exists() {
+                eprintln!(
+                    "⚠️  Warning: '{}' does not exist yet, waiting for it to appear",
+                    file
+                );
+            }
+            LineSource::File(FileFollower::open(path, from_start)?)
+        };
+
+        eprintln!("👀 Watching {} (press Ctrl+C to stop)...\n", file);
+
+        let config = crate::ai::AIConfig::load().unwrap_or_default();
+        let parser_config = config.parser_config();
+        let settings = ParseSettings {
+            log_format: &log_format,
+            no_multiline,
+            config: &parser_config,
+        };
+        let path = (file != "-").then_some(file.as_str());
+        let grouper = ErrorGrouper::new()
+            .with_max_samples(DEFAULT_MAX_SAMPLES)
+            .with_config(config.grouping);
+        let mut state = WatchState::new(spike_factor);
+        let mut parser: Option<Arc<dyn LogParser>> = None;
+        let mut pending: Vec<String> = Vec::new();
+        let mut window_start = Instant::now();
+
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        loop {
+            let batch = tokio::select! {
+                _ = &mut ctrl_c => break,
+                batch = source.next_batch(interval) => batch?,
+            };
+
+            let mut events = Vec::new();
+            let finished = batch.is_none();
+
+            match batch {
+                Some(lines) if !lines.is_empty() => {
+                    pending.extend(lines);
+                    // Keep buffering while the writer is active so stack traces
+                    // spanning several polls are parsed as a single entry
+                    if pending.len() >= MAX_PENDING_LINES {
+                        events.extend(Self::flush(
+                            &mut pending,
+                            &mut parser,
+                            &settings,
+                            path,
+                            &grouper,
+                            &mut state,
+                        )?);
+                    }
+                }
+                _ => {
+                    events.extend(Self::flush(
+                        &mut pending,
+                        &mut parser,
+                        &settings,
+                        path,
+                        &grouper,
+                        &mut state,
+                    )?);
+                }
+            }
+
+            if window_start.elapsed() >= window {
+                events.extend(state.roll_window(window));
+                window_start = Instant::now();
+            }
+
+            if !events.is_empty() {
+                Self::render(&file, &state, &events, limit)?;
+            }
+
+            if finished {
+                break;
+            }
+        }
+
+        // Parse anything still buffered before exiting
+        Self::flush(
+            &mut pending,
+            &mut parser,
+            &settings,
+            path,
+            &grouper,
+            &mut state,
+        )?;
+
+        eprintln!(
+            "\n👋 Stopped watching {} ({} entries, {} error groups)",
+            file,
+            state.total_entries,
+            state.groups.len()
+        );
+
+        Ok(())
+    }
+
+    /// Parse buffered lines and feed the resulting entries to the grouper
+    fn flush(
+        pending: &mut Vec<String>,
+        parser: &mut Option<Arc<dyn LogParser>>,
+        settings: &ParseSettings,
+        source: Option<&str>,
+        grouper: &ErrorGrouper,
+        state: &mut WatchState,
+    ) -> Result<Vec<WatchEvent>> {
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let lines = std::mem::take(pending);
+
+        // Detect the format from the first non-empty line we see
+        let parser = match parser {
+            Some(parser) => parser,
+            None => {
+                let Some(first_line) = lines.iter().find(|line| !line.trim().is_empty()) else {
+                    return Ok(Vec::new());
+                };
+                parser.insert(InvestigateCommand::create_parser(
+                    settings, first_line, source,
+                ))
+            }
+        };
+
+        let mut entries =
+            InvestigateCommand::parse_with_parser(parser, &lines, settings.no_multiline)?;
+        for entry in &mut entries {
+            entry.source = source.map(str::to_string);
+        }
+        Ok(state.ingest(grouper, entries))
+    }
+
+    fn render(file: &str, state: &WatchState, events: &[WatchEvent], limit: usize) -> Result<()> {
+        let mut groups: Vec<ErrorGroup> = state.groups.values().cloned().collect();
+        ErrorGrouper::sort_groups(&mut groups);
+
+        let mut output = String::new();
+
+        // Redraw in place when attached to a terminal
+        if io::stdout().is_terminal() {
+            output.push_str("\x1b[2J\x1b[H");
+        }
+
+        output.push_str(&format!(
+            "👀 {} — {} entries, {} error groups (updated {})\n",
+            file,
+            state.total_entries,
+            groups.len(),
+            chrono::Local::now().format("%H:%M:%S")
+        ));
+
+        for event in events {
+            output.push_str(&format!("  {}\n", event.describe(&state.groups)));
+        }
+
+        output.push_str(&TerminalFormatter::new(limit).format(&groups)?);
+        print!("{}", output);
+        io::Write::flush(&mut io::stdout())?;
+
+        Ok(())
+    }
+}
+
+/// Something worth refreshing the summary for
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A previously unseen error group appeared
+    NewGroup(String),
+    /// A group's rate in the last window exceeded its baseline
+    RateSpike {
+        id: String,
+        count: usize,
+        baseline: f64,
+        window_secs: u64,
+    },
+}
+
+impl WatchEvent {
+    fn describe(&self, groups: &HashMap<String, ErrorGroup>) -> String {
+        let pattern = |id: &str| {
+            groups
+                .get(id)
+                .map(|g| g.pattern.chars().take(100).collect::<String>())
+                .unwrap_or_default()
+        };
+
+        match self {
+            WatchEvent::NewGroup(id) => format!("🆕 New error group: {}", pattern(id)),
+            WatchEvent::RateSpike {
+                id,
+                count,
+                baseline,
+                window_secs,
+            } => format!(
+                "📈 Rate spike: {} ({} in last {}s, baseline {:.1})",
+                pattern(id),
+                count,
+                window_secs,
+                baseline
+            ),
+        }
+    }
+}
+
+/// Incrementally maintained groups and rate tracking for a watch session
+pub struct WatchState {
+    pub groups: HashMap<String, ErrorGroup>,
+    pub total_entries: usize,
+    spikes: SpikeDetector,
+}
+
+impl WatchState {
+    pub fn new(spike_factor: f64) -> Self {
+        Self {
+            groups: HashMap::new(),
+            total_entries: 0,
+            spikes: SpikeDetector::new(spike_factor),
+        }
+    }
+
+    /// Add newly parsed entries, returning a `NewGroup` event per new group
+    pub fn ingest(
+        &mut self,
+        grouper: &ErrorGrouper,
+        entries: Vec<crate::types::LogEntry>,
+    ) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+
+        for entry in entries {
+            self.total_entries += 1;
+
+            let id = match grouper.add_entry(&mut self.groups, entry) {
+                Some(GroupUpdate::Created(id)) => {
+                    events.push(WatchEvent::NewGroup(id.clone()));
+                    id
+                }
+                Some(GroupUpdate::Updated(id)) => id,
+                None => continue,
+            };
+
+            self.spikes.record(&id);
+        }
+
+        events
+    }
+
+    /// Close the current rate window and report spikes
+    pub fn roll_window(&mut self, window: Duration) -> Vec<WatchEvent> {
+        self.spikes
+            .roll()
+            .into_iter()
+            .map(|(id, count, baseline)| WatchEvent::RateSpike {
+                id,
+                count,
+                baseline,
+                window_secs: window.as_secs(),
+            })
+            .collect()
+    }
+}
+
+/// Detects per-group rate spikes against an exponentially smoothed baseline
+pub struct SpikeDetector {
+    factor: f64,
+    baselines: HashMap<String, f64>,
+    current: HashMap<String, usize>,
+}
+
+impl SpikeDetector {
+    pub fn new(factor: f64) -> Self {
+        Self {
+            factor: factor.max(1.0),
+            baselines: HashMap::new(),
+            current: HashMap::new(),
+        }
+    }
+
+    /// Record one occurrence of a group in the current window
+    pub fn record(&mut self, id: &str) {
+        *self.current.entry(id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Close the current window, returning `(id, count, baseline)` for each spike
+    pub fn roll(&mut self) -> Vec<(String, usize, f64)> {
+        let current = std::mem::take(&mut self.current);
+        let mut spikes = Vec::new();
+
+        for (id, baseline) in self.baselines.iter_mut() {
+            let count = current.get(id).copied().unwrap_or(0);
+            if count >= MIN_SPIKE_COUNT && count as f64 >= self.factor * baseline.max(1.0) {
+                spikes.push((id.clone(), count, *baseline));
+            }
+            *baseline = BASELINE_ALPHA * count as f64 + (1.0 - BASELINE_ALPHA) * *baseline;
+        }
+
+        // Groups seen for the first time start with their first window as baseline
+        for (id, count) in current {
+            self.baselines.entry(id).or_insert(count as f64);
+        }
+
+        spikes.sort_by_key(|spike| std::cmp::Reverse(spike.1));
+        spikes
+    }
+}
+
+/// Where new lines come from
+enum LineSource {
+    File(FileFollower),
+    Stdin(mpsc::UnboundedReceiver<String>),
+}
+
+impl LineSource {
+    fn stdin() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            use std::io::BufRead;
+            for line in io::stdin().lock().lines() {
+                match line {
+                    Ok(line) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to read from stdin: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        LineSource::Stdin(rx)
+    }
+
+    /// Wait for the next batch of lines.
+    ///
+    /// Returns an empty batch when the source was idle for `interval`, and
+    /// `None` once the source is exhausted (stdin closed).
+    async fn next_batch(&mut self, interval: Duration) -> Result<Option<Vec<String>>> {
+        match self {
+            LineSource::File(follower) => {
+                let lines = follower.poll()?;
+                if lines.is_empty() {
+                    tokio::time::sleep(interval).await;
+                }
+                Ok(Some(lines))
+            }
+            LineSource::Stdin(rx) => match tokio::time::timeout(interval, rx.recv()).await {
+                Err(_) => Ok(Some(Vec::new())),
+                Ok(None) => Ok(None),
+                Ok(Some(line)) => {
+                    let mut lines = vec![line];
+                    while lines.len() < MAX_PENDING_LINES {
+                        match rx.try_recv() {
+                            Ok(line) => lines.push(line),
+                            Err(_) => break,
+                        }
+                    }
+                    Ok(Some(lines))
+                }
+            },
+        }
+    }
+}
+
+/// Follows a file by name like `tail -F`.
+///
+/// Truncation (the file shrinking below the read position) restarts reading
+/// from the beginning. Rotation (the path pointing at a different file) drains
+/// the old file and then switches to the new one. Rotation is detected by
+/// device/inode and is therefore only supported on Unix; elsewhere a rotated
+/// file is picked up once it is smaller than the old read position.
+pub struct FileFollower {
+    path: PathBuf,
+    file: Option<File>,
+    identity: Option<(u64, u64)>,
+    position: u64,
+    /// Bytes of the incomplete last line
+    partial: Vec<u8>,
+}
+
+impl FileFollower {
+    /// Start following `path`, either from its current end or from the start
+    pub fn open(path: &Path, from_start: bool) -> Result<Self> {
+        let mut follower = Self {
+            path: path.to_path_buf(),
+            file: None,
+            identity: None,
+            position: 0,
+            partial: Vec::new(),
+        };
+
+        if let Ok(file) = File::open(path) {
+            let metadata = file.metadata()?;
+            follower.identity = file_identity(&metadata);
+            follower.position = if from_start { 0 } else { metadata.len() };
+            follower.file = Some(file);
+        }
+
+        Ok(follower)
+    }
+
+    /// Return the complete lines written since the last poll.
+    ///
+    /// At most about `MAX_PENDING_LINES` lines are read per poll, so a large
+    /// backlog (`--from-start`, a big append) is handed over in batches by
+    /// the following polls instead of being loaded at once.
+    pub fn poll(&mut self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        if self.file.is_none() {
+            // File did not exist yet (or disappeared); read it from the start
+            // once it shows up
+            if !self.reopen()? {
+                return Ok(lines);
+            }
+        }
+
+        match std::fs::metadata(&self.path) {
+            Ok(metadata) => {
+                let rotated = match (file_identity(&metadata), self.identity) {
+                    (Some(new), Some(old)) => new != old,
+                    _ => false,
+                };
+
+                if rotated {
+                    // Drain what is left of the old file before switching over
+                    if !self.read_available(&mut lines)? {
+                        return Ok(lines);
+                    }
+                    if !self.partial.is_empty() {
+                        lines.push(Self::decode(&std::mem::take(&mut self.partial)));
+                    }
+                    log::debug!("{} was rotated, reopening", self.path.display());
+                    self.reopen()?;
+                } else if metadata.len() < self.position {
+                    log::debug!("{} was truncated, reading from start", self.path.display());
+                    self.position = 0;
+                    self.partial.clear();
+                }
+            }
+            Err(_) => {
+                // Path removed or renamed and not yet recreated: keep draining
+                // the handle we still hold
+            }
+        }
+
+        self.read_available(&mut lines)?;
+        Ok(lines)
+    }
+
+    fn reopen(&mut self) -> Result<bool> {
+        match File::open(&self.path) {
+            Ok(file) => {
+                self.identity = file_identity(&file.metadata()?);
+                self.file = Some(file);
+                self.position = 0;
+                self.partial.clear();
+                Ok(true)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.file = None;
+                self.identity = None;
+                Ok(false)
+            }
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to open file '{}': {}",
+                self.path.display(),
+                e
+            )),
+        }
+    }
+
+    /// Read complete lines in blocks until the end of the file or until a
+    /// batch of lines is ready, returning whether the end was reached
+    fn read_available(&mut self, lines: &mut Vec<String>) -> Result<bool> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(true);
+        };
+
+        file.seek(SeekFrom::Start(self.position))?;
+        let mut block = vec![0; READ_BLOCK_SIZE];
+        while lines.len() < MAX_PENDING_LINES {
+            let read = file.read(&mut block)?;
+            if read == 0 {
+                return Ok(true);
+            }
+            self.position += read as u64;
+            self.partial.extend_from_slice(&block[..read]);
+
+            // Only hand out complete lines; keep the trailing fragment (which
+            // may end inside a UTF-8 character) for the next block
+            if let Some(last_newline) = self.partial.iter().rposition(|&b| b == b'\n') {
+                let rest = self.partial.split_off(last_newline + 1);
+                let complete = std::mem::replace(&mut self.partial, rest);
+                lines.extend(
+                    Self::decode(&complete)
+                        .lines()
+                        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string()),
+                );
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn decode(bytes: &[u8]) -> String {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &Path, content: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_follower_starts_at_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "old line\n");
+
+        let mut follower = FileFollower::open(&path, false).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        append(&path, "new line\n");
+        assert_eq!(follower.poll().unwrap(), vec!["new line"]);
+    }
+
+    #[test]
+    fn test_follower_holds_partial_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut follower = FileFollower::open(&path, true).unwrap();
+        append(&path, "first\nsec");
+        assert_eq!(follower.poll().unwrap(), vec!["first"]);
+
+        append(&path, "ond\r\n");
+        assert_eq!(follower.poll().unwrap(), vec!["second"]);
+    }
+
+    #[test]
+    fn test_follower_handles_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "one\ntwo\nthree\n");
+
+        let mut follower = FileFollower::open(&path, true).unwrap();
+        assert_eq!(follower.poll().unwrap().len(), 3);
+
+        std::fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), vec!["fresh"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follower_handles_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut follower = FileFollower::open(&path, false).unwrap();
+        append(&path, "before rotation\n");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "after rotation\n");
+
+        assert_eq!(
+            follower.poll().unwrap(),
+            vec!["before rotation", "after rotation"]
+        );
+
+        append(&path, "later\n");
+        assert_eq!(follower.poll().unwrap(), vec!["later"]);
+    }
+
+    #[test]
+    fn test_follower_waits_for_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+
+        let mut follower = FileFollower::open(&path, false).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        append(&path, "created\n");
+        assert_eq!(follower.poll().unwrap(), vec!["created"]);
+    }
+
+    #[test]
+    fn test_follower_reads_backlog_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        // The first block ends inside the "é"
+        let long = format!("{}é", "a".repeat(READ_BLOCK_SIZE - 1));
+        let mut content = format!("{}\n", long);
+        for i in 0..20_000 {
+            content.push_str(&format!("2024-01-15 10:30:00 INFO request {}\n", i));
+        }
+        append(&path, &content);
+
+        let mut follower = FileFollower::open(&path, true).unwrap();
+        let first = follower.poll().unwrap();
+        assert_eq!(first[0], long);
+        assert!(first.len() < 20_001);
+
+        let mut lines = first;
+        loop {
+            let batch = follower.poll().unwrap();
+            if batch.is_empty() {
+                break;
+            }
+            assert!(batch.len() < MAX_PENDING_LINES + READ_BLOCK_SIZE / 30);
+            lines.extend(batch);
+        }
+        assert_eq!(lines.len(), 20_001);
+        assert_eq!(lines[20_000], "2024-01-15 10:30:00 INFO request 19999");
+    }
+
+    #[test]
+    fn test_spike_detector() {
+        let mut detector = SpikeDetector::new(3.0);
+
+        // First window establishes the baseline
+        for _ in 0..2 {
+            detector.record("err-1");
+        }
+        assert!(detector.roll().is_empty());
+
+        // Steady rate is not a spike
+        for _ in 0..2 {
+            detector.record("err-1");
+        }
+        assert!(detector.roll().is_empty());
+
+        // A jump well above baseline is
+        for _ in 0..10 {
+            detector.record("err-1");
+        }
+        let spikes = detector.roll();
+        assert_eq!(spikes.len(), 1);
+        assert_eq!(spikes[0].0, "err-1");
+        assert_eq!(spikes[0].1, 10);
+    }
+
+    #[test]
+    fn test_watch_state_reports_new_groups() {
+        use crate::parser::{formats::PlainTextParser, LogParser};
+
+        let grouper = ErrorGrouper::new();
+        let parser = PlainTextParser::new();
+        let mut state = WatchState::new(3.0);
+
+        let entries = [
+            "2024-01-15 10:30:00 ERROR Connection refused to 10.0.0.1",
+            "2024-01-15 10:30:01 ERROR Connection refused to 10.0.0.2",
+            "2024-01-15 10:30:02 INFO Request served",
+        ]
+        .iter()
+        .filter_map(|line| parser.parse_line(line).unwrap())
+        .collect();
+
+        let events = state.ingest(&grouper, entries);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], WatchEvent::NewGroup(_)));
+        assert_eq!(state.total_entries, 3);
+        assert_eq!(state.groups.len(), 1);
+    }
+}
@@ -23,6 +23,9 @@ pub mod types {
         pub message: String,
         pub metadata: LogMetadata,
         pub raw: String,
+        /// Path of the file the entry was read from
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub source: Option<String>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +47,43 @@ pub mod types {
         Unknown,
     }
 
+    impl Severity {
+        /// Position from most (0) to least severe, `Unknown` last
+        pub fn rank(&self) -> u8 {
+            match self {
+                Severity::Error => 0,
+                Severity::Warning => 1,
+                Severity::Info => 2,
+                Severity::Debug => 3,
+                Severity::Trace => 4,
+                Severity::Unknown => 5,
+            }
+        }
+
+        /// Whether this severity is at least as severe as `threshold`
+        pub fn at_least(&self, threshold: Severity) -> bool {
+            *self != Severity::Unknown && self.rank() <= threshold.rank()
+        }
+    }
+
+    impl std::str::FromStr for Severity {
+        type Err = crate::Error;
+
+        fn from_str(name: &str) -> crate::Result<Self> {
+            match name.to_lowercase().as_str() {
+                "error" => Ok(Severity::Error),
+                "warn" | "warning" => Ok(Severity::Warning),
+                "info" => Ok(Severity::Info),
+                "debug" => Ok(Severity::Debug),
+                "trace" => Ok(Severity::Trace),
+                _ => Err(anyhow::anyhow!(
+                    "Unknown severity '{}' (expected error, warn, info, debug or trace)",
+                    name
+                )),
+            }
+        }
+    }
+
     #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct ErrorGroup {
         pub id: String,
@@ -54,6 +94,9 @@ pub mod types {
         pub severity: Severity,
         pub entries: Vec<LogEntry>,
         pub analysis: Option<ErrorAnalysis>,
+        /// Files the group's entries were read from
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub sources: Vec<String>,
     }
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
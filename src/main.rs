@@ -1,7 +1,10 @@
 use clap::Parser;
 use logai::{
     cli::{Cli, Commands},
-    commands::{CleanCommand, ConfigCommand, InvestigateCommand, InvestigateOptions},
+    commands::{
+        CacheCommand, CleanCommand, ConfigCommand, HistoryCommand, InvestigateCommand,
+        InvestigateOptions, McpCommand, WatchCommand, WatchOptions,
+    },
     logging, Result,
 };
 
@@ -14,20 +17,30 @@ async fn main() -> Result<()> {
             files,
             log_format,
             no_multiline,
+            parser_config,
+            recursive,
+            include,
+            exclude,
             stats,
             ai: ai_provider,
             model,
             api_key,
             ollama_host,
             region,
-            no_cache: _,
+            no_cache,
             format,
-            output: _,
+            output,
             limit,
-            severity: _,
+            severity,
+            since,
+            until,
+            filters,
+            grouping,
             no_mcp,
             mcp_config,
             concurrency,
+            no_history,
+            history_db,
         } => {
             // Enable file logging for investigate command
             let log_file_path = logging::init_logging(cli.verbose)?;
@@ -60,13 +73,35 @@ async fn main() -> Result<()> {
                 no_mcp,
                 mcp_config,
                 concurrency,
+                no_history,
+                history_db,
+                no_cache,
+                parser_config,
+                recursive,
+                include,
+                exclude,
+                severity,
+                output,
+                since,
+                until,
+                filters,
+                grouping,
             })
             .await?;
 
             // Print log file location at the end
             eprintln!("\n📋 Detailed logs: {}", log_file_path.display());
         }
-        Commands::Watch { file: _ } => {
+        Commands::Watch {
+            file,
+            log_format,
+            no_multiline,
+            from_start,
+            interval,
+            window,
+            spike_factor,
+            limit,
+        } => {
             // Initialize basic console logging for other commands
             env_logger::Builder::from_default_env()
                 .filter_level(if cli.verbose {
@@ -76,7 +111,17 @@ async fn main() -> Result<()> {
                 })
                 .init();
 
-            println!("Watch mode coming soon!");
+            WatchCommand::execute(WatchOptions {
+                file,
+                log_format,
+                no_multiline,
+                from_start,
+                interval_ms: interval,
+                window_secs: window,
+                spike_factor,
+                limit,
+            })
+            .await?;
         }
         Commands::Config { action } => {
             // Initialize basic console logging for other commands
@@ -90,6 +135,42 @@ async fn main() -> Result<()> {
 
             ConfigCommand::execute(action)?;
         }
+        Commands::History { action, history_db } => {
+            // Initialize basic console logging for other commands
+            env_logger::Builder::from_default_env()
+                .filter_level(if cli.verbose {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Info
+                })
+                .init();
+
+            HistoryCommand::execute(action, history_db.as_deref())?;
+        }
+        Commands::Cache { action } => {
+            // Initialize basic console logging for other commands
+            env_logger::Builder::from_default_env()
+                .filter_level(if cli.verbose {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Info
+                })
+                .init();
+
+            CacheCommand::execute(action)?;
+        }
+        Commands::Mcp { action } => {
+            // Console logging goes to stderr, keeping stdout free for the protocol
+            env_logger::Builder::from_default_env()
+                .filter_level(if cli.verbose {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Info
+                })
+                .init();
+
+            McpCommand::execute(action).await?;
+        }
         Commands::Clean { force } => {
             // Initialize basic console logging for other commands
             env_logger::Builder::from_default_env()
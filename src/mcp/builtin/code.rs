@@ -0,0 +1,428 @@
+use super::{required_str, LocalTool, BUILTIN_SERVER};
+use crate::mcp::{MCPError, Result, ToolInfo};
+use crate::parser::MetadataExtractor;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files larger than this are not searched or read
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Directories never descended into
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "vendor", "__pycache__"];
+
+/// A source tree the code tools are confined to
+pub struct SourceTree {
+    root: PathBuf,
+}
+
+impl SourceTree {
+    /// Use the directory at `root`
+    pub fn new(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let root = root.canonicalize().map_err(|e| {
+            MCPError::ConfigError(format!("Invalid source root '{}': {}", root.display(), e))
+        })?;
+        if !root.is_dir() {
+            return Err(MCPError::ConfigError(format!(
+                "Source root '{}' is not a directory",
+                root.display()
+            )));
+        }
+        Ok(Self { root })
+    }
+
+    /// Every searchable file, skipping hidden and build directories
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.starts_with('.') {
+                    continue;
+                }
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    if !SKIPPED_DIRS.contains(&name.as_ref()) {
+                        dirs.push(entry.path());
+                    }
+                } else if file_type.is_file()
+                    && entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES)
+                {
+                    files.push(entry.path());
+                }
+            }
+        }
+
+        files.sort();
+        files
+    }
+
+    /// Resolve `file` to a path inside the tree.
+    ///
+    /// Accepts paths relative to the root, absolute paths inside it, or a
+    /// trailing path such as `Service.java` as found in stack frames (the
+    /// shortest matching path wins).
+    fn resolve(&self, file: &str) -> Result<PathBuf> {
+        let candidate = self.root.join(file);
+        if let Ok(path) = candidate.canonicalize() {
+            if !path.starts_with(&self.root) {
+                return Err(MCPError::InvalidParameters(format!(
+                    "'{}' is outside the source root",
+                    file
+                )));
+            }
+            if path.is_file() {
+                return Ok(path);
+            }
+        }
+
+        let suffix = Path::new(file.trim_start_matches("./"));
+        self.files()
+            .into_iter()
+            .filter(|path| path.ends_with(suffix))
+            .min_by_key(|path| path.components().count())
+            .ok_or_else(|| MCPError::InvocationFailed(format!("No file matching '{}'", file)))
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+/// Searches the source tree for text or for the top frame of a stack trace
+pub struct SearchCodeTool {
+    tree: Arc<SourceTree>,
+    max_results: usize,
+    extractor: Arc<MetadataExtractor>,
+}
+
+impl SearchCodeTool {
+    /// Create the tool for `tree`, returning at most `max_results` matches
+    pub fn new(tree: Arc<SourceTree>, max_results: usize) -> Self {
+        Self {
+            tree,
+            max_results,
+            extractor: Arc::new(MetadataExtractor::new()),
+        }
+    }
+
+    fn search(&self, query: Option<&str>, stack_trace: Option<&str>) -> Result<Value> {
+        let mut matches = Vec::new();
+        let mut frame = Value::Null;
+        let mut terms: Vec<String> = query.map(String::from).into_iter().collect();
+
+        if let Some(stack_trace) = stack_trace {
+            let metadata = self.extractor.extract_from_stack_trace(stack_trace);
+            frame = json!(metadata);
+
+            // The frame's own location is the most useful match
+            if let Some(file) = metadata.get("file") {
+                if let Ok(path) = self.tree.resolve(file) {
+                    let line: usize = metadata
+                        .get("line")
+                        .and_then(|l| l.parse().ok())
+                        .unwrap_or(1);
+                    let text = std::fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|content| {
+                            content
+                                .lines()
+                                .nth(line.saturating_sub(1))
+                                .map(String::from)
+                        })
+                        .unwrap_or_default();
+                    matches.push(json!({
+                        "path": self.tree.relative(&path),
+                        "line": line,
+                        "text": text.trim()
+                    }));
+                }
+            }
+
+            // Then where the failing method is defined or called
+            if terms.is_empty() {
+                if let Some(method) = metadata
+                    .get("function")
+                    .and_then(|f| f.rsplit('.').next())
+                    .filter(|m| !m.is_empty())
+                {
+                    terms.push(method.to_string());
+                }
+            }
+        }
+
+        if terms.is_empty() && stack_trace.is_none() {
+            return Err(MCPError::InvalidParameters(
+                "Provide `query` or `stack_trace`".to_string(),
+            ));
+        }
+
+        let mut truncated = false;
+        'files: for path in self.tree.files() {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue; // binary or unreadable
+            };
+            for (index, text) in content.lines().enumerate() {
+                if !terms.iter().any(|term| text.contains(term.as_str())) {
+                    continue;
+                }
+                if matches.len() >= self.max_results {
+                    truncated = true;
+                    break 'files;
+                }
+                matches.push(json!({
+                    "path": self.tree.relative(&path),
+                    "line": index + 1,
+                    "text": text.trim()
+                }));
+            }
+        }
+
+        Ok(json!({
+            "frame": frame,
+            "matches": matches,
+            "truncated": truncated
+        }))
+    }
+}
+
+#[async_trait]
+impl LocalTool for SearchCodeTool {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            name: "search_code".to_string(),
+            description: "Search the application's source code. Pass `query` for a literal \
+                          text search, or `stack_trace` to locate the code of its top frame."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "Literal text to search for"},
+                    "stack_trace": {"type": "string", "description": "Stack trace from the log"}
+                }
+            }),
+            server: BUILTIN_SERVER.to_string(),
+        }
+    }
+
+    async fn call(&self, arguments: Value) -> Result<Value> {
+        let query = arguments
+            .get("query")
+            .and_then(|q| q.as_str())
+            .filter(|q| !q.is_empty())
+            .map(String::from);
+        let stack_trace = arguments
+            .get("stack_trace")
+            .and_then(|s| s.as_str())
+            .map(String::from);
+
+        let tool = Self {
+            tree: Arc::clone(&self.tree),
+            max_results: self.max_results,
+            extractor: Arc::clone(&self.extractor),
+        };
+        tokio::task::spawn_blocking(move || tool.search(query.as_deref(), stack_trace.as_deref()))
+            .await
+            .map_err(|e| MCPError::InvocationFailed(format!("Search failed: {}", e)))?
+    }
+}
+
+/// Returns the source lines around a `file:line`
+pub struct ReadSourceContextTool {
+    tree: Arc<SourceTree>,
+    context_lines: usize,
+}
+
+impl ReadSourceContextTool {
+    /// Create the tool for `tree`, showing `context_lines` on each side
+    pub fn new(tree: Arc<SourceTree>, context_lines: usize) -> Self {
+        Self {
+            tree,
+            context_lines,
+        }
+    }
+
+    fn read(&self, file: &str, line: Option<usize>, context: usize) -> Result<String> {
+        // Accept `Service.java:123` as well as separate arguments
+        let (file, line) = match file.rsplit_once(':') {
+            Some((name, number)) if number.parse::<usize>().is_ok() => {
+                (name, line.or(number.parse().ok()))
+            }
+            _ => (file, line),
+        };
+
+        let path = self.tree.resolve(file)?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| MCPError::InvocationFailed(format!("Failed to read '{}': {}", file, e)))?;
+        let lines: Vec<&str> = content.lines().collect();
+        if lines.is_empty() {
+            return Ok(format!("{} is empty", self.tree.relative(&path)));
+        }
+
+        let target = line.unwrap_or(1).clamp(1, lines.len());
+        let start = target.saturating_sub(context).max(1);
+        let end = (target + context).min(lines.len());
+        let width = end.to_string().len();
+
+        let mut output = format!(
+            "{} (lines {}-{} of {})\n",
+            self.tree.relative(&path),
+            start,
+            end,
+            lines.len()
+        );
+        for number in start..=end {
+            let marker = if line.is_some() && number == target {
+                '>'
+            } else {
+                ' '
+            };
+            output.push_str(&format!(
+                "{}{:>width$} | {}\n",
+                marker,
+                number,
+                lines[number - 1],
+                width = width
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl LocalTool for ReadSourceContextTool {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            name: "read_source_context".to_string(),
+            description: "Read the source lines around a location such as `Service.java:123` \
+                          or `src/db/pool.rs:45`."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "file": {"type": "string", "description": "File path or `file:line`"},
+                    "line": {"type": "integer", "description": "Line to center on"},
+                    "context": {"type": "integer", "description": "Lines to show on each side"}
+                },
+                "required": ["file"]
+            }),
+            server: BUILTIN_SERVER.to_string(),
+        }
+    }
+
+    async fn call(&self, arguments: Value) -> Result<Value> {
+        let file = required_str(&arguments, "file")?.to_string();
+        let line = arguments
+            .get("line")
+            .and_then(|l| l.as_u64())
+            .map(|l| l as usize);
+        let context = arguments
+            .get("context")
+            .and_then(|c| c.as_u64())
+            .map_or(self.context_lines, |c| c as usize);
+
+        let tool = Self::new(Arc::clone(&self.tree), self.context_lines);
+        tokio::task::spawn_blocking(move || tool.read(&file, line, context))
+            .await
+            .map_err(|e| MCPError::InvocationFailed(format!("Read failed: {}", e)))?
+            .map(Value::String)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A project at `<tmp>/app`, so `<tmp>` itself is outside the tree
+    fn source_tree() -> (TempDir, Arc<SourceTree>) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("app");
+        let src = root.join("src/main/java/com/example");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(
+            src.join("Service.java"),
+            "package com.example;\n\nclass Service {\n    void connect() {\n        throw new IllegalStateException(\"pool exhausted\");\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join("target/Service.java"), "connect()").unwrap();
+
+        let tree = Arc::new(SourceTree::new(&root).unwrap());
+        (dir, tree)
+    }
+
+    #[tokio::test]
+    async fn test_search_code_from_stack_trace() {
+        let (_dir, tree) = source_tree();
+        let tool = SearchCodeTool::new(tree, 10);
+
+        let output = tool
+            .call(json!({
+                "stack_trace": "java.lang.IllegalStateException: pool exhausted\n\tat com.example.Service.connect(Service.java:5)"
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(output["frame"]["file"], "Service.java");
+        let first = &output["matches"][0];
+        assert_eq!(first["path"], "src/main/java/com/example/Service.java");
+        assert_eq!(first["line"], 5);
+        assert!(first["text"].as_str().unwrap().contains("pool exhausted"));
+        // The method name is searched too, but never inside target/
+        assert!(output["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|m| !m["path"].as_str().unwrap().starts_with("target")));
+        assert!(output["matches"].as_array().unwrap().len() >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_code_query_limit() {
+        let (_dir, tree) = source_tree();
+        let tool = SearchCodeTool::new(tree, 1);
+
+        let output = tool.call(json!({"query": "o"})).await.unwrap();
+        assert_eq!(output["matches"].as_array().unwrap().len(), 1);
+        assert_eq!(output["truncated"], true);
+
+        assert!(tool.call(json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_source_context() {
+        let (_dir, tree) = source_tree();
+        let tool = ReadSourceContextTool::new(tree, 1);
+
+        let output = tool.call(json!({"file": "Service.java:5"})).await.unwrap();
+        let text = output.as_str().unwrap();
+        assert!(text.starts_with("src/main/java/com/example/Service.java (lines 4-6 of 7)"));
+        assert!(text.contains(">5 |         throw new IllegalStateException"));
+        assert!(text.contains(" 4 |     void connect() {"));
+    }
+
+    #[tokio::test]
+    async fn test_read_source_context_stays_inside_root() {
+        let (dir, tree) = source_tree();
+        std::fs::write(dir.path().join("outside.txt"), "secret").unwrap();
+        let tool = ReadSourceContextTool::new(tree, 1);
+
+        assert!(tool.call(json!({"file": "../outside.txt"})).await.is_err());
+        assert!(tool.call(json!({"file": "Missing.java"})).await.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use super::{LocalTool, BUILTIN_SERVER};
+use crate::mcp::{MCPError, Result, ToolInfo};
+use crate::types::LogEntry;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Entries returned unless a `limit` is given
+const DEFAULT_LIMIT: usize = 50;
+
+/// Upper bound on `limit`
+const MAX_LIMIT: usize = 200;
+
+/// Metadata keys that may hold a request or trace id
+const REQUEST_ID_KEYS: &[&str] = &[
+    "request_id",
+    "requestId",
+    "req_id",
+    "trace_id",
+    "traceId",
+    "correlation_id",
+];
+
+/// Searches the log entries kept for the current run
+pub struct QueryLogsTool {
+    entries: Arc<Vec<LogEntry>>,
+}
+
+impl QueryLogsTool {
+    /// Create the tool over `entries`
+    pub fn new(entries: Vec<LogEntry>) -> Self {
+        Self {
+            entries: Arc::new(entries),
+        }
+    }
+
+    fn timestamp(arguments: &Value, name: &str) -> Result<Option<DateTime<Utc>>> {
+        arguments
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|ts| ts.with_timezone(&Utc))
+                    .map_err(|e| {
+                        MCPError::InvalidParameters(format!(
+                            "`{}` must be an RFC 3339 timestamp: {}",
+                            name, e
+                        ))
+                    })
+            })
+            .transpose()
+    }
+
+    fn matches_request_id(entry: &LogEntry, request_id: &str) -> bool {
+        REQUEST_ID_KEYS
+            .iter()
+            .any(|key| entry.metadata.extra.get(*key).map(String::as_str) == Some(request_id))
+            || entry.raw.contains(request_id)
+    }
+}
+
+#[async_trait]
+impl LocalTool for QueryLogsTool {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            name: "query_logs".to_string(),
+            description: "Search the most recent log entries being analyzed, e.g. everything \
+                          logged for a request id or in the minutes around an error."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "since": {"type": "string", "description": "Only entries at or after this RFC 3339 time"},
+                    "until": {"type": "string", "description": "Only entries at or before this RFC 3339 time"},
+                    "request_id": {"type": "string", "description": "Request, trace or correlation id"},
+                    "contains": {"type": "string", "description": "Text the message must contain"},
+                    "severity": {"type": "string", "description": "error, warning, info, debug or trace"},
+                    "limit": {"type": "integer", "description": "Maximum entries to return", "default": DEFAULT_LIMIT}
+                }
+            }),
+            server: BUILTIN_SERVER.to_string(),
+        }
+    }
+
+    async fn call(&self, arguments: Value) -> Result<Value> {
+        let since = Self::timestamp(&arguments, "since")?;
+        let until = Self::timestamp(&arguments, "until")?;
+        let text = |name: &str| arguments.get(name).and_then(|v| v.as_str());
+        let request_id = text("request_id");
+        let contains = text("contains");
+        let severity = text("severity").map(str::to_lowercase);
+        let limit = arguments
+            .get("limit")
+            .and_then(|l| l.as_u64())
+            .map_or(DEFAULT_LIMIT, |l| (l as usize).min(MAX_LIMIT));
+
+        let matching: Vec<&LogEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                if since.is_some() || until.is_some() {
+                    let Some(ts) = entry.timestamp else {
+                        return false;
+                    };
+                    if since.is_some_and(|since| ts < since)
+                        || until.is_some_and(|until| ts > until)
+                    {
+                        return false;
+                    }
+                }
+                // `warn` matches `Warning`, and so on
+                severity.as_deref().is_none_or(|severity| {
+                    format!("{:?}", entry.severity)
+                        .to_lowercase()
+                        .starts_with(severity)
+                }) && contains.is_none_or(|text| entry.message.contains(text))
+                    && request_id.is_none_or(|id| Self::matches_request_id(entry, id))
+            })
+            .collect();
+
+        let entries: Vec<Value> = matching
+            .iter()
+            .take(limit)
+            .map(|entry| {
+                json!({
+                    "timestamp": entry.timestamp,
+                    "severity": entry.severity,
+                    "message": entry.message
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "total_matches": matching.len(),
+            "entries": entries
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LogMetadata, Severity};
+    use std::collections::HashMap;
+
+    fn entry(time: &str, severity: Severity, message: &str, request_id: Option<&str>) -> LogEntry {
+        let mut extra = HashMap::new();
+        if let Some(id) = request_id {
+            extra.insert("request_id".to_string(), id.to_string());
+        }
+        LogEntry {
+            timestamp: Some(
+                DateTime::parse_from_rfc3339(time)
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            severity,
+            message: message.to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra,
+            },
+            raw: message.to_string(),
+            source: None,
+        }
+    }
+
+    fn tool() -> QueryLogsTool {
+        QueryLogsTool::new(vec![
+            entry(
+                "2024-01-15T10:00:00Z",
+                Severity::Info,
+                "Request started",
+                Some("abc"),
+            ),
+            entry(
+                "2024-01-15T10:00:01Z",
+                Severity::Error,
+                "Query failed",
+                Some("abc"),
+            ),
+            entry(
+                "2024-01-15T10:05:00Z",
+                Severity::Warning,
+                "Slow query",
+                Some("def"),
+            ),
+            entry(
+                "2024-01-15T11:00:00Z",
+                Severity::Error,
+                "Query failed",
+                None,
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_query_logs_by_request_id() {
+        let output = tool().call(json!({"request_id": "abc"})).await.unwrap();
+        assert_eq!(output["total_matches"], 2);
+        assert_eq!(output["entries"][0]["message"], "Request started");
+    }
+
+    #[tokio::test]
+    async fn test_query_logs_by_time_window_and_severity() {
+        let output = tool()
+            .call(json!({
+                "since": "2024-01-15T10:00:01Z",
+                "until": "2024-01-15T10:30:00Z"
+            }))
+            .await
+            .unwrap();
+        assert_eq!(output["total_matches"], 2);
+
+        let output = tool()
+            .call(json!({"severity": "warn", "contains": "query"}))
+            .await
+            .unwrap();
+        assert_eq!(output["total_matches"], 1);
+        assert_eq!(output["entries"][0]["severity"], "Warning");
+
+        let output = tool()
+            .call(json!({"severity": "error", "limit": 1}))
+            .await
+            .unwrap();
+        assert_eq!(output["total_matches"], 2);
+        assert_eq!(output["entries"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_logs_rejects_bad_timestamp() {
+        assert!(matches!(
+            tool().call(json!({"since": "yesterday"})).await,
+            Err(MCPError::InvalidParameters(_))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+//! Built-in tools that run in-process.
+//!
+//! These are offered to the model next to tools discovered from external MCP
+//! servers, without spawning anything: `search_code` and `read_source_context`
+//! look at a configured source tree, and `query_logs` searches the log entries
+//! loaded for the current run.
+
+mod code;
+mod logs;
+
+pub use code::{ReadSourceContextTool, SearchCodeTool, SourceTree};
+pub use logs::QueryLogsTool;
+
+use crate::mcp::{BuiltinToolsConfig, MCPError, Result, ToolInfo};
+use crate::types::LogEntry;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Server name recorded for built-in tools
+pub const BUILTIN_SERVER: &str = "builtin";
+
+/// A tool executed in-process rather than by an MCP server
+#[async_trait]
+pub trait LocalTool: Send + Sync {
+    /// Name, description and input schema
+    fn info(&self) -> ToolInfo;
+
+    /// Run the tool with the model-supplied arguments
+    async fn call(&self, arguments: Value) -> Result<Value>;
+}
+
+/// Registry of in-process tools, keyed by name
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: BTreeMap<String, Arc<dyn LocalTool>>,
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in tools enabled by `config`.
+    ///
+    /// The code tools need a `source_root`; `query_logs` needs `entries`.
+    pub fn builtin(config: &BuiltinToolsConfig, entries: Vec<LogEntry>) -> Self {
+        let mut registry = Self::new();
+        if !config.enabled {
+            return registry;
+        }
+
+        if let Some(root) = &config.source_root {
+            match SourceTree::new(root) {
+                Ok(tree) => {
+                    let tree = Arc::new(tree);
+                    registry.register(SearchCodeTool::new(Arc::clone(&tree), config.max_results));
+                    registry.register(ReadSourceContextTool::new(tree, config.context_lines));
+                }
+                Err(e) => log::warn!("Code tools disabled: {}", e),
+            }
+        }
+
+        if !entries.is_empty() {
+            registry.register(QueryLogsTool::new(entries));
+        }
+
+        registry
+    }
+
+    /// Add a tool, replacing any tool with the same name
+    pub fn register(&mut self, tool: impl LocalTool + 'static) {
+        self.tools.insert(tool.info().name, Arc::new(tool));
+    }
+
+    /// Descriptions of every registered tool, sorted by name
+    pub fn tools(&self) -> Vec<ToolInfo> {
+        self.tools.values().map(|tool| tool.info()).collect()
+    }
+
+    /// Check if a tool is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.tools.contains_key(name)
+    }
+
+    /// Check if the registry has no tools
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Run a tool, returning its output in the MCP `tools/call` result shape
+    pub async fn call(&self, name: &str, arguments: Value) -> Result<Value> {
+        let tool = self.tools.get(name).ok_or_else(|| MCPError::ToolNotFound {
+            tool: name.to_string(),
+        })?;
+
+        let output = tool.call(arguments).await?;
+        let text = match output {
+            Value::String(text) => text,
+            other => serde_json::to_string_pretty(&other).unwrap_or_default(),
+        };
+
+        Ok(json!({"content": [{"type": "text", "text": text}]}))
+    }
+}
+
+/// Read a required string argument
+fn required_str<'a>(arguments: &'a Value, name: &str) -> Result<&'a str> {
+    arguments
+        .get(name)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| MCPError::InvalidParameters(format!("Missing string `{}`", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    #[async_trait]
+    impl LocalTool for Echo {
+        fn info(&self) -> ToolInfo {
+            ToolInfo {
+                name: "echo".to_string(),
+                description: "Echo the text back".to_string(),
+                parameters: json!({"type": "object"}),
+                server: BUILTIN_SERVER.to_string(),
+            }
+        }
+
+        async fn call(&self, arguments: Value) -> Result<Value> {
+            Ok(Value::String(required_str(&arguments, "text")?.to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_wraps_output_as_text_content() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Echo);
+
+        assert!(registry.contains("echo"));
+        let result = registry.call("echo", json!({"text": "hi"})).await.unwrap();
+        assert_eq!(result["content"][0]["text"], "hi");
+
+        assert!(matches!(
+            registry.call("echo", json!({})).await,
+            Err(MCPError::InvalidParameters(_))
+        ));
+        assert!(matches!(
+            registry.call("missing", json!({})).await,
+            Err(MCPError::ToolNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_builtin_registry_respects_config() {
+        let config = BuiltinToolsConfig::default();
+        assert!(ToolRegistry::builtin(&config, Vec::new()).is_empty());
+
+        let disabled = BuiltinToolsConfig {
+            enabled: false,
+            source_root: Some(".".to_string()),
+            ..Default::default()
+        };
+        assert!(ToolRegistry::builtin(&disabled, Vec::new()).is_empty());
+
+        let with_source = BuiltinToolsConfig {
+            source_root: Some(".".to_string()),
+            ..Default::default()
+        };
+        let names: Vec<_> = ToolRegistry::builtin(&with_source, Vec::new())
+            .tools()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["read_source_context", "search_code"]);
+    }
+}
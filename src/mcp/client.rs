@@ -1,13 +1,21 @@
-use crate::mcp::{MCPConfig, MCPError, Result, StdioTransport, ToolInfo, Transport};
+use crate::mcp::builtin::{ToolRegistry, BUILTIN_SERVER};
+use crate::mcp::{
+    HttpTransport, MCPConfig, MCPError, Result, RpcConnection, ServerNotification, StdioTransport,
+    ToolInfo, Transport, WebSocketTransport,
+};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How many notifications are buffered for slow subscribers
+const NOTIFICATION_CAPACITY: usize = 64;
 
 /// Connection to an MCP server
 pub struct ServerConnection {
     pub name: String,
     pub connected: bool,
-    pub transport: Arc<Mutex<Box<dyn Transport>>>,
+    pub rpc: Arc<RpcConnection>,
 }
 
 /// MCP Client for managing connections and tool invocations
@@ -18,6 +26,10 @@ pub struct MCPClient {
     tools: HashMap<String, ToolInfo>,
     /// Configuration
     config: MCPConfig,
+    /// Notifications received from any server
+    notifications: broadcast::Sender<ServerNotification>,
+    /// In-process tools, invoked without a server
+    local_tools: ToolRegistry,
 }
 
 impl MCPClient {
@@ -30,6 +42,8 @@ impl MCPClient {
             servers: HashMap::new(),
             tools: HashMap::new(),
             config,
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            local_tools: ToolRegistry::new(),
         })
     }
 
@@ -68,34 +82,37 @@ impl MCPClient {
     async fn connect_server(&mut self, server_config: &crate::mcp::ServerConfig) -> Result<()> {
         use crate::mcp::ConnectionConfig;
 
-        let transport: Box<dyn Transport> = match &server_config.connection {
+        let transport: Arc<dyn Transport> = match &server_config.connection {
             ConnectionConfig::Stdio { command, args } => {
                 let transport = StdioTransport::new(command.clone(), args.clone());
                 transport.start().await?;
-                Box::new(transport)
-            }
-            ConnectionConfig::Http { url: _ } => {
-                return Err(MCPError::TransportError(
-                    "HTTP transport not yet implemented".to_string(),
-                ));
+                Arc::new(transport)
             }
-            ConnectionConfig::WebSocket { url: _ } => {
-                return Err(MCPError::TransportError(
-                    "WebSocket transport not yet implemented".to_string(),
-                ));
+            ConnectionConfig::Http { url } => Arc::new(HttpTransport::new(
+                url.clone(),
+                server_config.auth.as_ref(),
+            )?),
+            ConnectionConfig::WebSocket { url } => {
+                Arc::new(WebSocketTransport::connect(url, server_config.auth.as_ref()).await?)
             }
         };
 
-        let transport = Arc::new(Mutex::new(transport));
+        let rpc = Arc::new(RpcConnection::new(
+            server_config.name.clone(),
+            transport,
+            self.notifications.clone(),
+        ));
 
         // Perform MCP initialization handshake
-        self.initialize_connection(&server_config.name, &transport)
-            .await?;
+        if let Err(e) = self.initialize_connection(&server_config.name, &rpc).await {
+            let _ = rpc.close().await;
+            return Err(e);
+        }
 
         let connection = ServerConnection {
             name: server_config.name.clone(),
             connected: true,
-            transport,
+            rpc,
         };
 
         self.servers.insert(server_config.name.clone(), connection);
@@ -103,31 +120,19 @@ impl MCPClient {
     }
 
     /// Initialize MCP connection with handshake
-    async fn initialize_connection(
-        &self,
-        server_name: &str,
-        transport: &Arc<Mutex<Box<dyn Transport>>>,
-    ) -> Result<()> {
-        // Send initialize request
-        let init_request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 0,
-            "method": "initialize",
-            "params": {
-                "protocolVersion": "2024-11-05",
-                "capabilities": {},
-                "clientInfo": {
-                    "name": "logai",
-                    "version": env!("CARGO_PKG_VERSION")
-                }
+    async fn initialize_connection(&self, server_name: &str, rpc: &RpcConnection) -> Result<()> {
+        let params = serde_json::json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "logai",
+                "version": env!("CARGO_PKG_VERSION")
             }
         });
 
-        let transport_guard = transport.lock().await;
-        transport_guard.send(init_request).await?;
-
-        // Receive initialize response
-        let response = transport_guard.receive().await?;
+        let response = self
+            .with_timeout(server_name, rpc.request("initialize", params))
+            .await?;
 
         // Check for errors
         if let Some(error) = response.get("error") {
@@ -149,19 +154,44 @@ impl MCPClient {
             ));
         }
 
+        // Tell the server initialization is complete before issuing requests
+        rpc.notify("notifications/initialized", serde_json::json!({}))
+            .await?;
+
         log::debug!("Initialized connection to server '{}'", server_name);
         Ok(())
     }
 
+    /// Wait for a server response, giving up after the default timeout
+    async fn with_timeout(
+        &self,
+        server_name: &str,
+        request: impl std::future::Future<Output = Result<serde_json::Value>>,
+    ) -> Result<serde_json::Value> {
+        let timeout = Duration::from_secs(self.config.default_timeout);
+        tokio::time::timeout(timeout, request).await.map_err(|_| {
+            MCPError::TransportError(format!(
+                "Server '{}' did not respond within {}s",
+                server_name, self.config.default_timeout
+            ))
+        })?
+    }
+
     /// Discover tools from all connected servers
+    ///
+    /// Tools previously discovered from a server are replaced by its current list.
     pub async fn discover_tools(&mut self) -> Result<Vec<ToolInfo>> {
         let mut all_tools = Vec::new();
+        let mut refreshed = Vec::new();
 
         for (server_name, connection) in &self.servers {
             if !connection.connected {
                 continue;
             }
 
+            // Any change announced before this point is covered by the new list
+            connection.rpc.take_tools_changed();
+
             match self
                 .discover_tools_from_server(server_name, connection)
                 .await
@@ -173,6 +203,7 @@ impl MCPClient {
                         server_name
                     );
                     all_tools.extend(tools);
+                    refreshed.push(server_name.clone());
                 }
                 Err(e) => {
                     log::error!(
@@ -185,6 +216,8 @@ impl MCPClient {
         }
 
         // Store tools in registry
+        self.tools
+            .retain(|_, tool| !refreshed.contains(&tool.server));
         for tool in &all_tools {
             self.tools.insert(tool.name.clone(), tool.clone());
         }
@@ -198,19 +231,12 @@ impl MCPClient {
         server_name: &str,
         connection: &ServerConnection,
     ) -> Result<Vec<ToolInfo>> {
-        // Send list_tools request
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "tools/list",
-            "params": {}
-        });
-
-        let transport = connection.transport.lock().await;
-        transport.send(request).await?;
-
-        // Receive response
-        let response = transport.receive().await?;
+        let response = self
+            .with_timeout(
+                server_name,
+                connection.rpc.request("tools/list", serde_json::json!({})),
+            )
+            .await?;
 
         // Parse tools from response
         log::debug!(
@@ -262,8 +288,7 @@ impl MCPClient {
     /// Close all server connections
     pub async fn disconnect(&mut self) -> Result<()> {
         for (name, connection) in self.servers.iter_mut() {
-            let mut transport = connection.transport.lock().await;
-            if let Err(e) = transport.close().await {
+            if let Err(e) = connection.rpc.close().await {
                 log::error!("Failed to close connection to '{}': {}", name, e);
             }
             connection.connected = false;
@@ -271,6 +296,16 @@ impl MCPClient {
         Ok(())
     }
 
+    /// Offer in-process tools alongside those discovered from servers.
+    ///
+    /// A local tool replaces any server tool with the same name.
+    pub fn register_local_tools(&mut self, registry: ToolRegistry) {
+        for tool in registry.tools() {
+            self.tools.insert(tool.name.clone(), tool);
+        }
+        self.local_tools = registry;
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &MCPConfig {
         &self.config
@@ -295,14 +330,26 @@ impl MCPClient {
     pub fn connected_servers(&self) -> Vec<&str> {
         self.servers
             .values()
-            .filter(|s| s.connected)
+            .filter(|s| s.connected && s.rpc.is_open())
             .map(|s| s.name.as_str())
             .collect()
     }
 
     /// Check if any servers are connected
     pub fn is_connected(&self) -> bool {
-        self.servers.values().any(|s| s.connected)
+        self.servers
+            .values()
+            .any(|s| s.connected && s.rpc.is_open())
+    }
+
+    /// Subscribe to notifications sent by connected servers
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<ServerNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Check if any server announced a changed tool list since the last discovery
+    pub fn tools_changed(&self) -> bool {
+        self.servers.values().any(|s| s.rpc.tools_changed())
     }
 
     /// Invoke a tool by name with parameters
@@ -327,27 +374,31 @@ impl MCPClient {
         // Validate parameters against schema (basic validation)
         self.validate_parameters(tool_name, &params, &tool.parameters)?;
 
-        // Get the server connection
-        let connection = self.servers.get(&tool.server).ok_or_else(|| {
-            MCPError::InvocationFailed(format!("Server '{}' not found", tool.server))
-        })?;
-
-        if !connection.connected {
-            return Err(MCPError::InvocationFailed(format!(
-                "Server '{}' is not connected",
-                tool.server
-            )));
-        }
-
         // Get timeout for this tool
         let timeout = self.config.get_tool_timeout(tool_name);
 
         // Invoke with timeout
-        let result = tokio::time::timeout(
-            timeout,
-            self.invoke_tool_on_server(tool_name, params, connection),
-        )
-        .await;
+        let result = if tool.server == BUILTIN_SERVER {
+            tokio::time::timeout(timeout, self.local_tools.call(tool_name, params)).await
+        } else {
+            // Get the server connection
+            let connection = self.servers.get(&tool.server).ok_or_else(|| {
+                MCPError::InvocationFailed(format!("Server '{}' not found", tool.server))
+            })?;
+
+            if !connection.connected {
+                return Err(MCPError::InvocationFailed(format!(
+                    "Server '{}' is not connected",
+                    tool.server
+                )));
+            }
+
+            tokio::time::timeout(
+                timeout,
+                self.invoke_tool_on_server(tool_name, params, connection),
+            )
+            .await
+        };
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
@@ -392,22 +443,16 @@ impl MCPClient {
         params: serde_json::Value,
         connection: &ServerConnection,
     ) -> Result<serde_json::Value> {
-        // Send tool invocation request
-        let request = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": 2,
-            "method": "tools/call",
-            "params": {
-                "name": tool_name,
-                "arguments": params
-            }
-        });
-
-        let transport = connection.transport.lock().await;
-        transport.send(request).await?;
-
-        // Receive response
-        let response = transport.receive().await?;
+        let response = connection
+            .rpc
+            .request(
+                "tools/call",
+                serde_json::json!({
+                    "name": tool_name,
+                    "arguments": params
+                }),
+            )
+            .await?;
 
         // Check for errors
         if let Some(error) = response.get("error") {
@@ -512,4 +557,41 @@ mod tests {
         let client = MCPClient::new(config).unwrap();
         assert!(!client.is_connected());
     }
+
+    #[tokio::test]
+    async fn test_local_tools_are_invoked_in_process() {
+        use crate::types::{LogEntry, LogMetadata, Severity};
+
+        let entry = LogEntry {
+            timestamp: None,
+            severity: Severity::Error,
+            message: "Query failed".to_string(),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: "ERROR Query failed request_id=abc".to_string(),
+            source: None,
+        };
+
+        let mut client = MCPClient::new(MCPConfig::default()).unwrap();
+        client.register_local_tools(ToolRegistry::builtin(&Default::default(), vec![entry]));
+
+        assert!(!client.is_connected());
+        assert_eq!(client.get_tool("query_logs").unwrap().server, "builtin");
+
+        let result = client
+            .invoke_tool("query_logs", serde_json::json!({"request_id": "abc"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.metadata.server, "builtin");
+        assert!(result.data.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Query failed"));
+    }
 }
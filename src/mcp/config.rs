@@ -1,9 +1,10 @@
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
 /// Configuration for MCP integration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPConfig {
     /// List of MCP servers to connect to
     #[serde(default)]
@@ -14,6 +15,64 @@ pub struct MCPConfig {
     /// Per-tool timeout overrides (seconds)
     #[serde(default)]
     pub tool_timeouts: HashMap<String, u64>,
+    /// Built-in in-process tools
+    #[serde(default)]
+    pub builtin_tools: BuiltinToolsConfig,
+}
+
+/// Configuration for the built-in tools (`search_code`, `read_source_context`, `query_logs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltinToolsConfig {
+    /// Whether built-in tools are offered
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Source tree searched by the code tools (code tools are off when unset)
+    #[serde(default)]
+    pub source_root: Option<String>,
+    /// Maximum matches returned by `search_code`
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// Lines shown on each side of the requested line by `read_source_context`
+    #[serde(default = "default_context_lines")]
+    pub context_lines: usize,
+    /// Most recent log entries kept in memory for `query_logs`
+    #[serde(default = "default_max_log_entries")]
+    pub max_log_entries: usize,
+}
+
+impl Default for BuiltinToolsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            source_root: None,
+            max_results: default_max_results(),
+            context_lines: default_context_lines(),
+            max_log_entries: default_max_log_entries(),
+        }
+    }
+}
+
+fn default_max_results() -> usize {
+    20
+}
+
+fn default_context_lines() -> usize {
+    10
+}
+
+fn default_max_log_entries() -> usize {
+    10_000
+}
+
+impl Default for MCPConfig {
+    fn default() -> Self {
+        Self {
+            servers: Vec::new(),
+            default_timeout: default_timeout(),
+            tool_timeouts: HashMap::new(),
+            builtin_tools: BuiltinToolsConfig::default(),
+        }
+    }
 }
 
 fn default_timeout() -> u64 {
@@ -37,6 +96,10 @@ impl MCPConfig {
             if server.name.is_empty() {
                 return Err("Server name cannot be empty".to_string());
             }
+            if let Some(auth) = &server.auth {
+                auth.header_value()
+                    .map_err(|e| format!("Server '{}': {}", server.name, e))?;
+            }
         }
         Ok(())
     }
@@ -93,3 +156,106 @@ pub struct AuthConfig {
     /// Credentials (key-value pairs)
     pub credentials: HashMap<String, String>,
 }
+
+impl AuthConfig {
+    /// Value of the `Authorization` header for this configuration.
+    ///
+    /// `Bearer` uses the `token` credential; `Basic` uses `username` and
+    /// `password`.
+    pub fn header_value(&self) -> Result<String, String> {
+        let credential = |key: &str| {
+            self.credentials
+                .get(key)
+                .map(String::as_str)
+                .ok_or_else(|| format!("{} auth requires a '{}' credential", self.scheme, key))
+        };
+
+        match self.scheme.to_ascii_lowercase().as_str() {
+            "bearer" => Ok(format!("Bearer {}", credential("token")?)),
+            "basic" => {
+                let username = credential("username")?;
+                let password = self.credentials.get("password").map_or("", String::as_str);
+                let encoded = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password));
+                Ok(format!("Basic {}", encoded))
+            }
+            _ => Err(format!(
+                "Unsupported auth scheme '{}' (expected Bearer or Basic)",
+                self.scheme
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(scheme: &str, credentials: &[(&str, &str)]) -> AuthConfig {
+        AuthConfig {
+            scheme: scheme.to_string(),
+            credentials: credentials
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_auth_header_value() {
+        assert_eq!(
+            auth("Bearer", &[("token", "secret")])
+                .header_value()
+                .unwrap(),
+            "Bearer secret"
+        );
+        assert_eq!(
+            auth("basic", &[("username", "user"), ("password", "pass")])
+                .header_value()
+                .unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+        assert!(auth("Bearer", &[]).header_value().is_err());
+        assert!(auth("Digest", &[("token", "x")]).header_value().is_err());
+    }
+
+    #[test]
+    fn test_validate_checks_auth() {
+        let config: MCPConfig = toml::from_str(
+            r#"
+            [[servers]]
+            name = "metrics"
+            connection = { type = "Http", url = "https://mcp.example.com/mcp" }
+            auth = { scheme = "Bearer", credentials = {} }
+            "#,
+        )
+        .unwrap();
+
+        let error = config.validate().unwrap_err();
+        assert!(error.contains("metrics"));
+        assert!(error.contains("token"));
+    }
+
+    #[test]
+    fn test_builtin_tools_config() {
+        let config: MCPConfig = toml::from_str("").unwrap();
+        assert!(config.builtin_tools.enabled);
+        assert_eq!(config.default_timeout, MCPConfig::default().default_timeout);
+        assert!(config.builtin_tools.source_root.is_none());
+
+        let config: MCPConfig = toml::from_str(
+            r#"
+            [builtin_tools]
+            source_root = "/src/app"
+            context_lines = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.builtin_tools.source_root.as_deref(),
+            Some("/src/app")
+        );
+        assert_eq!(config.builtin_tools.context_lines, 3);
+        assert_eq!(config.builtin_tools.max_results, 20);
+    }
+}
@@ -1,13 +1,20 @@
+pub mod builtin;
 pub mod client;
 pub mod config;
 pub mod error;
 pub mod protocol;
+pub mod rpc;
+pub mod server;
 pub mod transport;
 
+pub use builtin::{LocalTool, ToolRegistry};
 pub use client::MCPClient;
-pub use config::{AuthConfig, ConnectionConfig, MCPConfig, ServerConfig};
+pub use config::{AuthConfig, BuiltinToolsConfig, ConnectionConfig, MCPConfig, ServerConfig};
 pub use error::{MCPError, Result};
 pub use protocol::{
-    ToolInfo, ToolInvocation, ToolInvocationRecord, ToolInvocationSummary, ToolMetadata, ToolResult,
+    ServerNotification, ToolInfo, ToolInvocation, ToolInvocationRecord, ToolInvocationSummary,
+    ToolMetadata, ToolResult,
 };
-pub use transport::{StdioTransport, Transport};
+pub use rpc::RpcConnection;
+pub use server::MCPServer;
+pub use transport::{HttpTransport, StdioTransport, Transport, WebSocketTransport};
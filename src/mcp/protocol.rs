@@ -72,3 +72,14 @@ pub struct ToolInvocationSummary {
     /// Which suggestions/resources came from this tool
     pub contributed_to: Vec<String>,
 }
+
+/// A notification sent by an MCP server outside of any request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerNotification {
+    /// Name of the server that sent it
+    pub server: String,
+    /// Notification method, e.g. `notifications/tools/list_changed`
+    pub method: String,
+    /// Notification parameters
+    pub params: serde_json::Value,
+}
@@ -0,0 +1,420 @@
+//! JSON-RPC request multiplexing over an MCP transport.
+//!
+//! Each connection owns a reader task that pulls messages off the transport
+//! and routes them: responses go to the request waiting on the matching id,
+//! notifications are broadcast to subscribers, and server-initiated requests
+//! are answered directly. Any number of requests may be in flight at once.
+
+use crate::mcp::{MCPError, Result, ServerNotification, Transport};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+
+type PendingMap = Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>;
+
+/// State shared between a connection and its reader task
+struct Shared {
+    server: String,
+    transport: Arc<dyn Transport>,
+    pending: PendingMap,
+    open: AtomicBool,
+    tools_changed: AtomicBool,
+    notifications: broadcast::Sender<ServerNotification>,
+}
+
+/// A JSON-RPC connection to one MCP server
+pub struct RpcConnection {
+    shared: Arc<Shared>,
+    next_id: AtomicU64,
+    reader: JoinHandle<()>,
+}
+
+/// Removes a pending request if its caller stops waiting (e.g. on timeout)
+struct PendingGuard<'a> {
+    pending: &'a PendingMap,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl RpcConnection {
+    /// Start routing messages from `transport`; notifications are published
+    /// on `notifications`
+    pub fn new(
+        server: impl Into<String>,
+        transport: Arc<dyn Transport>,
+        notifications: broadcast::Sender<ServerNotification>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            server: server.into(),
+            transport,
+            pending: Mutex::new(HashMap::new()),
+            open: AtomicBool::new(true),
+            tools_changed: AtomicBool::new(false),
+            notifications,
+        });
+
+        let reader = tokio::spawn(Self::read_loop(Arc::clone(&shared)));
+
+        Self {
+            shared,
+            next_id: AtomicU64::new(0),
+            reader,
+        }
+    }
+
+    /// Send a request and wait for the response with the same id.
+    ///
+    /// Returns the full response message, including any `error` member.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        if !self.is_open() {
+            return Err(MCPError::TransportError(format!(
+                "Connection to '{}' is closed",
+                self.shared.server
+            )));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.shared.pending.lock().unwrap().insert(id, sender);
+        let _guard = PendingGuard {
+            pending: &self.shared.pending,
+            id,
+        };
+
+        self.shared
+            .transport
+            .send(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .await?;
+
+        receiver.await.unwrap_or_else(|_| {
+            Err(MCPError::TransportError(format!(
+                "Connection to '{}' closed before responding",
+                self.shared.server
+            )))
+        })
+    }
+
+    /// Send a notification (no response expected)
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        self.shared
+            .transport
+            .send(json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+            }))
+            .await
+    }
+
+    /// Whether the reader is still receiving messages
+    pub fn is_open(&self) -> bool {
+        self.shared.open.load(Ordering::SeqCst)
+    }
+
+    /// Whether the server reported a changed tool list
+    pub fn tools_changed(&self) -> bool {
+        self.shared.tools_changed.load(Ordering::SeqCst)
+    }
+
+    /// Like [`Self::tools_changed`], but also clears the flag
+    pub fn take_tools_changed(&self) -> bool {
+        self.shared.tools_changed.swap(false, Ordering::SeqCst)
+    }
+
+    /// Stop the reader and close the transport
+    pub async fn close(&self) -> Result<()> {
+        self.shared.open.store(false, Ordering::SeqCst);
+        self.reader.abort();
+        Self::fail_pending(&self.shared, "connection closed");
+        self.shared.transport.close().await
+    }
+
+    async fn read_loop(shared: Arc<Shared>) {
+        loop {
+            match shared.transport.receive().await {
+                Ok(message) => Self::route(&shared, message).await,
+                Err(e) => {
+                    log::debug!("MCP: Reader for '{}' stopped: {}", shared.server, e);
+                    shared.open.store(false, Ordering::SeqCst);
+                    Self::fail_pending(&shared, &e.to_string());
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn route(shared: &Shared, message: Value) {
+        let method = message.get("method").and_then(|m| m.as_str());
+        let id = message.get("id").filter(|id| !id.is_null());
+
+        match (method, id) {
+            // Response to one of our requests
+            (None, Some(id)) => {
+                let sender =
+                    Self::parse_id(id).and_then(|id| shared.pending.lock().unwrap().remove(&id));
+                match sender {
+                    Some(sender) => {
+                        let _ = sender.send(Ok(message));
+                    }
+                    None => log::debug!(
+                        "MCP: Dropping response with unknown id {} from '{}'",
+                        id,
+                        shared.server
+                    ),
+                }
+            }
+            // Request initiated by the server
+            (Some(method), Some(id)) => {
+                let response = if method == "ping" {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                } else {
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32601, "message": format!("Method not found: {}", method)}
+                    })
+                };
+                if let Err(e) = shared.transport.send(response).await {
+                    log::debug!("MCP: Failed to answer '{}' request: {}", method, e);
+                }
+            }
+            (Some(method), None) => Self::handle_notification(shared, method, &message),
+            (None, None) => log::debug!(
+                "MCP: Ignoring malformed message from '{}': {}",
+                shared.server,
+                message
+            ),
+        }
+    }
+
+    fn handle_notification(shared: &Shared, method: &str, message: &Value) {
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match method {
+            "notifications/tools/list_changed" => {
+                shared.tools_changed.store(true, Ordering::SeqCst);
+            }
+            "notifications/message" => {
+                let level = match params.get("level").and_then(|l| l.as_str()) {
+                    Some("debug") => log::Level::Debug,
+                    Some("info") | Some("notice") => log::Level::Info,
+                    Some("warning") => log::Level::Warn,
+                    Some(_) => log::Level::Error,
+                    None => log::Level::Info,
+                };
+                log::log!(
+                    level,
+                    "MCP [{}]: {}",
+                    shared.server,
+                    params.get("data").unwrap_or(&Value::Null)
+                );
+            }
+            _ => log::debug!("MCP: Notification {} from '{}'", method, shared.server),
+        }
+
+        // Nobody listening is fine
+        let _ = shared.notifications.send(ServerNotification {
+            server: shared.server.clone(),
+            method: method.to_string(),
+            params,
+        });
+    }
+
+    fn fail_pending(shared: &Shared, reason: &str) {
+        let pending: Vec<_> = shared.pending.lock().unwrap().drain().collect();
+        for (_, sender) in pending {
+            let _ = sender.send(Err(MCPError::TransportError(format!(
+                "Connection to '{}' lost: {}",
+                shared.server, reason
+            ))));
+        }
+    }
+
+    fn parse_id(id: &Value) -> Option<u64> {
+        id.as_u64()
+            .or_else(|| id.as_str().and_then(|s| s.parse().ok()))
+    }
+}
+
+impl Drop for RpcConnection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use tokio::sync::{mpsc, Mutex as AsyncMutex};
+
+    /// In-memory transport: sent messages are captured, received ones are fed
+    /// by the test
+    struct ChannelTransport {
+        sent: mpsc::UnboundedSender<Value>,
+        incoming: AsyncMutex<mpsc::UnboundedReceiver<Value>>,
+    }
+
+    #[async_trait]
+    impl Transport for ChannelTransport {
+        async fn send(&self, message: Value) -> Result<()> {
+            self.sent.send(message).unwrap();
+            Ok(())
+        }
+
+        async fn receive(&self) -> Result<Value> {
+            self.incoming
+                .lock()
+                .await
+                .recv()
+                .await
+                .ok_or_else(|| MCPError::TransportError("closed".to_string()))
+        }
+
+        async fn close(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_alive(&self) -> bool {
+            true
+        }
+    }
+
+    fn connection() -> (
+        RpcConnection,
+        mpsc::UnboundedReceiver<Value>,
+        mpsc::UnboundedSender<Value>,
+        broadcast::Receiver<ServerNotification>,
+    ) {
+        let (sent_tx, sent_rx) = mpsc::unbounded_channel();
+        let (incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+        let (notify_tx, notify_rx) = broadcast::channel(16);
+        let transport = Arc::new(ChannelTransport {
+            sent: sent_tx,
+            incoming: AsyncMutex::new(incoming_rx),
+        });
+
+        (
+            RpcConnection::new("test", transport, notify_tx),
+            sent_rx,
+            incoming_tx,
+            notify_rx,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_responses_are_matched_by_id() {
+        let (rpc, mut sent, incoming, _) = connection();
+        let rpc = Arc::new(rpc);
+
+        let first = tokio::spawn({
+            let rpc = Arc::clone(&rpc);
+            async move { rpc.request("tools/call", json!({"name": "a"})).await }
+        });
+        let second = tokio::spawn({
+            let rpc = Arc::clone(&rpc);
+            async move { rpc.request("tools/call", json!({"name": "b"})).await }
+        });
+
+        let mut ids = HashMap::new();
+        for _ in 0..2 {
+            let request = sent.recv().await.unwrap();
+            ids.insert(
+                request["params"]["name"].as_str().unwrap().to_string(),
+                request["id"].clone(),
+            );
+        }
+
+        // Answer out of order, with a notification in between
+        incoming
+            .send(json!({"jsonrpc": "2.0", "id": ids["b"], "result": "B"}))
+            .unwrap();
+        incoming
+            .send(json!({"jsonrpc": "2.0", "method": "notifications/progress", "params": {}}))
+            .unwrap();
+        incoming
+            .send(json!({"jsonrpc": "2.0", "id": ids["a"], "result": "A"}))
+            .unwrap();
+
+        assert_eq!(first.await.unwrap().unwrap()["result"], "A");
+        assert_eq!(second.await.unwrap().unwrap()["result"], "B");
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_broadcast() {
+        let (rpc, _sent, incoming, mut notifications) = connection();
+
+        incoming
+            .send(json!({"jsonrpc": "2.0", "method": "notifications/tools/list_changed"}))
+            .unwrap();
+
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.server, "test");
+        assert_eq!(notification.method, "notifications/tools/list_changed");
+        assert!(rpc.take_tools_changed());
+        assert!(!rpc.take_tools_changed());
+    }
+
+    #[tokio::test]
+    async fn test_server_ping_is_answered() {
+        let (_rpc, mut sent, incoming, _) = connection();
+
+        incoming
+            .send(json!({"jsonrpc": "2.0", "id": "srv-1", "method": "ping"}))
+            .unwrap();
+        let reply = sent.recv().await.unwrap();
+        assert_eq!(reply["id"], "srv-1");
+        assert_eq!(reply["result"], json!({}));
+
+        incoming
+            .send(json!({"jsonrpc": "2.0", "id": 7, "method": "sampling/createMessage"}))
+            .unwrap();
+        let reply = sent.recv().await.unwrap();
+        assert_eq!(reply["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn test_pending_requests_fail_when_transport_closes() {
+        let (rpc, mut sent, incoming, _) = connection();
+        let rpc = Arc::new(rpc);
+
+        let request = tokio::spawn({
+            let rpc = Arc::clone(&rpc);
+            async move { rpc.request("tools/list", json!({})).await }
+        });
+        sent.recv().await.unwrap();
+        drop(incoming);
+
+        let error = request.await.unwrap().unwrap_err();
+        assert!(error.to_string().contains("lost"));
+        assert!(!rpc.is_open());
+        assert!(rpc.request("tools/list", json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_requests_are_removed() {
+        let (rpc, _sent, _incoming, _) = connection();
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(20),
+            rpc.request("tools/call", json!({})),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(rpc.shared.pending.lock().unwrap().is_empty());
+    }
+}
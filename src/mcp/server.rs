@@ -0,0 +1,586 @@
+//! Run logai as an MCP server.
+//!
+//! Exposes log parsing, error grouping, AI analysis and run history as MCP
+//! tools over stdio or streamable HTTP, so IDE assistants and agents can call
+//! into logai directly.
+
+use crate::ai::{AIProvider, AnalysisConfig, ParallelAnalyzer};
+use crate::analyzer::Analyzer;
+use crate::commands::{investigate::ParseSettings, InvestigateCommand};
+use crate::mcp::ToolInfo;
+use crate::parser::ParserConfig;
+use crate::storage::RunHistory;
+use crate::types::{ErrorGroup, LogEntry};
+use crate::Result;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+/// Name reported to clients and used as the `server` of every tool
+pub const SERVER_NAME: &str = "logai";
+
+/// MCP protocol revision implemented by the server
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Entries returned by `parse_logs` unless a `limit` is given
+const DEFAULT_ENTRY_LIMIT: usize = 100;
+
+/// Groups returned by `group_errors` unless a `limit` is given
+const DEFAULT_GROUP_LIMIT: usize = 20;
+
+/// Example entries kept per group in tool output
+const SAMPLE_ENTRIES: usize = 5;
+
+/// MCP server exposing logai's parsing, grouping and analysis as tools
+#[derive(Default)]
+pub struct MCPServer {
+    provider: Option<Arc<dyn AIProvider>>,
+    analysis_config: AnalysisConfig,
+    history_path: Option<PathBuf>,
+    parser_config: ParserConfig,
+}
+
+impl MCPServer {
+    /// Create a server without an AI provider (`analyze_group` is unavailable)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `provider` for the `analyze_group` tool
+    pub fn with_provider(mut self, provider: Arc<dyn AIProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Set the retry and concurrency settings used by `analyze_group`
+    pub fn with_analysis_config(mut self, config: AnalysisConfig) -> Self {
+        self.analysis_config = config;
+        self
+    }
+
+    /// Read runs from the history database at `path` instead of the default
+    pub fn with_history_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history_path = Some(path.into());
+        self
+    }
+
+    /// Apply `config` when parsing `parse_logs` and `group_errors` input
+    pub fn with_parser_config(mut self, config: ParserConfig) -> Self {
+        self.parser_config = config;
+        self
+    }
+
+    /// Tools offered by the server
+    pub fn tools(&self) -> Vec<ToolInfo> {
+        let source = json!({
+            "content": {"type": "string", "description": "Raw log text"},
+            "path": {"type": "string", "description": "Path of a log file to read"},
+            "format": {
+                "type": "string",
+                "description": "Log format (auto, json, logfmt, apache, nginx, syslog, plain, ecs, bunyan, pino, clef, gcp, cri, docker, journald, or a configured custom format)",
+                "default": "auto"
+            },
+            "no_multiline": {
+                "type": "boolean",
+                "description": "Disable multi-line handling (e.g. stack traces)",
+                "default": false
+            }
+        });
+        let with = |extra: Value| {
+            let mut properties = source.clone();
+            properties
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            json!({"type": "object", "properties": properties})
+        };
+
+        let mut tools = vec![
+            ToolInfo {
+                name: "parse_logs".to_string(),
+                description: "Parse log text or a log file into structured entries. \
+                              Provide either `content` or `path`."
+                    .to_string(),
+                parameters: with(json!({
+                    "limit": {"type": "integer", "description": "Maximum entries to return", "default": DEFAULT_ENTRY_LIMIT}
+                })),
+                server: SERVER_NAME.to_string(),
+            },
+            ToolInfo {
+                name: "group_errors".to_string(),
+                description: "Parse logs and group similar errors and warnings, most \
+                              severe and frequent first. Provide either `content` or `path`."
+                    .to_string(),
+                parameters: with(json!({
+                    "limit": {"type": "integer", "description": "Maximum groups to return", "default": DEFAULT_GROUP_LIMIT}
+                })),
+                server: SERVER_NAME.to_string(),
+            },
+            ToolInfo {
+                name: "get_run".to_string(),
+                description: "Fetch a past `logai investigate` run and its error groups \
+                              from history."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {"id": {"type": "integer", "description": "Run id"}},
+                    "required": ["id"]
+                }),
+                server: SERVER_NAME.to_string(),
+            },
+        ];
+
+        if self.provider.is_some() {
+            tools.push(ToolInfo {
+                name: "analyze_group".to_string(),
+                description: "Explain an error group returned by `group_errors` with AI: \
+                              root cause and suggested fixes."
+                    .to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "group": {"type": "object", "description": "An error group from `group_errors`"}
+                    },
+                    "required": ["group"]
+                }),
+                server: SERVER_NAME.to_string(),
+            });
+        }
+
+        tools
+    }
+
+    /// Handle one JSON-RPC message (or batch); returns the response, if any
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        if let Value::Array(batch) = message {
+            let mut responses = Vec::new();
+            for message in batch {
+                if let Some(response) = Box::pin(self.handle_message(message)).await {
+                    responses.push(response);
+                }
+            }
+            return (!responses.is_empty()).then_some(Value::Array(responses));
+        }
+
+        // Responses from the client and notifications need no reply
+        let method = message.get("method")?.as_str()?;
+        let id = message.get("id").filter(|id| !id.is_null())?.clone();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": params
+                    .get("protocolVersion")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(PROTOCOL_VERSION),
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION")}
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({
+                "tools": self
+                    .tools()
+                    .into_iter()
+                    .map(|tool| json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "inputSchema": tool.parameters
+                    }))
+                    .collect::<Vec<_>>()
+            })),
+            "tools/call" => self.handle_tool_call(&params).await,
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message}
+            }),
+        })
+    }
+
+    async fn handle_tool_call(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or((-32602, "Missing tool name".to_string()))?;
+        if !self.tools().iter().any(|tool| tool.name == name) {
+            return Err((-32602, format!("Unknown tool: {}", name)));
+        }
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        // Tool failures are results the caller's model can read, not protocol errors
+        Ok(match self.call_tool(name, &arguments).await {
+            Ok(output) => json!({
+                "content": [{
+                    "type": "text",
+                    "text": serde_json::to_string_pretty(&output).unwrap_or_default()
+                }],
+                "isError": false
+            }),
+            Err(e) => json!({
+                "content": [{"type": "text", "text": e.to_string()}],
+                "isError": true
+            }),
+        })
+    }
+
+    /// Run a tool directly and return its JSON output
+    pub async fn call_tool(&self, name: &str, arguments: &Value) -> Result<Value> {
+        match name {
+            "parse_logs" => {
+                let (entries, parser, total_lines) = self.load_entries(arguments)?;
+                let limit = Self::limit(arguments, DEFAULT_ENTRY_LIMIT);
+                Ok(json!({
+                    "parser": parser,
+                    "total_lines": total_lines,
+                    "parsed_entries": entries.len(),
+                    "truncated": entries.len() > limit,
+                    "entries": &entries[..entries.len().min(limit)]
+                }))
+            }
+            "group_errors" => {
+                let (entries, parser, _) = self.load_entries(arguments)?;
+                let groups = Analyzer::new().analyze(entries)?;
+                let limit = Self::limit(arguments, DEFAULT_GROUP_LIMIT);
+                Ok(json!({
+                    "parser": parser,
+                    "total_groups": groups.len(),
+                    "groups": Self::sample_groups(groups, limit)
+                }))
+            }
+            "analyze_group" => self.analyze_group(arguments).await,
+            "get_run" => self.get_run(arguments),
+            _ => anyhow::bail!("Unknown tool: {}", name),
+        }
+    }
+
+    async fn analyze_group(&self, arguments: &Value) -> Result<Value> {
+        let provider = self
+            .provider
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No AI provider configured"))?;
+        let group: ErrorGroup = serde_json::from_value(
+            arguments
+                .get("group")
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Missing `group` argument"))?,
+        )
+        .map_err(|e| anyhow::anyhow!("Invalid error group: {}", e))?;
+
+        let analyzer = ParallelAnalyzer::new(provider, self.analysis_config.clone());
+        let mut groups = vec![group];
+        let stats = analyzer.analyze_groups(&mut groups, |_| {}).await?;
+
+        match groups.remove(0).analysis {
+            Some(analysis) => Ok(serde_json::to_value(analysis)?),
+            None => {
+                let reasons: Vec<_> = stats.failure_reasons.into_keys().collect();
+                anyhow::bail!("AI analysis failed: {}", reasons.join("; "))
+            }
+        }
+    }
+
+    fn get_run(&self, arguments: &Value) -> Result<Value> {
+        let id = arguments
+            .get("id")
+            .and_then(|id| id.as_i64())
+            .ok_or_else(|| anyhow::anyhow!("Missing integer `id` argument"))?;
+
+        let history = match &self.history_path {
+            Some(path) => RunHistory::open(path)?,
+            None => RunHistory::new()?,
+        };
+        let run = history
+            .get_run(id)?
+            .ok_or_else(|| anyhow::anyhow!("Run #{} not found in history", id))?;
+        let groups = history.load_groups(id)?;
+
+        Ok(json!({
+            "run": run,
+            "groups": Self::sample_groups(groups, usize::MAX)
+        }))
+    }
+
+    /// Parse the `content` or `path` argument with the requested format
+    fn load_entries(&self, arguments: &Value) -> Result<(Vec<LogEntry>, String, usize)> {
+        let content = match (
+            arguments.get("content").and_then(|c| c.as_str()),
+            arguments.get("path").and_then(|p| p.as_str()),
+        ) {
+            (Some(content), _) => content.to_string(),
+            (None, Some(path)) => {
+                let mut content = String::new();
+                crate::parser::input::open(std::path::Path::new(path))
+                    .and_then(|mut file| file.read_to_string(&mut content))
+                    .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", path, e))?;
+                content
+            }
+            (None, None) => anyhow::bail!("Provide either `content` or `path`"),
+        };
+
+        let lines: Vec<String> = content.lines().map(String::from).collect();
+        let Some(first_line) = lines.first() else {
+            return Ok((Vec::new(), String::new(), 0));
+        };
+
+        let format = arguments
+            .get("format")
+            .and_then(|f| f.as_str())
+            .unwrap_or("auto");
+        let no_multiline = arguments
+            .get("no_multiline")
+            .and_then(|m| m.as_bool())
+            .unwrap_or(false);
+
+        let settings = ParseSettings {
+            log_format: format,
+            no_multiline,
+            config: &self.parser_config,
+        };
+        let source = arguments.get("path").and_then(|p| p.as_str());
+        let parser = InvestigateCommand::create_parser(&settings, first_line, source);
+        let entries = InvestigateCommand::parse_with_parser(&parser, &lines, no_multiline)?;
+
+        Ok((entries, parser.name().to_string(), lines.len()))
+    }
+
+    fn limit(arguments: &Value, default: usize) -> usize {
+        arguments
+            .get("limit")
+            .and_then(|l| l.as_u64())
+            .map_or(default, |l| l as usize)
+    }
+
+    /// Keep the first `limit` groups with a few example entries each
+    fn sample_groups(mut groups: Vec<ErrorGroup>, limit: usize) -> Vec<ErrorGroup> {
+        groups.truncate(limit);
+        for group in &mut groups {
+            group.entries.truncate(SAMPLE_ENTRIES);
+        }
+        groups
+    }
+
+    /// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes
+    pub async fn serve_stdio(self: Arc<Self>) -> Result<()> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.serve(stdin, tokio::io::stdout()).await
+    }
+
+    /// Serve newline-delimited JSON-RPC over any reader/writer pair.
+    ///
+    /// Requests are handled concurrently; responses are written as they complete.
+    pub async fn serve<R, W>(self: Arc<Self>, reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let writer = Arc::new(Mutex::new(writer));
+        let mut tasks = JoinSet::new();
+        let mut lines = reader.lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let server = Arc::clone(&self);
+            let writer = Arc::clone(&writer);
+            tasks.spawn(async move {
+                let response = match serde_json::from_str(&line) {
+                    Ok(message) => server.handle_message(message).await,
+                    Err(e) => Some(Self::parse_error(&e)),
+                };
+
+                if let Some(response) = response {
+                    let mut writer = writer.lock().await;
+                    let mut text = response.to_string();
+                    text.push('\n');
+                    if writer.write_all(text.as_bytes()).await.is_ok() {
+                        let _ = writer.flush().await;
+                    }
+                }
+            });
+
+            // Reap finished requests so the set doesn't grow unbounded
+            while tasks.try_join_next().is_some() {}
+        }
+
+        while tasks.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Serve MCP streamable HTTP on `listener` at the `/mcp` endpoint
+    pub async fn serve_http(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        let app = Router::new()
+            .route(
+                "/mcp",
+                post(Self::handle_http)
+                    .get(|| async { StatusCode::METHOD_NOT_ALLOWED })
+                    .delete(|| async { StatusCode::OK }),
+            )
+            .with_state(self);
+
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+
+    async fn handle_http(
+        axum::extract::State(server): axum::extract::State<Arc<Self>>,
+        body: String,
+    ) -> Response {
+        let response = match serde_json::from_str(&body) {
+            Ok(message) => server.handle_message(message).await,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    [(header::CONTENT_TYPE, "application/json")],
+                    Self::parse_error(&e).to_string(),
+                )
+                    .into_response()
+            }
+        };
+
+        match response {
+            Some(response) => (
+                [(header::CONTENT_TYPE, "application/json")],
+                response.to_string(),
+            )
+                .into_response(),
+            // Notifications and client responses are only acknowledged
+            None => StatusCode::ACCEPTED.into_response(),
+        }
+    }
+
+    fn parse_error(error: &serde_json::Error) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32700, "message": format!("Parse error: {}", error)}
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGS: &str = "2024-01-15T10:30:00Z ERROR Database connection failed: timeout for request 100234\n\
+                        2024-01-15T10:30:05Z ERROR Database connection failed: timeout for request 100876\n\
+                        2024-01-15T10:30:10Z INFO Request handled\n";
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools() {
+        let server = MCPServer::new();
+
+        let response = server
+            .handle_message(request(
+                "initialize",
+                json!({"protocolVersion": "2025-03-26"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(response["result"]["serverInfo"]["name"], "logai");
+
+        let response = server
+            .handle_message(request("tools/list", json!({})))
+            .await
+            .unwrap();
+        let names: Vec<_> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        // analyze_group needs a provider
+        assert_eq!(names, vec!["parse_logs", "group_errors", "get_run"]);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_get_no_response() {
+        let server = MCPServer::new();
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_message(notification).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_and_tool() {
+        let server = MCPServer::new();
+
+        let response = server
+            .handle_message(request("resources/list", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32601);
+
+        let response = server
+            .handle_message(request("tools/call", json!({"name": "analyze_group"})))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_group_errors_tool() {
+        let server = MCPServer::new();
+
+        let output = server
+            .call_tool("group_errors", &json!({"content": LOGS}))
+            .await
+            .unwrap();
+        assert_eq!(output["total_groups"], 1);
+        assert_eq!(output["groups"][0]["count"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_logs_limit_and_errors() {
+        let server = MCPServer::new();
+
+        let output = server
+            .call_tool("parse_logs", &json!({"content": LOGS, "limit": 1}))
+            .await
+            .unwrap();
+        assert_eq!(output["parsed_entries"], 3);
+        assert_eq!(output["truncated"], true);
+        assert_eq!(output["entries"].as_array().unwrap().len(), 1);
+
+        let response = server
+            .handle_message(request(
+                "tools/call",
+                json!({"name": "parse_logs", "arguments": {}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_batch_requests() {
+        let server = MCPServer::new();
+
+        let batch = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "ping"},
+            {"jsonrpc": "2.0", "method": "notifications/initialized"},
+            {"jsonrpc": "2.0", "id": 2, "method": "ping"}
+        ]);
+        let response = server.handle_message(batch).await.unwrap();
+        assert_eq!(response.as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,260 @@
+use super::Transport;
+use crate::mcp::{AuthConfig, MCPError, Result};
+use async_trait::async_trait;
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::sync::Mutex as StdMutex;
+use tokio::sync::{mpsc, Mutex};
+
+/// Header carrying the session assigned by the server during initialization
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Streamable HTTP transport.
+///
+/// Every outgoing message is POSTed to the server URL. The server answers
+/// with either a single JSON body or an SSE stream; messages from either are
+/// queued and handed out by [`Transport::receive`].
+pub struct HttpTransport {
+    client: Client,
+    url: String,
+    authorization: Option<String>,
+    session_id: StdMutex<Option<String>>,
+    sender: mpsc::UnboundedSender<Result<Value>>,
+    receiver: Mutex<mpsc::UnboundedReceiver<Result<Value>>>,
+}
+
+impl HttpTransport {
+    /// Create a transport for the MCP endpoint at `url`
+    pub fn new(url: String, auth: Option<&AuthConfig>) -> Result<Self> {
+        let authorization = auth
+            .map(|auth| auth.header_value())
+            .transpose()
+            .map_err(MCPError::ConfigError)?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        Ok(Self {
+            client: Client::new(),
+            url,
+            authorization,
+            session_id: StdMutex::new(None),
+            sender,
+            receiver: Mutex::new(receiver),
+        })
+    }
+
+    /// Session id assigned by the server, if any
+    pub fn session_id(&self) -> Option<String> {
+        self.session_id.lock().unwrap().clone()
+    }
+
+    fn request(&self, mut builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(authorization) = &self.authorization {
+            builder = builder.header(AUTHORIZATION, authorization);
+        }
+        if let Some(session_id) = self.session_id() {
+            builder = builder.header(SESSION_HEADER, session_id);
+        }
+        builder
+    }
+
+    /// Queue every JSON-RPC message in a JSON body (single message or batch)
+    fn queue_json(&self, body: &str) -> Result<()> {
+        if body.trim().is_empty() {
+            return Ok(());
+        }
+
+        let value: Value = serde_json::from_str(body)
+            .map_err(|e| MCPError::ProtocolError(format!("Failed to parse message: {}", e)))?;
+
+        match value {
+            Value::Array(messages) => {
+                for message in messages {
+                    let _ = self.sender.send(Ok(message));
+                }
+            }
+            message => {
+                let _ = self.sender.send(Ok(message));
+            }
+        }
+        Ok(())
+    }
+
+    /// Forward messages from an SSE response until the server ends the stream
+    fn spawn_event_stream(&self, mut response: reqwest::Response) {
+        let sender = self.sender.clone();
+
+        tokio::spawn(async move {
+            let mut parser = SseParser::default();
+            loop {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        for data in parser.feed(&chunk) {
+                            let message = serde_json::from_str(&data).map_err(|e| {
+                                MCPError::ProtocolError(format!("Failed to parse event: {}", e))
+                            });
+                            if sender.send(message).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Ok(None) => return,
+                    Err(e) => {
+                        let _ = sender.send(Err(MCPError::TransportError(format!(
+                            "Event stream failed: {}",
+                            e
+                        ))));
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, message: Value) -> Result<()> {
+        let response = self
+            .request(self.client.post(&self.url))
+            .header(CONTENT_TYPE, "application/json")
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(&message)
+            .send()
+            .await
+            .map_err(|e| MCPError::TransportError(format!("HTTP request failed: {}", e)))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().unwrap() = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if status == StatusCode::ACCEPTED {
+            // Notifications and responses are acknowledged without a body
+            return Ok(());
+        }
+        if status == StatusCode::NOT_FOUND && self.session_id().is_some() {
+            return Err(MCPError::TransportError(
+                "MCP session expired; reconnect to start a new one".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(MCPError::TransportError(format!(
+                "HTTP {}: {}",
+                status,
+                body.trim()
+            )));
+        }
+
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+        if is_event_stream {
+            self.spawn_event_stream(response);
+            Ok(())
+        } else {
+            let body = response
+                .text()
+                .await
+                .map_err(|e| MCPError::TransportError(format!("Failed to read body: {}", e)))?;
+            self.queue_json(&body)
+        }
+    }
+
+    async fn receive(&self) -> Result<Value> {
+        self.receiver.lock().await.recv().await.unwrap_or_else(|| {
+            Err(MCPError::TransportError(
+                "Connection closed by server".to_string(),
+            ))
+        })
+    }
+
+    async fn close(&self) -> Result<()> {
+        // Explicitly end the session; servers that don't support it reply 405
+        if self.session_id().is_some() {
+            let _ = self.request(self.client.delete(&self.url)).send().await;
+            *self.session_id.lock().unwrap() = None;
+        }
+        Ok(())
+    }
+
+    fn is_alive(&self) -> bool {
+        true
+    }
+}
+
+/// Incremental parser for `text/event-stream` bodies
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed raw bytes, returning the data of every completed event
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                // A blank line dispatches the event
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(data) = line.strip_prefix("data:") {
+                self.data
+                    .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+            }
+            // `event:`, `id:`, `retry:` and `:` comments are not needed here
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_dispatches_on_blank_line() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.feed(b"event: message\ndata: {\"id\":").is_empty());
+        assert!(parser.feed(b"1}\r\n").is_empty());
+        assert_eq!(parser.feed(b"\r\n"), vec!["{\"id\":1}"]);
+    }
+
+    #[test]
+    fn test_sse_parser_joins_multiline_data_and_skips_comments() {
+        let mut parser = SseParser::default();
+
+        let events = parser.feed(b": keep-alive\n\ndata: a\ndata: b\n\ndata:c\n\n");
+        assert_eq!(events, vec!["a\nb", "c"]);
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_auth() {
+        let auth = AuthConfig {
+            scheme: "Bearer".to_string(),
+            credentials: Default::default(),
+        };
+
+        let result = HttpTransport::new("http://localhost".to_string(), Some(&auth));
+        assert!(matches!(result, Err(MCPError::ConfigError(_))));
+    }
+}
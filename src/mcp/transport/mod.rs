@@ -0,0 +1,29 @@
+//! Transports carrying JSON-RPC messages between the MCP client and servers.
+
+mod http;
+mod stdio;
+mod websocket;
+
+pub use http::HttpTransport;
+pub use stdio::StdioTransport;
+pub use websocket::WebSocketTransport;
+
+use crate::mcp::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Transport layer for MCP communication
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Send a message to the server
+    async fn send(&self, message: Value) -> Result<()>;
+
+    /// Receive a message from the server
+    async fn receive(&self) -> Result<Value>;
+
+    /// Close the connection
+    async fn close(&self) -> Result<()>;
+
+    /// Check if the connection is alive
+    fn is_alive(&self) -> bool;
+}
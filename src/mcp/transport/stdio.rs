@@ -1,30 +1,20 @@
+use super::Transport;
 use crate::mcp::{MCPError, Result};
 use async_trait::async_trait;
 use serde_json::Value;
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, Command};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::Mutex;
 
-/// Transport layer for MCP communication
-#[async_trait]
-pub trait Transport: Send + Sync {
-    /// Send a message to the server
-    async fn send(&self, message: Value) -> Result<()>;
-
-    /// Receive a message from the server
-    async fn receive(&self) -> Result<Value>;
-
-    /// Close the connection
-    async fn close(&mut self) -> Result<()>;
-
-    /// Check if the connection is alive
-    fn is_alive(&self) -> bool;
-}
-
 /// Stdio-based transport (spawns a process)
+///
+/// Messages are newline-delimited JSON. Writing and reading use separate
+/// locks so a pending read never blocks outgoing requests.
 pub struct StdioTransport {
     process: Mutex<Option<Child>>,
+    stdin: Mutex<Option<ChildStdin>>,
+    stdout: Mutex<Option<BufReader<ChildStdout>>>,
     command: String,
     args: Vec<String>,
 }
@@ -34,6 +24,8 @@ impl StdioTransport {
     pub fn new(command: String, args: Vec<String>) -> Self {
         Self {
             process: Mutex::new(None),
+            stdin: Mutex::new(None),
+            stdout: Mutex::new(None),
             command,
             args,
         }
@@ -47,14 +39,19 @@ impl StdioTransport {
             return Ok(()); // Already started
         }
 
-        let child = Command::new(&self.command)
+        let mut child = Command::new(&self.command)
             .args(&self.args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| MCPError::TransportError(format!("Failed to spawn process: {}", e)))?;
 
+        // Keep one reader for the life of the process so buffered lines
+        // are never lost between reads
+        *self.stdin.lock().await = child.stdin.take();
+        *self.stdout.lock().await = child.stdout.take().map(BufReader::new);
         *process_guard = Some(child);
         Ok(())
     }
@@ -63,30 +60,21 @@ impl StdioTransport {
 #[async_trait]
 impl Transport for StdioTransport {
     async fn send(&self, message: Value) -> Result<()> {
-        let mut process_guard = self.process.lock().await;
+        let mut stdin_guard = self.stdin.lock().await;
 
-        let process = process_guard
+        let stdin = stdin_guard
             .as_mut()
             .ok_or_else(|| MCPError::TransportError("Process not started".to_string()))?;
 
-        let stdin = process
-            .stdin
-            .as_mut()
-            .ok_or_else(|| MCPError::TransportError("No stdin available".to_string()))?;
-
-        let message_str = serde_json::to_string(&message)
+        let mut message_str = serde_json::to_string(&message)
             .map_err(|e| MCPError::ProtocolError(format!("Failed to serialize message: {}", e)))?;
+        message_str.push('\n');
 
         stdin
             .write_all(message_str.as_bytes())
             .await
             .map_err(|e| MCPError::TransportError(format!("Failed to write to stdin: {}", e)))?;
 
-        stdin
-            .write_all(b"\n")
-            .await
-            .map_err(|e| MCPError::TransportError(format!("Failed to write newline: {}", e)))?;
-
         stdin
             .flush()
             .await
@@ -96,36 +84,37 @@ impl Transport for StdioTransport {
     }
 
     async fn receive(&self) -> Result<Value> {
-        let mut process_guard = self.process.lock().await;
+        let mut stdout_guard = self.stdout.lock().await;
 
-        let process = process_guard
+        let reader = stdout_guard
             .as_mut()
             .ok_or_else(|| MCPError::TransportError("Process not started".to_string()))?;
 
-        let stdout = process
-            .stdout
-            .as_mut()
-            .ok_or_else(|| MCPError::TransportError("No stdout available".to_string()))?;
-
-        let mut reader = BufReader::new(stdout);
         let mut line = String::new();
-
-        reader
-            .read_line(&mut line)
-            .await
-            .map_err(|e| MCPError::TransportError(format!("Failed to read from stdout: {}", e)))?;
-
-        if line.is_empty() {
-            return Err(MCPError::TransportError(
-                "Connection closed by server".to_string(),
-            ));
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line).await.map_err(|e| {
+                MCPError::TransportError(format!("Failed to read from stdout: {}", e))
+            })?;
+
+            if read == 0 {
+                return Err(MCPError::TransportError(
+                    "Connection closed by server".to_string(),
+                ));
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
         }
 
         serde_json::from_str(&line)
             .map_err(|e| MCPError::ProtocolError(format!("Failed to parse message: {}", e)))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&self) -> Result<()> {
+        // Closing stdin lets well-behaved servers exit on their own
+        self.stdin.lock().await.take();
+
         let mut process_guard = self.process.lock().await;
 
         if let Some(mut child) = process_guard.take() {
@@ -172,4 +161,22 @@ mod tests {
         let result = transport.send(message).await;
         assert!(result.is_err());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_transport_keeps_buffered_lines() {
+        // Both messages arrive in a single write; neither may be lost
+        let transport = StdioTransport::new(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                r#"printf '{"id":1}\n\n{"id":2}\n'"#.to_string(),
+            ],
+        );
+        transport.start().await.unwrap();
+
+        assert_eq!(transport.receive().await.unwrap()["id"], 1);
+        assert_eq!(transport.receive().await.unwrap()["id"], 2);
+        assert!(transport.receive().await.is_err());
+    }
 }
@@ -0,0 +1,174 @@
+use super::Transport;
+use crate::mcp::{AuthConfig, MCPError, Result};
+use async_trait::async_trait;
+use futures_util::stream::{SplitSink, StreamExt};
+use futures_util::SinkExt;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::AUTHORIZATION;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// How often a keepalive ping is sent to the server
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+
+/// WebSocket transport carrying one JSON-RPC message per text frame
+pub struct WebSocketTransport {
+    sink: Arc<Mutex<WsSink>>,
+    receiver: Mutex<mpsc::UnboundedReceiver<Result<Value>>>,
+    alive: Arc<AtomicBool>,
+}
+
+impl WebSocketTransport {
+    /// Connect to the MCP server at `url`, sending `auth` on the upgrade request
+    pub async fn connect(url: &str, auth: Option<&AuthConfig>) -> Result<Self> {
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| MCPError::ConfigError(format!("Invalid WebSocket URL: {}", e)))?;
+
+        if let Some(auth) = auth {
+            let value = auth.header_value().map_err(MCPError::ConfigError)?;
+            let value = HeaderValue::from_str(&value)
+                .map_err(|e| MCPError::ConfigError(format!("Invalid credentials: {}", e)))?;
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+
+        // Both rustls backends are linked in, so one has to be picked explicitly
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let (stream, _response) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| MCPError::TransportError(format!("WebSocket handshake failed: {}", e)))?;
+
+        let (sink, mut stream) = stream.split();
+        let sink = Arc::new(Mutex::new(sink));
+        let alive = Arc::new(AtomicBool::new(true));
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        // Reader: forward JSON-RPC frames until the server closes the socket
+        {
+            let alive = Arc::clone(&alive);
+            tokio::spawn(async move {
+                while let Some(frame) = stream.next().await {
+                    let message = match frame {
+                        Ok(Message::Text(text)) => Self::parse(text.as_str()),
+                        Ok(Message::Binary(data)) => match std::str::from_utf8(&data) {
+                            Ok(text) => Self::parse(text),
+                            Err(_) => continue,
+                        },
+                        Ok(Message::Close(frame)) => {
+                            let reason = frame
+                                .map(|f| format!("{} {}", u16::from(f.code), f.reason))
+                                .unwrap_or_else(|| "no reason given".to_string());
+                            Err(MCPError::TransportError(format!(
+                                "Connection closed by server ({})",
+                                reason.trim()
+                            )))
+                        }
+                        // Pings are answered automatically by tungstenite
+                        Ok(Message::Ping(_)) | Ok(Message::Pong(_)) | Ok(Message::Frame(_)) => {
+                            continue
+                        }
+                        Err(e) => Err(MCPError::TransportError(format!(
+                            "WebSocket read failed: {}",
+                            e
+                        ))),
+                    };
+
+                    let closing = message.is_err();
+                    if closing {
+                        alive.store(false, Ordering::SeqCst);
+                    }
+                    if sender.send(message).is_err() || closing {
+                        break;
+                    }
+                }
+                alive.store(false, Ordering::SeqCst);
+            });
+        }
+
+        // Keepalive: ping periodically so idle connections aren't dropped
+        {
+            let sink = Arc::clone(&sink);
+            let alive = Arc::clone(&alive);
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(PING_INTERVAL);
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    if !alive.load(Ordering::SeqCst)
+                        || sink
+                            .lock()
+                            .await
+                            .send(Message::Ping(Default::default()))
+                            .await
+                            .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            sink,
+            receiver: Mutex::new(receiver),
+            alive,
+        })
+    }
+
+    fn parse(text: &str) -> Result<Value> {
+        serde_json::from_str(text)
+            .map_err(|e| MCPError::ProtocolError(format!("Failed to parse message: {}", e)))
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    async fn send(&self, message: Value) -> Result<()> {
+        if !self.is_alive() {
+            return Err(MCPError::TransportError(
+                "WebSocket connection is closed".to_string(),
+            ));
+        }
+
+        let text = serde_json::to_string(&message)
+            .map_err(|e| MCPError::ProtocolError(format!("Failed to serialize message: {}", e)))?;
+
+        self.sink
+            .lock()
+            .await
+            .send(Message::Text(text.into()))
+            .await
+            .map_err(|e| MCPError::TransportError(format!("WebSocket write failed: {}", e)))
+    }
+
+    async fn receive(&self) -> Result<Value> {
+        self.receiver.lock().await.recv().await.unwrap_or_else(|| {
+            Err(MCPError::TransportError(
+                "Connection closed by server".to_string(),
+            ))
+        })
+    }
+
+    async fn close(&self) -> Result<()> {
+        if self.alive.swap(false, Ordering::SeqCst) {
+            let mut sink = self.sink.lock().await;
+            let _ = sink.send(Message::Close(None)).await;
+            let _ = sink.close().await;
+        }
+        Ok(())
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+}
@@ -737,6 +737,8 @@ impl HtmlFormatter {
             <div class="error-meta">
                 <div class="meta-item"><strong>First seen:</strong> {}</div>
                 <div class="meta-item"><strong>Last seen:</strong> {}</div>
+                <div class="meta-item"><strong>ID:</strong> {}</div>
+                {}
                 {}
             </div>
             <div style="display: flex; gap: 10px; margin-top: 15px;">
@@ -762,6 +764,7 @@ impl HtmlFormatter {
             if group.count == 1 { "" } else { "s" },
             first_seen,
             last_seen,
+            self.escape_html(&group.id),
             group
                 .entries
                 .first()
@@ -771,6 +774,14 @@ impl HtmlFormatter {
                     self.escape_html(f)
                 ))
                 .unwrap_or_default(),
+            if group.sources.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    r#"<div class="meta-item"><strong>Sources:</strong> {}</div>"#,
+                    self.escape_html(&group.sources.join(", "))
+                )
+            },
             idx,
             idx,
             idx,
@@ -118,6 +118,15 @@ impl OutputFormatter for TerminalFormatter {
                 Self::format_time_ago(&group.last_seen).bright_black()
             ));
 
+            output.push_str(&format!("   ID: {}\n", group.id.bright_black()));
+
+            if !group.sources.is_empty() {
+                output.push_str(&format!(
+                    "   Sources: {}\n",
+                    group.sources.join(", ").bright_black()
+                ));
+            }
+
             // Show first entry as example
             if let Some(entry) = group.entries.first() {
                 output.push_str(&format!("\n   {}\n", "📋 Example:".bold()));
@@ -1,8 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Configuration for log parser behavior
+/// Names accepted by `--log-format` that custom formats cannot reuse
+pub const BUILTIN_FORMATS: &[&str] = &[
+    "auto", "json", "logfmt", "apache", "nginx", "syslog", "plain", "ecs", "bunyan", "pino",
+    "clef", "gcp", "cri", "docker", "journald",
+];
+
+/// Configuration for log parser behavior.
+///
+/// Read from the `[parser]` section of `~/.logai/config.toml`, or from a file
+/// given with `--parser-config`; missing keys take their default values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParserConfig {
     // Format detection
     /// Automatically detect log format from content
@@ -29,9 +39,10 @@ pub struct ParserConfig {
     pub streaming_threshold_mb: usize,
 
     // Timestamp parsing
-    /// Custom timestamp format patterns
+    /// Custom chrono timestamp formats (e.g. `%d/%m/%Y %H:%M:%S`), tried when a
+    /// parser finds no timestamp
     pub custom_timestamp_formats: Vec<String>,
-    /// Use current time as fallback when timestamp parsing fails
+    /// Use current time for entries whose timestamp cannot be parsed
     pub fallback_to_current_time: bool,
 
     // Metadata extraction
@@ -47,6 +58,43 @@ pub struct ParserConfig {
     pub max_parse_errors: Option<usize>,
     /// Attempt UTF-8 recovery for encoding errors
     pub utf8_recovery: bool,
+
+    // User-defined formats
+    /// Named formats selectable with `--log-format <name>` and tried first by
+    /// auto-detection
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub formats: Vec<FormatDefinition>,
+}
+
+/// A user-defined line format (a `[[formats]]` table).
+///
+/// Exactly one of `pattern` (a regex with named captures), `grok` or `json`
+/// must be set. Captures named `timestamp`/`ts`/`time`, `level`/`severity`,
+/// `message`/`msg`, `file`, `line`, `function`/`func` and `thread` fill the
+/// matching entry fields; any other capture is kept in the entry's metadata.
+/// For JSON formats the `fields` keys are dotted paths into each object.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatDefinition {
+    /// Name used with `--log-format`
+    pub name: String,
+    /// Regular expression with named captures
+    pub pattern: Option<String>,
+    /// Grok pattern such as `%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{GREEDYDATA:message}`
+    pub grok: Option<String>,
+    /// Parse lines as JSON objects, starting from `preset` when set
+    pub json: bool,
+    /// JSON field mapping preset (ecs, bunyan, pino, clef, gcp); implies `json`
+    pub preset: Option<String>,
+    /// Extra grok patterns available to `grok` (name -> pattern)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub patterns: HashMap<String, String>,
+    /// Chrono format of the timestamp capture (RFC 3339 and similar when unset)
+    pub timestamp_format: Option<String>,
+    /// Entry field for captures or JSON paths whose name differs from it
+    /// (capture or path -> field)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, String>,
 }
 
 impl Default for ParserConfig {
@@ -62,12 +110,13 @@ impl Default for ParserConfig {
             num_threads: None,
             streaming_threshold_mb: 100,
             custom_timestamp_formats: vec![],
-            fallback_to_current_time: true,
+            fallback_to_current_time: false,
             extract_metadata: true,
             custom_metadata_patterns: HashMap::new(),
             skip_invalid_lines: true,
             max_parse_errors: None,
             utf8_recovery: true,
+            formats: vec![],
         }
     }
 }
@@ -107,9 +156,32 @@ impl ParserConfig {
                 "streaming_threshold_mb must be greater than 0"
             ));
         }
+        for format in &self.formats {
+            if BUILTIN_FORMATS.contains(&format.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Format name '{}' is reserved for a built-in format",
+                    format.name
+                ));
+            }
+            super::formats::CustomParser::new(format)?;
+        }
         Ok(())
     }
 
+    /// Number of unparseable lines tolerated per input (`None` = unlimited)
+    pub fn error_budget(&self) -> Option<usize> {
+        if self.skip_invalid_lines {
+            self.max_parse_errors
+        } else {
+            Some(0)
+        }
+    }
+
+    /// Custom format called `name`, if any
+    pub fn format(&self, name: &str) -> Option<&FormatDefinition> {
+        self.formats.iter().find(|format| format.name == name)
+    }
+
     /// Get streaming threshold in bytes
     pub fn streaming_threshold_bytes(&self) -> u64 {
         (self.streaming_threshold_mb as u64) * 1024 * 1024
@@ -151,4 +223,20 @@ mod tests {
         assert!(!config.parallel_parsing);
         assert!(!config.enable_multiline);
     }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config: ParserConfig =
+            toml::from_str("chunk_size = 250\nmax_parse_errors = 3").unwrap();
+        assert_eq!(config.chunk_size, 250);
+        assert!(config.enable_multiline);
+        assert_eq!(config.error_budget(), Some(3));
+
+        let strict = ParserConfig {
+            skip_invalid_lines: false,
+            ..Default::default()
+        };
+        assert_eq!(strict.error_budget(), Some(0));
+        assert_eq!(ParserConfig::default().error_budget(), None);
+    }
 }
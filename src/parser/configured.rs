@@ -0,0 +1,222 @@
+use super::{LogParser, MetadataExtractor, ParserConfig, TimestampParser};
+use crate::types::LogEntry;
+use crate::Result;
+use chrono::Utc;
+use std::sync::Arc;
+
+/// Wrapper parser that applies a `ParserConfig` to the entries of another parser.
+///
+/// Fills in timestamps from `custom_timestamp_formats` (or the current time when
+/// `fallback_to_current_time` is set), extracts metadata with the built-in and
+/// `custom_metadata_patterns`, and caps multi-line entries at
+/// `max_multiline_lines` lines.
+pub struct ConfiguredParser {
+    inner: Arc<dyn LogParser>,
+    timestamps: TimestampParser,
+    metadata: Option<MetadataExtractor>,
+    fallback_to_current_time: bool,
+    enable_multiline: bool,
+    max_multiline_lines: usize,
+}
+
+impl ConfiguredParser {
+    /// Create a parser applying `config` to the entries of `inner`
+    pub fn new(inner: Arc<dyn LogParser>, config: &ParserConfig) -> Self {
+        Self {
+            inner,
+            timestamps: TimestampParser::with_format_strings(&config.custom_timestamp_formats),
+            metadata: config.extract_metadata.then(|| {
+                MetadataExtractor::with_custom_patterns(config.custom_metadata_patterns.clone())
+            }),
+            fallback_to_current_time: config.fallback_to_current_time,
+            enable_multiline: config.enable_multiline,
+            max_multiline_lines: config.max_multiline_lines.max(1),
+        }
+    }
+
+    fn finish(&self, mut entry: LogEntry) -> LogEntry {
+        // Keep the first lines of oversized entries (e.g. deep recursion traces)
+        let limit = self.max_multiline_lines;
+        for text in [&mut entry.message, &mut entry.raw] {
+            if let Some((end, _)) = text.match_indices('\n').nth(limit - 1) {
+                text.truncate(end);
+            }
+        }
+
+        if entry.timestamp.is_none() {
+            if self.timestamps.has_custom_formats() {
+                entry.timestamp = self.timestamps.find(&entry.raw);
+            }
+            if entry.timestamp.is_none() && self.fallback_to_current_time {
+                entry.timestamp = Some(Utc::now());
+            }
+        }
+
+        if let Some(extractor) = &self.metadata {
+            let found = if entry.message.contains('\n') {
+                extractor.extract_from_stack_trace(&entry.message)
+            } else {
+                extractor.extract(&entry.message)
+            };
+
+            // Values set by the format parser take precedence
+            let metadata = &mut entry.metadata;
+            for (key, value) in found {
+                match key.as_str() {
+                    "file" => {
+                        metadata.file.get_or_insert(value);
+                    }
+                    "line" => {
+                        if metadata.line.is_none() {
+                            metadata.line = value.parse().ok();
+                        }
+                    }
+                    "function" => {
+                        metadata.function.get_or_insert(value);
+                    }
+                    "thread" => {
+                        metadata.thread.get_or_insert(value);
+                    }
+                    _ => {
+                        metadata.extra.entry(key).or_insert(value);
+                    }
+                }
+            }
+        }
+
+        entry
+    }
+}
+
+impl LogParser for ConfiguredParser {
+    fn parse_line(&self, line: &str) -> Result<Option<LogEntry>> {
+        Ok(self.inner.parse_line(line)?.map(|entry| self.finish(entry)))
+    }
+
+    fn parse_lines(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
+        Ok(self
+            .inner
+            .parse_lines(lines)?
+            .into_iter()
+            .map(|entry| self.finish(entry))
+            .collect())
+    }
+
+    fn can_parse(&self, sample: &str) -> bool {
+        self.inner.can_parse(sample)
+    }
+
+    fn supports_multiline(&self) -> bool {
+        self.enable_multiline && self.inner.supports_multiline()
+    }
+
+    fn is_continuation_line(&self, line: &str) -> bool {
+        self.inner.is_continuation_line(line)
+    }
+
+    fn continues_on_next_line(&self, line: &str) -> bool {
+        self.inner.continues_on_next_line(line)
+    }
+
+    fn max_entry_lines(&self) -> Option<usize> {
+        Some(self.max_multiline_lines)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::formats::PlainTextParser;
+    use crate::parser::StackTraceParser;
+    use chrono::Datelike;
+    use std::collections::HashMap;
+
+    fn parser(config: ParserConfig) -> ConfiguredParser {
+        ConfiguredParser::new(
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new()))),
+            &config,
+        )
+    }
+
+    #[test]
+    fn test_custom_timestamp_and_fallback() {
+        let config = ParserConfig {
+            custom_timestamp_formats: vec!["%d/%m/%Y %H:%M:%S".to_string()],
+            ..Default::default()
+        };
+        let entry = parser(config)
+            .parse_line("17/11/2025 10:30:00 ERROR Payment failed")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.timestamp.unwrap().day(), 17);
+
+        let entry = parser(ParserConfig::default())
+            .parse_line("ERROR Payment failed")
+            .unwrap()
+            .unwrap();
+        assert!(entry.timestamp.is_none());
+
+        let config = ParserConfig {
+            fallback_to_current_time: true,
+            ..Default::default()
+        };
+        let entry = parser(config)
+            .parse_line("ERROR Payment failed")
+            .unwrap()
+            .unwrap();
+        assert!(entry.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_metadata_extraction() {
+        let mut patterns = HashMap::new();
+        patterns.insert("tenant".to_string(), r"tenant=(\w+)".to_string());
+        let config = ParserConfig {
+            custom_metadata_patterns: patterns,
+            ..Default::default()
+        };
+
+        let entry = parser(config)
+            .parse_line("ERROR tenant=acme request_id=abc-123 failed at Service.java:42")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.metadata.file.as_deref(), Some("Service.java"));
+        assert_eq!(entry.metadata.line, Some(42));
+        assert_eq!(entry.metadata.extra["tenant"], "acme");
+        assert_eq!(entry.metadata.extra["request_id"], "abc-123");
+
+        let config = ParserConfig {
+            extract_metadata: false,
+            ..Default::default()
+        };
+        let entry = parser(config)
+            .parse_line("ERROR failed at Service.java:42")
+            .unwrap()
+            .unwrap();
+        assert!(entry.metadata.file.is_none());
+    }
+
+    #[test]
+    fn test_multiline_cap() {
+        let mut lines = vec!["Error: deep recursion".to_string()];
+        lines.extend((0..50).map(|i| format!("    at frame{} (app.js:{}:1)", i, i)));
+
+        let config = ParserConfig {
+            max_multiline_lines: 10,
+            ..Default::default()
+        };
+        let entries = parser(config).parse_lines(&lines).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message.lines().count(), 10);
+
+        let config = ParserConfig {
+            enable_multiline: false,
+            ..Default::default()
+        };
+        assert!(!parser(config).supports_multiline());
+    }
+}
@@ -1,6 +1,9 @@
 use super::{
-    formats::{ApacheParser, JsonParser, NginxParser, PlainTextParser, SyslogParser},
-    LogParser, StackTraceParser,
+    formats::{
+        ApacheParser, ContainerParser, ContainerRuntime, CustomParser, JournaldParser, JsonParser,
+        LogfmtParser, NginxParser, PlainTextParser, SyslogParser,
+    },
+    FormatDefinition, LogParser, StackTraceParser,
 };
 use std::sync::Arc;
 
@@ -10,12 +13,29 @@ impl FormatDetector {
     /// Detect log format from a sample line and return appropriate parser
     /// Parsers are tried in order of specificity (most specific first)
     pub fn detect(sample: &str) -> Arc<dyn LogParser> {
+        // Container runtime envelopes wrap the other formats
+        if let Some(parser) = Self::detect_container(sample, None) {
+            return parser;
+        }
+
+        // journald JSON is JSON too, so check for its fields first
+        let journald_parser = JournaldParser::new();
+        if journald_parser.can_parse(sample) {
+            return Arc::new(journald_parser);
+        }
+
         // Try JSON first (most specific)
         let json_parser = JsonParser::new();
         if json_parser.can_parse(sample) {
             return Arc::new(StackTraceParser::new(Arc::new(json_parser)));
         }
 
+        // Try logfmt (key=value pairs)
+        let logfmt_parser = LogfmtParser::new();
+        if logfmt_parser.can_parse(sample) {
+            return Arc::new(StackTraceParser::new(Arc::new(logfmt_parser)));
+        }
+
         // Try Apache format
         let apache_parser = ApacheParser::new();
         if apache_parser.can_parse(sample) {
@@ -38,13 +58,62 @@ impl FormatDetector {
         Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new())))
     }
 
+    /// Container runtime parser for `sample`, taking pod and container
+    /// metadata from the `source` path when given
+    fn detect_container(sample: &str, source: Option<&str>) -> Option<Arc<dyn LogParser>> {
+        [ContainerRuntime::Docker, ContainerRuntime::Cri]
+            .into_iter()
+            .map(ContainerParser::new)
+            .find(|parser| parser.can_parse(sample))
+            .map(|parser| match source {
+                Some(path) => Arc::new(parser.with_source_path(path)) as Arc<dyn LogParser>,
+                None => Arc::new(parser),
+            })
+    }
+
+    /// Detect log format, trying user-defined `formats` before the built-in
+    /// ones; `source` is the path the sample was read from, if any
+    pub fn detect_with_formats(
+        sample: &str,
+        formats: &[FormatDefinition],
+        source: Option<&str>,
+    ) -> Arc<dyn LogParser> {
+        for definition in formats {
+            // Invalid definitions are reported by `ParserConfig::validate`
+            if let Ok(parser) = CustomParser::new(definition) {
+                if parser.can_parse(sample) {
+                    return Arc::new(StackTraceParser::new(Arc::new(parser)));
+                }
+            }
+        }
+
+        Self::detect_container(sample, source).unwrap_or_else(|| Self::detect(sample))
+    }
+
     /// Detect format with confidence scoring
     pub fn detect_with_confidence(sample: &str) -> (Arc<dyn LogParser>, f32) {
+        if let Some(parser) = Self::detect_container(sample, None) {
+            return (parser, 0.95);
+        }
+
+        let journald_parser = JournaldParser::new();
+        if journald_parser.can_parse(sample) {
+            return (Arc::new(journald_parser), 0.95);
+        }
+
         let json_parser = JsonParser::new();
         if json_parser.can_parse(sample) {
             return (Arc::new(StackTraceParser::new(Arc::new(json_parser))), 0.95);
         }
 
+        let logfmt_parser = LogfmtParser::new();
+        if logfmt_parser.can_parse(sample) {
+            return (
+                Arc::new(StackTraceParser::new(Arc::new(logfmt_parser))),
+                0.90,
+            );
+        }
+
         let apache_parser = ApacheParser::new();
         if apache_parser.can_parse(sample) {
             return (Arc::new(apache_parser), 0.90);
@@ -73,21 +142,39 @@ impl FormatDetector {
             return Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new())));
         }
 
+        // Container envelopes wrap the other formats, so they win when most samples use one
+        for runtime in [ContainerRuntime::Docker, ContainerRuntime::Cri] {
+            let parser = ContainerParser::new(runtime);
+            if samples.iter().filter(|s| parser.can_parse(s)).count() * 2 > samples.len() {
+                return Arc::new(parser);
+            }
+        }
+
         // Try each parser and count matches
+        let mut journald_matches = 0;
         let mut json_matches = 0;
+        let mut logfmt_matches = 0;
         let mut apache_matches = 0;
         let mut nginx_matches = 0;
         let mut syslog_matches = 0;
 
+        let journald_parser = JournaldParser::new();
         let json_parser = JsonParser::new();
+        let logfmt_parser = LogfmtParser::new();
         let apache_parser = ApacheParser::new();
         let nginx_parser = NginxParser::new();
         let syslog_parser = SyslogParser::new();
 
         for sample in samples {
+            if journald_parser.can_parse(sample) {
+                journald_matches += 1;
+            }
             if json_parser.can_parse(sample) {
                 json_matches += 1;
             }
+            if logfmt_parser.can_parse(sample) {
+                logfmt_matches += 1;
+            }
             if apache_parser.can_parse(sample) {
                 apache_matches += 1;
             }
@@ -100,15 +187,23 @@ impl FormatDetector {
         }
 
         // Return parser with most matches
-        let max_matches = json_matches
+        let max_matches = journald_matches
+            .max(json_matches)
+            .max(logfmt_matches)
             .max(apache_matches)
             .max(nginx_matches)
             .max(syslog_matches);
 
         if max_matches > 0 {
+            if journald_matches == max_matches {
+                return Arc::new(journald_parser);
+            }
             if json_matches == max_matches {
                 return Arc::new(StackTraceParser::new(Arc::new(json_parser)));
             }
+            if logfmt_matches == max_matches {
+                return Arc::new(StackTraceParser::new(Arc::new(logfmt_parser)));
+            }
             if apache_matches == max_matches {
                 return Arc::new(apache_parser);
             }
@@ -159,6 +254,41 @@ mod tests {
         assert!(parser.can_parse(sample));
     }
 
+    #[test]
+    fn test_detect_logfmt() {
+        let sample = r#"ts=2025-11-17T10:30:00Z level=error msg="payment failed""#;
+        assert_eq!(FormatDetector::detect(sample).name(), "logfmt");
+
+        let samples = vec![sample, "level=info msg=started", "random line"];
+        assert_eq!(
+            FormatDetector::detect_from_samples(&samples).name(),
+            "logfmt"
+        );
+    }
+
+    #[test]
+    fn test_detect_container_formats() {
+        let docker = r#"{"log":"ERROR boom\n","stream":"stderr","time":"2025-11-17T10:30:00Z"}"#;
+        assert_eq!(FormatDetector::detect(docker).name(), "docker");
+
+        let cri = "2025-11-17T10:30:00.123456789Z stdout F ERROR boom";
+        assert_eq!(FormatDetector::detect(cri).name(), "cri");
+    }
+
+    #[test]
+    fn test_detect_journald() {
+        let json = r#"{"__REALTIME_TIMESTAMP":"1700000000000000","PRIORITY":"3","MESSAGE":"boom"}"#;
+        assert_eq!(FormatDetector::detect(json).name(), "journald");
+        assert_eq!(
+            FormatDetector::detect("__CURSOR=s=1;i=1").name(),
+            "journald"
+        );
+
+        // Ordinary JSON logs stay with the JSON parser
+        let app = r#"{"level":"error","message":"boom"}"#;
+        assert_ne!(FormatDetector::detect(app).name(), "journald");
+    }
+
     #[test]
     fn test_detect_plain() {
         let sample = "2025-11-17 ERROR Something went wrong";
@@ -166,6 +296,24 @@ mod tests {
         assert!(parser.can_parse(sample));
     }
 
+    #[test]
+    fn test_detect_custom_format() {
+        let formats = vec![FormatDefinition {
+            name: "inhouse".to_string(),
+            grok: Some(
+                "%{TIMESTAMP_ISO8601:ts} \\|%{LOGLEVEL:level}\\| %{GREEDYDATA:message}".to_string(),
+            ),
+            ..Default::default()
+        }];
+
+        let parser =
+            FormatDetector::detect_with_formats("2025-11-17 10:30:00 |ERROR| boom", &formats, None);
+        assert_eq!(parser.name(), "inhouse");
+
+        let parser = FormatDetector::detect_with_formats("2025-11-17 ERROR boom", &formats, None);
+        assert_eq!(parser.name(), "plain");
+    }
+
     #[test]
     fn test_detect_with_confidence() {
         let json_sample = r#"{"level":"error","message":"test"}"#;
@@ -128,6 +128,7 @@ impl LogParser for ApacheParser {
                 message: format!("{} {}", status, request),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -168,6 +169,7 @@ impl LogParser for ApacheParser {
                 message: format!("{} {}", status, request),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -178,6 +180,10 @@ impl LogParser for ApacheParser {
     fn can_parse(&self, sample: &str) -> bool {
         self.common_pattern.is_match(sample) || self.combined_pattern.is_match(sample)
     }
+
+    fn name(&self) -> &str {
+        "apache"
+    }
 }
 
 #[cfg(test)]
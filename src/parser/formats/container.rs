@@ -0,0 +1,394 @@
+use crate::parser::{detector::FormatDetector, LogParser};
+use crate::types::LogEntry;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Container runtime log file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    /// CRI (containerd, CRI-O): `<ts> stdout F <msg>`
+    Cri,
+    /// Docker json-file driver: `{"log":"...","stream":"stderr","time":"..."}`
+    Docker,
+}
+
+/// One line of a container log, before the application payload is parsed
+struct Record {
+    timestamp: Option<DateTime<Utc>>,
+    stream: String,
+    payload: String,
+    /// The runtime split a long line and the payload continues on the next record
+    partial: bool,
+}
+
+/// Parser for container runtime logs.
+///
+/// Unwraps the runtime's envelope, joins lines the runtime split, and parses
+/// the payload with a parser detected from the first payload, so JSON or
+/// logfmt application logs inside the envelope keep their fields. The stream
+/// and the pod, namespace and container (when the file path shows them) are
+/// added to the entry's metadata.
+pub struct ContainerParser {
+    runtime: ContainerRuntime,
+    cri_pattern: Regex,
+    inner: OnceLock<Arc<dyn LogParser>>,
+    source: HashMap<String, String>,
+}
+
+impl ContainerParser {
+    /// Create a parser for `runtime` logs
+    pub fn new(runtime: ContainerRuntime) -> Self {
+        Self {
+            runtime,
+            cri_pattern: Regex::new(r"^(\S+) (stdout|stderr) ([FP])(?: (.*))?$").unwrap(),
+            inner: OnceLock::new(),
+            source: HashMap::new(),
+        }
+    }
+
+    /// Add the pod, namespace and container named by `path` to every entry
+    pub fn with_source_path(mut self, path: &str) -> Self {
+        self.source = Self::source_metadata(Path::new(path));
+        self
+    }
+
+    /// Kubernetes and Docker metadata encoded in a container log path:
+    ///
+    /// - `/var/log/containers/<pod>_<namespace>_<container>-<id>.log`
+    /// - `/var/log/pods/<namespace>_<pod>_<uid>/<container>/<n>.log`
+    /// - `/var/lib/docker/containers/<id>/<id>-json.log`
+    pub fn source_metadata(path: &Path) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        // Name of the path's nth ancestor (0 is the file itself)
+        let name = |n: usize| {
+            path.ancestors()
+                .nth(n)
+                .and_then(Path::file_name)
+                .and_then(|n| n.to_str())
+        };
+        let file_name = name(0).unwrap_or("");
+
+        if let Some(id) = file_name.strip_suffix("-json.log") {
+            metadata.insert("container_id".to_string(), id.to_string());
+        } else if name(3) == Some("pods") {
+            if let (Some(container), Some(pod_dir)) = (name(1), name(2)) {
+                let mut parts = pod_dir.splitn(3, '_');
+                if let (Some(namespace), Some(pod)) = (parts.next(), parts.next()) {
+                    metadata.insert("namespace".to_string(), namespace.to_string());
+                    metadata.insert("pod".to_string(), pod.to_string());
+                    metadata.insert("container".to_string(), container.to_string());
+                }
+            }
+        } else if name(1) == Some("containers") {
+            let stem = file_name.strip_suffix(".log").unwrap_or(file_name);
+            let mut parts = stem.splitn(3, '_');
+            if let (Some(pod), Some(namespace), Some(rest)) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                // <container>-<64 hex id>
+                let container = match rest.rsplit_once('-') {
+                    Some((container, id)) if id.len() == 64 => {
+                        metadata.insert("container_id".to_string(), id.to_string());
+                        container
+                    }
+                    _ => rest,
+                };
+                metadata.insert("pod".to_string(), pod.to_string());
+                metadata.insert("namespace".to_string(), namespace.to_string());
+                metadata.insert("container".to_string(), container.to_string());
+            }
+        }
+
+        metadata
+    }
+
+    fn record(&self, line: &str) -> Option<Record> {
+        match self.runtime {
+            ContainerRuntime::Cri => {
+                let caps = self
+                    .cri_pattern
+                    .captures(line.trim_end_matches(['\r', '\n']))?;
+                Some(Record {
+                    timestamp: DateTime::parse_from_rfc3339(&caps[1])
+                        .ok()
+                        .map(|dt| dt.with_timezone(&Utc)),
+                    stream: caps[2].to_string(),
+                    payload: caps.get(4).map_or("", |m| m.as_str()).to_string(),
+                    partial: &caps[3] == "P",
+                })
+            }
+            ContainerRuntime::Docker => {
+                let json: Value = serde_json::from_str(line.trim()).ok()?;
+                let log = json.get("log")?.as_str()?;
+                Some(Record {
+                    timestamp: json
+                        .get("time")
+                        .and_then(|t| t.as_str())
+                        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                        .map(|dt| dt.with_timezone(&Utc)),
+                    stream: json
+                        .get("stream")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("stdout")
+                        .to_string(),
+                    payload: log.trim_end_matches(['\r', '\n']).to_string(),
+                    // Docker splits lines over 16 KB; only the last piece ends in a newline
+                    partial: !log.ends_with('\n'),
+                })
+            }
+        }
+    }
+
+    /// Join records the runtime split back into whole lines
+    fn join_partial(records: impl IntoIterator<Item = Record>) -> Vec<Record> {
+        let mut joined: Vec<Record> = Vec::new();
+        let mut continues = false;
+        for record in records {
+            match joined.last_mut() {
+                Some(previous) if continues => {
+                    previous.payload.push_str(&record.payload);
+                    previous.partial = record.partial;
+                }
+                _ => joined.push(record),
+            }
+            continues = joined.last().is_some_and(|r| r.partial);
+        }
+        joined
+    }
+
+    fn inner(&self, payload: &str) -> &Arc<dyn LogParser> {
+        self.inner.get_or_init(|| FormatDetector::detect(payload))
+    }
+
+    fn finish(&self, mut entry: LogEntry, record: &Record) -> LogEntry {
+        if entry.timestamp.is_none() {
+            entry.timestamp = record.timestamp;
+        }
+        let extra = &mut entry.metadata.extra;
+        extra
+            .entry("stream".to_string())
+            .or_insert_with(|| record.stream.clone());
+        for (key, value) in &self.source {
+            extra.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        entry
+    }
+}
+
+impl LogParser for ContainerParser {
+    fn parse_line(&self, line: &str) -> Result<Option<LogEntry>> {
+        let Some(record) = self.record(line) else {
+            return Ok(None);
+        };
+        if record.payload.trim().is_empty() {
+            return Ok(None);
+        }
+        let entry = self.inner(&record.payload).parse_line(&record.payload)?;
+        Ok(entry.map(|entry| self.finish(entry, &record)))
+    }
+
+    fn parse_lines(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
+        let records = Self::join_partial(lines.iter().filter_map(|line| self.record(line)));
+        let Some(first) = records.iter().find(|r| !r.payload.trim().is_empty()) else {
+            return Ok(Vec::new());
+        };
+        let inner = Arc::clone(self.inner(&first.payload));
+
+        // Group each entry's first record with its continuation lines (stack
+        // frames), then parse the payloads as if they were the whole file
+        let mut entries = Vec::new();
+        let mut start = 0;
+        while start < records.len() {
+            let end = (start + 1..records.len())
+                .find(|&i| {
+                    let payload = &records[i].payload;
+                    !payload.trim().is_empty() && !inner.is_continuation_line(payload)
+                })
+                .unwrap_or(records.len());
+
+            let payloads: Vec<String> = records[start..end]
+                .iter()
+                .map(|r| r.payload.clone())
+                .collect();
+            let parsed = if inner.supports_multiline() {
+                inner.parse_lines(&payloads)?
+            } else {
+                let mut parsed = Vec::new();
+                for payload in &payloads {
+                    parsed.extend(inner.parse_line(payload)?);
+                }
+                parsed
+            };
+            entries.extend(
+                parsed
+                    .into_iter()
+                    .map(|entry| self.finish(entry, &records[start])),
+            );
+            start = end;
+        }
+
+        Ok(entries)
+    }
+
+    fn can_parse(&self, sample: &str) -> bool {
+        self.record(sample).is_some()
+    }
+
+    fn supports_multiline(&self) -> bool {
+        true
+    }
+
+    /// Blank payloads and the continuation lines of the payload format (e.g.
+    /// stack frames) continue an entry, as in `parse_lines`
+    fn is_continuation_line(&self, line: &str) -> bool {
+        let Some(record) = self.record(line) else {
+            return false;
+        };
+        let payload = &record.payload;
+        payload.trim().is_empty() || self.inner(payload).is_continuation_line(payload)
+    }
+
+    fn continues_on_next_line(&self, line: &str) -> bool {
+        self.record(line).is_some_and(|record| record.partial)
+    }
+
+    fn name(&self) -> &str {
+        match self.runtime {
+            ContainerRuntime::Cri => "cri",
+            ContainerRuntime::Docker => "docker",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Severity;
+
+    #[test]
+    fn test_parse_cri_with_partial_lines() {
+        let parser = ContainerParser::new(ContainerRuntime::Cri);
+        let lines: Vec<String> = [
+            "2025-11-17T10:30:00.123456789Z stdout F starting worker",
+            "2025-11-17T10:30:01.000000000Z stderr P 2025-11-17T10:30:01Z ERROR payment ",
+            "2025-11-17T10:30:01.000000001Z stderr F failed for order 42",
+            "2025-11-17T10:30:02.000000000Z stderr F java.lang.IllegalStateException: boom",
+            "2025-11-17T10:30:02.000000001Z stderr F     at com.example.Pay.charge(Pay.java:10)",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let entries = parser.parse_lines(&lines).unwrap();
+        // The trace printed after the error line belongs to it
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].severity, Severity::Error);
+        assert!(entries[1].message.contains("payment failed for order 42"));
+        assert_eq!(entries[1].metadata.extra["stream"], "stderr");
+        assert!(entries[1].message.contains("at com.example.Pay.charge"));
+        assert!(parser.can_parse(&lines[0]));
+        assert!(!parser.can_parse("plain text line"));
+    }
+
+    #[test]
+    fn test_parse_docker_json_with_inner_json() {
+        let parser = ContainerParser::new(ContainerRuntime::Docker);
+        let line = r#"{"log":"{\"level\":\"error\",\"message\":\"db down\",\"service\":\"api\"}\n","stream":"stderr","time":"2025-11-17T10:30:00.5Z"}"#;
+
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.message, "db down");
+        assert_eq!(entry.metadata.extra["service"], "api");
+        assert_eq!(entry.metadata.extra["stream"], "stderr");
+        assert!(entry.timestamp.is_some());
+
+        // Lines over 16 KB arrive in pieces without a trailing newline
+        let lines = vec![
+            r#"{"log":"ERROR first half ","stream":"stdout","time":"2025-11-17T10:30:01Z"}"#
+                .to_string(),
+            r#"{"log":"second half\n","stream":"stdout","time":"2025-11-17T10:30:01Z"}"#
+                .to_string(),
+        ];
+        let parser = ContainerParser::new(ContainerRuntime::Docker);
+        let entries = parser.parse_lines(&lines).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "ERROR first half second half");
+    }
+
+    #[test]
+    fn test_stream_never_cuts_split_records_or_traces() {
+        use crate::parser::EntryStream;
+
+        let lines: Vec<String> = [
+            "2025-11-17T10:30:00.000000000Z stdout F starting worker",
+            "2025-11-17T10:30:01.000000000Z stderr P 2025-11-17T10:30:01Z ERROR payment ",
+            "2025-11-17T10:30:01.000000001Z stderr F failed for order 42",
+            "2025-11-17T10:30:02.000000000Z stderr F java.lang.IllegalStateException: boom",
+            "2025-11-17T10:30:02.000000001Z stderr P     at com.example.Pay.",
+            "2025-11-17T10:30:02.000000002Z stderr F charge(Pay.java:10)",
+            "2025-11-17T10:30:02.000000003Z stderr F     at com.example.Main.main(Main.java:5)",
+            "2025-11-17T10:30:03.000000000Z stdout P request ",
+            "2025-11-17T10:30:03.000000001Z stdout P handled ",
+            "2025-11-17T10:30:03.000000002Z stdout F in 5ms",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let messages = |entries: Vec<LogEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.message).collect()
+        };
+
+        let expected = messages(
+            ContainerParser::new(ContainerRuntime::Cri)
+                .parse_lines(&lines)
+                .unwrap(),
+        );
+        assert_eq!(expected.len(), 3);
+        assert!(expected[1].contains("payment failed for order 42"));
+        assert!(expected[1].contains("at com.example.Pay.charge(Pay.java:10)"));
+        assert!(expected[1].contains("at com.example.Main.main"));
+        assert_eq!(expected[2], "request handled in 5ms");
+
+        for parallel_chunks in [1, 4] {
+            let stream = EntryStream::new(
+                Arc::new(ContainerParser::new(ContainerRuntime::Cri)),
+                lines.iter().cloned().map(Ok),
+                true,
+            )
+            .with_chunk_size(1)
+            .with_parallel_chunks(parallel_chunks);
+            let streamed = messages(stream.collect::<Result<Vec<_>>>().unwrap());
+            assert_eq!(streamed, expected, "{} parallel chunks", parallel_chunks);
+        }
+    }
+
+    #[test]
+    fn test_source_metadata_from_path() {
+        let metadata = ContainerParser::source_metadata(Path::new(&format!(
+            "/var/log/containers/api-7d9f_shop_server-{}.log",
+            "a".repeat(64)
+        )));
+        assert_eq!(metadata["pod"], "api-7d9f");
+        assert_eq!(metadata["namespace"], "shop");
+        assert_eq!(metadata["container"], "server");
+
+        let metadata = ContainerParser::source_metadata(Path::new(
+            "/var/log/pods/shop_api-7d9f_1234-5678/server/0.log",
+        ));
+        assert_eq!(metadata["namespace"], "shop");
+        assert_eq!(metadata["pod"], "api-7d9f");
+        assert_eq!(metadata["container"], "server");
+
+        let metadata = ContainerParser::source_metadata(Path::new(
+            "/var/lib/docker/containers/abc123/abc123-json.log",
+        ));
+        assert_eq!(metadata["container_id"], "abc123");
+
+        assert!(ContainerParser::source_metadata(Path::new("/tmp/app.log")).is_empty());
+    }
+}
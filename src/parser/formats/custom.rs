@@ -0,0 +1,346 @@
+use super::json::{JsonFieldMapping, JsonParser};
+use crate::parser::{grok, FormatDefinition, LogParser, TimestampParser};
+use crate::types::{LogEntry, LogMetadata, Severity};
+use crate::Result;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Entry field a capture fills
+enum Field {
+    Timestamp,
+    Severity,
+    Message,
+    File,
+    Line,
+    Function,
+    Thread,
+    Extra(String),
+}
+
+impl Field {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "timestamp" | "ts" | "time" => Field::Timestamp,
+            "level" | "severity" => Field::Severity,
+            "message" | "msg" => Field::Message,
+            "file" => Field::File,
+            "line" => Field::Line,
+            "function" | "func" => Field::Function,
+            "thread" => Field::Thread,
+            other => Field::Extra(other.to_string()),
+        }
+    }
+}
+
+/// How a custom format reads a line
+enum Matcher {
+    Pattern {
+        pattern: Regex,
+        fields: Vec<(String, Field)>,
+    },
+    Json(Box<JsonParser>),
+}
+
+/// Parser for a user-defined format from a `[[formats]]` table
+pub struct CustomParser {
+    name: String,
+    matcher: Matcher,
+    timestamps: Option<TimestampParser>,
+}
+
+impl CustomParser {
+    /// Compile `definition` into a parser
+    pub fn new(definition: &FormatDefinition) -> Result<Self> {
+        let json = definition.json || definition.preset.is_some();
+        let pattern = match (&definition.pattern, &definition.grok, json) {
+            (Some(pattern), None, false) => pattern.clone(),
+            (None, Some(grok), false) => grok::expand(grok, &definition.patterns)
+                .map_err(|e| anyhow::anyhow!("Format '{}': {}", definition.name, e))?,
+            (None, None, true) => {
+                return Ok(Self {
+                    name: definition.name.clone(),
+                    matcher: Matcher::Json(Box::new(Self::json_parser(definition)?)),
+                    timestamps: None,
+                })
+            }
+            _ => anyhow::bail!(
+                "Format '{}' must set exactly one of `pattern`, `grok` or `json`",
+                definition.name
+            ),
+        };
+        let pattern = Regex::new(&pattern)
+            .map_err(|e| anyhow::anyhow!("Format '{}': invalid pattern: {}", definition.name, e))?;
+
+        let fields = pattern
+            .capture_names()
+            .flatten()
+            .map(|capture| {
+                let target = definition
+                    .fields
+                    .get(capture)
+                    .map_or(capture, String::as_str);
+                (capture.to_string(), Field::from_name(target))
+            })
+            .collect();
+
+        Ok(Self {
+            name: definition.name.clone(),
+            matcher: Matcher::Pattern { pattern, fields },
+            timestamps: definition
+                .timestamp_format
+                .as_ref()
+                .map(|format| TimestampParser::with_format_strings(std::slice::from_ref(format))),
+        })
+    }
+
+    fn json_parser(definition: &FormatDefinition) -> Result<JsonParser> {
+        let mapping = match &definition.preset {
+            Some(preset) => JsonFieldMapping::preset(preset).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Format '{}': unknown JSON preset '{}'",
+                    definition.name,
+                    preset
+                )
+            })?,
+            None => JsonFieldMapping::default(),
+        };
+        let mapping = definition
+            .fields
+            .iter()
+            .fold(mapping, |mapping, (path, field)| {
+                mapping.with_field(path, field)
+            });
+        Ok(JsonParser::with_mapping(mapping))
+    }
+
+    fn parse_severity(level: &str) -> Severity {
+        match level.to_lowercase().as_str() {
+            "error" | "err" | "fatal" | "critical" | "crit" | "severe" | "alert" | "emerg"
+            | "emergency" => Severity::Error,
+            "warn" | "warning" => Severity::Warning,
+            "info" | "information" | "notice" => Severity::Info,
+            "debug" => Severity::Debug,
+            "trace" => Severity::Trace,
+            _ => Severity::Unknown,
+        }
+    }
+
+    fn parse_timestamp(&self, text: &str) -> Option<DateTime<Utc>> {
+        if let Some(timestamps) = &self.timestamps {
+            return timestamps.find(text);
+        }
+
+        // `2025-11-17 10:30:00,123` is as common as RFC 3339 in application logs
+        let normalized = text.replacen(' ', "T", 1).replace(',', ".");
+        DateTime::parse_from_rfc3339(&normalized)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(&normalized, "%Y-%m-%dT%H:%M:%S%.f")
+                    .ok()
+                    .map(|dt| Utc.from_utc_datetime(&dt))
+            })
+            .or_else(|| {
+                DateTime::parse_from_str(text, "%d/%b/%Y:%H:%M:%S %z")
+                    .ok()
+                    .map(|dt| dt.with_timezone(&Utc))
+            })
+            .or_else(|| {
+                DateTime::parse_from_rfc2822(text)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&Utc))
+            })
+    }
+}
+
+impl LogParser for CustomParser {
+    fn parse_line(&self, line: &str) -> Result<Option<LogEntry>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let (pattern, fields) = match &self.matcher {
+            Matcher::Pattern { pattern, fields } => (pattern, fields),
+            Matcher::Json(parser) => return parser.parse_line(line),
+        };
+        let Some(caps) = pattern.captures(line) else {
+            return Ok(None);
+        };
+
+        let mut timestamp = None;
+        let mut severity = Severity::Unknown;
+        let mut message = None;
+        let mut metadata = LogMetadata {
+            file: None,
+            line: None,
+            function: None,
+            thread: None,
+            extra: HashMap::new(),
+        };
+
+        for (capture, field) in fields {
+            let Some(value) = caps.name(capture).map(|m| m.as_str()) else {
+                continue;
+            };
+            match field {
+                Field::Timestamp => timestamp = self.parse_timestamp(value),
+                Field::Severity => severity = Self::parse_severity(value),
+                Field::Message => message = Some(value.to_string()),
+                Field::File => metadata.file = Some(value.to_string()),
+                Field::Line => metadata.line = value.parse().ok(),
+                Field::Function => metadata.function = Some(value.to_string()),
+                Field::Thread => metadata.thread = Some(value.to_string()),
+                Field::Extra(key) => {
+                    metadata.extra.insert(key.clone(), value.to_string());
+                }
+            }
+        }
+
+        Ok(Some(LogEntry {
+            timestamp,
+            severity,
+            // Without a message capture the whole line is the message
+            message: message.unwrap_or_else(|| line.to_string()),
+            metadata,
+            raw: line.to_string(),
+            source: None,
+        }))
+    }
+
+    fn can_parse(&self, sample: &str) -> bool {
+        match &self.matcher {
+            Matcher::Pattern { pattern, .. } => pattern.is_match(sample.trim()),
+            Matcher::Json(parser) => parser.can_parse(sample),
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn definition() -> FormatDefinition {
+        FormatDefinition {
+            name: "inhouse".to_string(),
+            grok: Some(
+                "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} \\[%{NOTSPACE:thread}\\] \
+                 %{WORD:service} req=%{NOTSPACE:rid} %{GREEDYDATA:message}"
+                    .to_string(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_grok_format() {
+        let mut definition = definition();
+        definition
+            .fields
+            .insert("rid".to_string(), "request_id".to_string());
+        let parser = CustomParser::new(&definition).unwrap();
+
+        let entry = parser
+            .parse_line(
+                "2025-11-17 10:30:00,123 ERROR [worker-1] billing req=abc-123 Payment failed",
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.timestamp.unwrap().minute(), 30);
+        assert_eq!(entry.message, "Payment failed");
+        assert_eq!(entry.metadata.thread.as_deref(), Some("worker-1"));
+        assert_eq!(entry.metadata.extra["service"], "billing");
+        assert_eq!(entry.metadata.extra["request_id"], "abc-123");
+        assert_eq!(parser.name(), "inhouse");
+
+        assert!(parser.parse_line("not our format").unwrap().is_none());
+        assert!(!parser.can_parse("not our format"));
+    }
+
+    #[test]
+    fn test_parse_regex_format_with_timestamp_format() {
+        let definition = FormatDefinition {
+            name: "legacy".to_string(),
+            pattern: Some(
+                r"^(?P<time>\d{2}/\d{2}/\d{4} \d{2}:\d{2}:\d{2}) <(?P<level>\w+)> (?P<file>[\w.]+):(?P<line>\d+) (?P<msg>.*)$"
+                    .to_string(),
+            ),
+            timestamp_format: Some("%d/%m/%Y %H:%M:%S".to_string()),
+            ..Default::default()
+        };
+        let parser = CustomParser::new(&definition).unwrap();
+
+        let entry = parser
+            .parse_line("17/11/2025 10:30:00 <warn> Cache.java:88 Cache miss ratio high")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Warning);
+        assert_eq!(entry.timestamp.unwrap().hour(), 10);
+        assert_eq!(entry.metadata.file.as_deref(), Some("Cache.java"));
+        assert_eq!(entry.metadata.line, Some(88));
+        assert_eq!(entry.message, "Cache miss ratio high");
+    }
+
+    #[test]
+    fn test_json_format_with_preset_and_fields() {
+        let mut fields = HashMap::new();
+        fields.insert("labels.order_id".to_string(), "order_id".to_string());
+        let definition = FormatDefinition {
+            name: "orders".to_string(),
+            preset: Some("ecs".to_string()),
+            fields,
+            ..Default::default()
+        };
+        let parser = CustomParser::new(&definition).unwrap();
+
+        let entry = parser
+            .parse_line(
+                r#"{"log.level":"error","message":"Charge failed","labels":{"order_id":"o-1"}}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(parser.name(), "orders");
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.metadata.extra["order_id"], "o-1");
+        assert!(parser.can_parse(r#"{"message":"x"}"#));
+        assert!(!parser.can_parse("plain text"));
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let both = FormatDefinition {
+            name: "both".to_string(),
+            pattern: Some(".*".to_string()),
+            grok: Some("%{GREEDYDATA}".to_string()),
+            ..Default::default()
+        };
+        assert!(CustomParser::new(&both).is_err());
+
+        let neither = FormatDefinition {
+            name: "neither".to_string(),
+            ..Default::default()
+        };
+        assert!(CustomParser::new(&neither).is_err());
+
+        let bad_preset = FormatDefinition {
+            name: "bad".to_string(),
+            preset: Some("log4j".to_string()),
+            ..Default::default()
+        };
+        assert!(CustomParser::new(&bad_preset).is_err());
+
+        let bad_regex = FormatDefinition {
+            name: "bad".to_string(),
+            pattern: Some("(unclosed".to_string()),
+            ..Default::default()
+        };
+        assert!(CustomParser::new(&bad_regex).is_err());
+    }
+}
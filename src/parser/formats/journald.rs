@@ -0,0 +1,378 @@
+use crate::parser::{formats::SyslogParser, LogParser};
+use crate::types::{LogEntry, LogMetadata, Severity};
+use crate::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Fields mapped onto `LogMetadata::extra` under a shorter name
+const EXTRA_FIELDS: &[(&str, &str)] = &[
+    ("_SYSTEMD_UNIT", "unit"),
+    ("_SYSTEMD_USER_UNIT", "user_unit"),
+    ("_PID", "pid"),
+    ("_HOSTNAME", "hostname"),
+    ("SYSLOG_IDENTIFIER", "app_name"),
+    ("PRIORITY", "priority"),
+];
+
+/// Fields that fill the entry itself and are not repeated in metadata
+const ENTRY_FIELDS: &[&str] = &["MESSAGE", "CODE_FILE", "CODE_LINE", "CODE_FUNC", "TID"];
+
+/// Parser for systemd journal logs, as written by `journalctl -o json` (one
+/// object per line) or `journalctl -o export` (`KEY=value` lines, entries
+/// separated by a blank line, binary fields as `KEY`, a little-endian 64-bit
+/// length and the raw bytes).
+///
+/// Fields that occur several times are kept: multiple `MESSAGE` values are
+/// joined with newlines and other repeated fields with commas. Entries
+/// without a `MESSAGE` are skipped.
+pub struct JournaldParser;
+
+impl Default for JournaldParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JournaldParser {
+    /// Create a new journald parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Journal field names are upper-case letters, digits and underscores
+    fn is_field_name(name: &[u8]) -> bool {
+        !name.is_empty()
+            && !name[0].is_ascii_digit()
+            && name
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || *b == b'_')
+    }
+
+    /// Whether `line` is an export format field (`KEY=value` or a bare `KEY`)
+    fn is_export_field(line: &str) -> bool {
+        let name = line.split_once('=').map_or(line, |(name, _)| name);
+        Self::is_field_name(name.as_bytes())
+    }
+
+    /// Values of a `journalctl -o json` field: a string, a byte array for
+    /// binary data, an array of either for repeated fields, or `null` for
+    /// values too large to show
+    fn json_values(value: &Value) -> Vec<String> {
+        fn bytes(values: &[Value]) -> Option<String> {
+            let bytes = values
+                .iter()
+                .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect::<Option<Vec<u8>>>()?;
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+
+        match value {
+            Value::String(s) => vec![s.clone()],
+            Value::Number(n) => vec![n.to_string()],
+            Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_number) => {
+                bytes(items).into_iter().collect()
+            }
+            Value::Array(items) => items
+                .iter()
+                .flat_map(|item| match item {
+                    Value::Array(b) => bytes(b).into_iter().collect(),
+                    other => Self::json_values(other),
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn parse_json(line: &str) -> Option<Vec<(String, String)>> {
+        let Value::Object(object) = serde_json::from_str(line).ok()? else {
+            return None;
+        };
+        Some(
+            object
+                .iter()
+                .flat_map(|(key, value)| {
+                    Self::json_values(value)
+                        .into_iter()
+                        .map(move |v| (key.clone(), v))
+                })
+                .collect(),
+        )
+    }
+
+    /// Parse the entries in export format text.
+    ///
+    /// Binary fields are read by their length prefix. When the prefix was
+    /// mangled (lines decoded lossily, `\r` stripped) the value runs up to the
+    /// next line that looks like a field instead. JSON lines found between
+    /// entries are parsed as `journalctl -o json` output.
+    fn parse_text(text: &str) -> Vec<LogEntry> {
+        let bytes = text.as_bytes();
+        let line_end = |from: usize| {
+            bytes[from..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |i| from + i)
+        };
+
+        let mut entries = Vec::new();
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let end = line_end(pos);
+            let line = &text[pos..end];
+
+            if line.trim().is_empty() {
+                entries.extend(Self::export_entry(std::mem::take(&mut fields)));
+                pos = end + 1;
+                continue;
+            }
+
+            if fields.is_empty() && line.trim_start().starts_with('{') {
+                let line = line.trim();
+                entries
+                    .extend(Self::parse_json(line).and_then(|f| Self::entry(f, line.to_string())));
+                pos = end + 1;
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                if Self::is_field_name(key.as_bytes()) {
+                    fields.push((key.to_string(), value.to_string()));
+                }
+                pos = end + 1;
+                continue;
+            }
+
+            if !Self::is_field_name(line.as_bytes()) {
+                // Not part of a journal entry
+                pos = end + 1;
+                continue;
+            }
+
+            // Binary field: KEY\n<u64 le length><data>\n
+            let start = end + 1;
+            let length = bytes
+                .get(start..start + 8)
+                .filter(|prefix| prefix.is_ascii())
+                .map(|prefix| u64::from_le_bytes(prefix.try_into().unwrap()) as usize);
+            let exact = length.and_then(|length| {
+                let data_end = (start + 8).checked_add(length)?;
+                let terminated = data_end == bytes.len() || bytes.get(data_end) == Some(&b'\n');
+                (terminated && text.is_char_boundary(data_end)).then_some(data_end)
+            });
+
+            let (value, next) = match exact {
+                Some(data_end) => (&text[start + 8..data_end], data_end + 1),
+                None => {
+                    // Skip what is left of the length prefix, then stop at
+                    // the next field or blank line
+                    let data_start = text[start.min(text.len())..]
+                        .char_indices()
+                        .nth(8)
+                        .map_or(text.len(), |(i, _)| start + i);
+                    let mut data_end = line_end(data_start);
+                    while data_end < bytes.len() {
+                        let next_line = &text[data_end + 1..line_end(data_end + 1)];
+                        if next_line.is_empty() || Self::is_export_field(next_line) {
+                            break;
+                        }
+                        data_end = line_end(data_end + 1);
+                    }
+                    (&text[data_start..data_end], data_end + 1)
+                }
+            };
+            fields.push((line.to_string(), value.to_string()));
+            pos = next;
+        }
+
+        entries.extend(Self::export_entry(fields));
+        entries
+    }
+
+    /// Microseconds since the epoch, as journald stores timestamps
+    fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+        let micros: i64 = value.trim().parse().ok()?;
+        Utc.timestamp_micros(micros).single()
+    }
+
+    fn entry(fields: Vec<(String, String)>, raw: String) -> Option<LogEntry> {
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, value) in fields {
+            values.entry(key).or_default().push(value);
+        }
+        let first = |key: &str| values.get(key).and_then(|v| v.first()).cloned();
+
+        let message = values.get("MESSAGE")?.join("\n");
+        let severity = first("PRIORITY")
+            .and_then(|p| p.trim().parse::<u8>().ok())
+            .map_or(Severity::Unknown, SyslogParser::priority_to_severity);
+        let timestamp = first("_SOURCE_REALTIME_TIMESTAMP")
+            .or_else(|| first("__REALTIME_TIMESTAMP"))
+            .and_then(|t| Self::parse_timestamp(&t));
+
+        let mut extra = HashMap::new();
+        for (field, name) in EXTRA_FIELDS {
+            if let Some(value) = first(field) {
+                extra.insert(name.to_string(), value);
+            }
+        }
+        // Fields the application added (trusted and internal ones start with `_`)
+        for (key, value) in &values {
+            let mapped = ENTRY_FIELDS.contains(&key.as_str())
+                || EXTRA_FIELDS.iter().any(|(field, _)| field == key);
+            if !key.starts_with('_') && !mapped {
+                extra.insert(key.clone(), value.join(", "));
+            }
+        }
+
+        Some(LogEntry {
+            timestamp,
+            severity,
+            message,
+            metadata: LogMetadata {
+                file: first("CODE_FILE"),
+                line: first("CODE_LINE").and_then(|l| l.trim().parse().ok()),
+                function: first("CODE_FUNC"),
+                thread: first("TID"),
+                extra,
+            },
+            raw,
+            source: None,
+        })
+    }
+
+    /// Entry for export format `fields`, with the fields as its raw text
+    fn export_entry(fields: Vec<(String, String)>) -> Option<LogEntry> {
+        let raw = fields
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self::entry(fields, raw)
+    }
+}
+
+impl LogParser for JournaldParser {
+    fn parse_line(&self, line: &str) -> Result<Option<LogEntry>> {
+        let line = line.trim();
+        // Export format entries span several lines and need `parse_lines`
+        Ok(Self::parse_json(line).and_then(|fields| Self::entry(fields, line.to_string())))
+    }
+
+    fn parse_lines(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
+        Ok(Self::parse_text(&lines.join("\n")))
+    }
+
+    fn can_parse(&self, sample: &str) -> bool {
+        let sample = sample.trim();
+        if sample.starts_with("__CURSOR=") || sample.starts_with("__REALTIME_TIMESTAMP=") {
+            return true;
+        }
+
+        let Some(fields) = Self::parse_json(sample) else {
+            return false;
+        };
+        let has = |name: &str| fields.iter().any(|(key, _)| key == name);
+        has("__CURSOR")
+            || has("__REALTIME_TIMESTAMP")
+            || (has("MESSAGE") && (has("PRIORITY") || has("_SYSTEMD_UNIT") || has("_PID")))
+    }
+
+    fn supports_multiline(&self) -> bool {
+        true
+    }
+
+    fn is_continuation_line(&self, line: &str) -> bool {
+        // Export entries start with their cursor; JSON entries are single lines
+        !line.starts_with("__CURSOR=") && !line.trim_start().starts_with('{')
+    }
+
+    fn name(&self) -> &str {
+        "journald"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_journalctl_json() {
+        let parser = JournaldParser::new();
+        let line = r#"{"__CURSOR":"s=1;i=2","__REALTIME_TIMESTAMP":"1700000000123456","PRIORITY":"3","_SYSTEMD_UNIT":"nginx.service","_PID":"812","_HOSTNAME":"web-1","CODE_FILE":"src/main.c","CODE_LINE":"42","CODE_FUNC":"serve","MESSAGE":"upstream timed out","REQUEST_ID":"abc"}"#;
+
+        assert!(parser.can_parse(line));
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.message, "upstream timed out");
+        assert_eq!(
+            entry.timestamp.unwrap().timestamp_micros(),
+            1_700_000_000_123_456
+        );
+        assert_eq!(entry.metadata.file.as_deref(), Some("src/main.c"));
+        assert_eq!(entry.metadata.line, Some(42));
+        assert_eq!(entry.metadata.function.as_deref(), Some("serve"));
+        assert_eq!(entry.metadata.extra["unit"], "nginx.service");
+        assert_eq!(entry.metadata.extra["pid"], "812");
+        assert_eq!(entry.metadata.extra["hostname"], "web-1");
+        assert_eq!(entry.metadata.extra["REQUEST_ID"], "abc");
+        assert!(!entry.metadata.extra.contains_key("__CURSOR"));
+    }
+
+    #[test]
+    fn test_parse_json_binary_and_repeated_fields() {
+        let parser = JournaldParser::new();
+        // "ok\n!" as bytes, and MESSAGE given twice
+        let line = r#"{"PRIORITY":"4","MESSAGE":[[111,107,10,33],"second"],"TAG":["a","b"]}"#;
+
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(entry.severity, Severity::Warning);
+        assert_eq!(entry.message, "ok\n!\nsecond");
+        assert_eq!(entry.metadata.extra["TAG"], "a, b");
+
+        let line = r#"{"PRIORITY":"6","MESSAGE":[104,105]}"#;
+        assert_eq!(parser.parse_line(line).unwrap().unwrap().message, "hi");
+    }
+
+    #[test]
+    fn test_parse_export_format() {
+        let parser = JournaldParser::new();
+        let message = "panic: boom\n\ngoroutine 1";
+        let mut text = String::from(
+            "__CURSOR=s=1;i=1\n__REALTIME_TIMESTAMP=1700000000000000\nPRIORITY=2\n_SYSTEMD_UNIT=api.service\nMESSAGE\n",
+        );
+        text.push_str(std::str::from_utf8(&(message.len() as u64).to_le_bytes()).unwrap());
+        text.push_str(message);
+        text.push_str("\n_PID=99\n\n__CURSOR=s=1;i=2\nPRIORITY=6\nMESSAGE=started\n");
+
+        let lines = lines(&text);
+        assert!(parser.can_parse(&lines[0]));
+        assert!(!parser.is_continuation_line(&lines[0]));
+        assert!(parser.is_continuation_line(&lines[1]));
+
+        let entries = parser.parse_lines(&lines).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].severity, Severity::Error);
+        assert_eq!(entries[0].message, message);
+        assert_eq!(entries[0].metadata.extra["unit"], "api.service");
+        assert_eq!(entries[0].metadata.extra["pid"], "99");
+        assert_eq!(entries[1].severity, Severity::Info);
+        assert_eq!(entries[1].message, "started");
+    }
+
+    #[test]
+    fn test_export_binary_field_with_mangled_length() {
+        // A length byte above 0x7f does not survive lossy line decoding
+        let text =
+            "__CURSOR=c\nMESSAGE\n\u{fffd}\0\0\0\0\0\0\0first line\nsecond line\nPRIORITY=3\n";
+        let entries = JournaldParser::new().parse_lines(&lines(text)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "first line\nsecond line");
+        assert_eq!(entries[0].severity, Severity::Error);
+    }
+}
@@ -2,10 +2,195 @@ use crate::parser::LogParser;
 use crate::types::{LogEntry, LogMetadata, Severity};
 use crate::Result;
 use chrono::{DateTime, Utc};
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 
-pub struct JsonParser;
+/// Names of the built-in field mapping presets
+pub const JSON_PRESETS: &[&str] = &["ecs", "bunyan", "pino", "clef", "gcp"];
+
+/// Dotted paths (e.g. `log.level`) read for each entry field, in order of preference
+#[derive(Debug, Clone)]
+pub struct JsonFieldMapping {
+    pub message: Vec<String>,
+    pub level: Vec<String>,
+    pub timestamp: Vec<String>,
+    /// Appended to the message on a new line so traces take part in grouping
+    pub stack_trace: Vec<String>,
+    pub file: Vec<String>,
+    pub line: Vec<String>,
+    pub function: Vec<String>,
+    pub thread: Vec<String>,
+    /// Severity of entries without a level field (CLEF omits `@l` for Information)
+    pub default_severity: Severity,
+    /// Keys used in `extra` for flattened paths (path -> key)
+    pub extra: HashMap<String, String>,
+}
+
+fn paths(paths: &[&str]) -> Vec<String> {
+    paths.iter().map(|path| path.to_string()).collect()
+}
+
+impl Default for JsonFieldMapping {
+    /// Common field names across logging libraries, including the presets' fields
+    fn default() -> Self {
+        Self {
+            message: paths(&[
+                "message",
+                "msg",
+                "text",
+                "@m",
+                "@mt",
+                "textPayload",
+                "event",
+            ]),
+            level: paths(&["level", "severity", "priority", "log.level", "@l", "lvl"]),
+            timestamp: paths(&["timestamp", "time", "@timestamp", "@t", "ts"]),
+            stack_trace: paths(&[
+                "error.stack_trace",
+                "error.stack",
+                "err.stack",
+                "exception",
+                "@x",
+                "stack_trace",
+                "stacktrace",
+                "stack",
+            ]),
+            file: paths(&[
+                "file",
+                "log.origin.file.name",
+                "src.file",
+                "logging.googleapis.com/sourceLocation.file",
+            ]),
+            line: paths(&[
+                "line",
+                "log.origin.file.line",
+                "src.line",
+                "logging.googleapis.com/sourceLocation.line",
+            ]),
+            function: paths(&[
+                "function",
+                "func",
+                "log.origin.function",
+                "src.func",
+                "logging.googleapis.com/sourceLocation.function",
+            ]),
+            thread: paths(&["thread", "thread_id", "process.thread.name", "threadName"]),
+            default_severity: Severity::Unknown,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl JsonFieldMapping {
+    /// Mapping for a preset in `JSON_PRESETS`
+    pub fn preset(name: &str) -> Option<Self> {
+        let mapping = match name {
+            // Elastic Common Schema
+            "ecs" => Self {
+                message: paths(&["message", "error.message"]),
+                level: paths(&["log.level"]),
+                timestamp: paths(&["@timestamp"]),
+                stack_trace: paths(&["error.stack_trace"]),
+                file: paths(&["log.origin.file.name"]),
+                line: paths(&["log.origin.file.line"]),
+                function: paths(&["log.origin.function"]),
+                thread: paths(&["process.thread.name"]),
+                ..Self::empty()
+            },
+            // Bunyan and Pino use numeric levels (30 = info, 50 = error)
+            "bunyan" => Self {
+                message: paths(&["msg"]),
+                level: paths(&["level"]),
+                timestamp: paths(&["time"]),
+                stack_trace: paths(&["err.stack"]),
+                file: paths(&["src.file"]),
+                line: paths(&["src.line"]),
+                function: paths(&["src.func"]),
+                ..Self::empty()
+            },
+            "pino" => Self {
+                message: paths(&["msg"]),
+                level: paths(&["level"]),
+                timestamp: paths(&["time"]),
+                stack_trace: paths(&["err.stack"]),
+                ..Self::empty()
+            },
+            // Serilog compact log event format
+            "clef" => Self {
+                message: paths(&["@m", "@mt"]),
+                level: paths(&["@l"]),
+                timestamp: paths(&["@t"]),
+                stack_trace: paths(&["@x"]),
+                default_severity: Severity::Info,
+                ..Self::empty()
+            },
+            // Google Cloud Logging structured payloads
+            "gcp" => Self {
+                message: paths(&["message", "textPayload", "jsonPayload.message"]),
+                level: paths(&["severity"]),
+                timestamp: paths(&["timestamp", "time"]),
+                stack_trace: paths(&["stack_trace", "exception"]),
+                file: paths(&[
+                    "logging.googleapis.com/sourceLocation.file",
+                    "sourceLocation.file",
+                ]),
+                line: paths(&[
+                    "logging.googleapis.com/sourceLocation.line",
+                    "sourceLocation.line",
+                ]),
+                function: paths(&[
+                    "logging.googleapis.com/sourceLocation.function",
+                    "sourceLocation.function",
+                ]),
+                ..Self::empty()
+            },
+            _ => return None,
+        };
+        Some(mapping)
+    }
+
+    fn empty() -> Self {
+        Self {
+            message: vec![],
+            level: vec![],
+            timestamp: vec![],
+            stack_trace: vec![],
+            file: vec![],
+            line: vec![],
+            function: vec![],
+            thread: vec![],
+            default_severity: Severity::Unknown,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Read `path` into `field` (an entry field name, or a key for `extra`)
+    /// before any other path
+    pub fn with_field(mut self, path: &str, field: &str) -> Self {
+        let paths = match field {
+            "message" | "msg" => &mut self.message,
+            "level" | "severity" => &mut self.level,
+            "timestamp" | "ts" | "time" => &mut self.timestamp,
+            "stack_trace" => &mut self.stack_trace,
+            "file" => &mut self.file,
+            "line" => &mut self.line,
+            "function" | "func" => &mut self.function,
+            "thread" => &mut self.thread,
+            _ => {
+                self.extra.insert(path.to_string(), field.to_string());
+                return self;
+            }
+        };
+        paths.insert(0, path.to_string());
+        self
+    }
+}
+
+/// Parser for JSON logs, one object per line
+pub struct JsonParser {
+    mapping: JsonFieldMapping,
+    name: &'static str,
+}
 
 impl Default for JsonParser {
     fn default() -> Self {
@@ -15,17 +200,96 @@ impl Default for JsonParser {
 
 impl JsonParser {
     pub fn new() -> Self {
-        Self
+        Self::with_mapping(JsonFieldMapping::default())
+    }
+
+    /// Create a parser reading fields from the paths in `mapping`
+    pub fn with_mapping(mapping: JsonFieldMapping) -> Self {
+        Self {
+            mapping,
+            name: "json",
+        }
+    }
+
+    /// Create a parser for a preset in `JSON_PRESETS`
+    pub fn with_preset(name: &str) -> Option<Self> {
+        let preset = JSON_PRESETS.iter().find(|preset| **preset == name)?;
+        Some(Self {
+            mapping: JsonFieldMapping::preset(preset)?,
+            name: preset,
+        })
+    }
+
+    /// Find the value at a dotted `path`. Keys that themselves contain dots
+    /// (`logging.googleapis.com/sourceLocation`) are matched before splitting.
+    fn lookup<'a>(obj: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+        if let Some(value) = obj.get(path) {
+            return Some(value);
+        }
+        path.match_indices('.').find_map(|(i, _)| {
+            obj.get(&path[..i])?
+                .as_object()
+                .and_then(|inner| Self::lookup(inner, &path[i + 1..]))
+        })
+    }
+
+    /// First present, non-null value among `paths`, with the path it came from
+    fn first<'a, 'p>(
+        obj: &'a Map<String, Value>,
+        paths: &'p [String],
+    ) -> Option<(&'p str, &'a Value)> {
+        paths.iter().find_map(|path| {
+            Self::lookup(obj, path)
+                .filter(|value| !value.is_null())
+                .map(|value| (path.as_str(), value))
+        })
+    }
+
+    /// Like `first`, recording the path so it is left out of `extra`
+    fn take<'a, 'p>(
+        obj: &'a Map<String, Value>,
+        paths: &'p [String],
+        used: &mut HashSet<&'p str>,
+    ) -> Option<&'a Value> {
+        let (path, value) = Self::first(obj, paths)?;
+        used.insert(path);
+        Some(value)
+    }
+
+    fn text(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
     }
 
     fn parse_severity(value: &Value) -> Severity {
+        // Bunyan/Pino numeric levels: 10 trace, 20 debug, 30 info, 40 warn, 50 error, 60 fatal
+        let numeric = value
+            .as_u64()
+            .or_else(|| value.as_str().and_then(|s| s.parse().ok()));
+        if let Some(level) = numeric {
+            return match level {
+                50.. => Severity::Error,
+                40..=49 => Severity::Warning,
+                30..=39 => Severity::Info,
+                20..=29 => Severity::Debug,
+                10..=19 => Severity::Trace,
+                _ => Severity::Unknown,
+            };
+        }
+
         let s = value.as_str().unwrap_or("").to_lowercase();
         match s.as_str() {
-            "error" | "err" | "fatal" | "critical" => Severity::Error,
+            "error" | "err" | "fatal" | "critical" | "crit" | "alert" | "emergency" | "panic" => {
+                Severity::Error
+            }
             "warn" | "warning" => Severity::Warning,
-            "info" | "information" => Severity::Info,
+            "info" | "information" | "notice" => Severity::Info,
             "debug" => Severity::Debug,
-            "trace" => Severity::Trace,
+            "trace" | "verbose" => Severity::Trace,
             _ => Severity::Unknown,
         }
     }
@@ -40,12 +304,54 @@ impl JsonParser {
                         .ok()
                         .map(|dt| dt.with_timezone(&Utc))
                 })
-        } else if let Some(ts_num) = value.as_i64() {
-            DateTime::from_timestamp(ts_num, 0)
+        } else if let Some(ts_num) = value.as_f64() {
+            // Epoch milliseconds (Pino) or seconds
+            if ts_num > 1e12 {
+                DateTime::from_timestamp_millis(ts_num as i64)
+            } else {
+                DateTime::from_timestamp_millis((ts_num * 1000.0) as i64)
+            }
+        } else if let Some(obj) = value.as_object() {
+            // {"seconds": ..., "nanos": ...} as in Cloud Logging
+            let seconds = obj.get("seconds")?.as_i64()?;
+            let nanos = obj.get("nanos").and_then(|n| n.as_u64()).unwrap_or(0);
+            DateTime::from_timestamp(seconds, nanos as u32)
         } else {
             None
         }
     }
+
+    /// Add the values of `obj` to `extra` under dotted keys, skipping `used` paths
+    fn flatten(
+        &self,
+        obj: &Map<String, Value>,
+        prefix: &str,
+        used: &HashSet<&str>,
+        extra: &mut HashMap<String, String>,
+    ) {
+        for (key, value) in obj {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            if used.contains(path.as_str()) {
+                continue;
+            }
+
+            let text = match value {
+                Value::Null => continue,
+                Value::Object(inner) => {
+                    self.flatten(inner, &path, used, extra);
+                    continue;
+                }
+                Value::Array(_) => value.to_string(),
+                other => Self::text(other).unwrap_or_default(),
+            };
+            let key = self.mapping.extra.get(&path).cloned().unwrap_or(path);
+            extra.insert(key, text);
+        }
+    }
 }
 
 impl LogParser for JsonParser {
@@ -65,54 +371,42 @@ impl LogParser for JsonParser {
             None => return Ok(None),
         };
 
-        // Extract common fields
-        let message = obj
-            .get("message")
-            .or_else(|| obj.get("msg"))
-            .or_else(|| obj.get("text"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        let severity = obj
-            .get("level")
-            .or_else(|| obj.get("severity"))
-            .or_else(|| obj.get("priority"))
-            .map(Self::parse_severity)
-            .unwrap_or(Severity::Unknown);
+        let mapping = &self.mapping;
+        let mut used = HashSet::new();
 
-        let timestamp = obj
-            .get("timestamp")
-            .or_else(|| obj.get("time"))
-            .or_else(|| obj.get("@timestamp"))
-            .and_then(Self::parse_timestamp);
-
-        // Extract metadata
-        let mut extra = HashMap::new();
-        for (key, value) in obj.iter() {
-            if !matches!(
-                key.as_str(),
-                "message" | "msg" | "text" | "level" | "severity" | "timestamp" | "time"
-            ) {
-                if let Some(s) = value.as_str() {
-                    extra.insert(key.clone(), s.to_string());
+        let mut message = Self::take(obj, &mapping.message, &mut used)
+            .and_then(Self::text)
+            .unwrap_or_default();
+        if let Some(stack) = Self::take(obj, &mapping.stack_trace, &mut used).and_then(Self::text) {
+            if !message.contains(stack.trim()) {
+                if !message.is_empty() {
+                    message.push('\n');
                 }
+                message.push_str(&stack);
             }
         }
 
+        let severity = Self::take(obj, &mapping.level, &mut used)
+            .map(Self::parse_severity)
+            .unwrap_or(mapping.default_severity);
+        let timestamp =
+            Self::take(obj, &mapping.timestamp, &mut used).and_then(Self::parse_timestamp);
+
+        let file = Self::take(obj, &mapping.file, &mut used).and_then(Self::text);
+        let line_number = Self::take(obj, &mapping.line, &mut used)
+            .and_then(Self::text)
+            .and_then(|n| n.parse().ok());
+        let function = Self::take(obj, &mapping.function, &mut used).and_then(Self::text);
+        let thread = Self::take(obj, &mapping.thread, &mut used).and_then(Self::text);
+
+        let mut extra = HashMap::new();
+        self.flatten(obj, "", &used, &mut extra);
+
         let metadata = LogMetadata {
-            file: obj.get("file").and_then(|v| v.as_str()).map(String::from),
-            line: obj.get("line").and_then(|v| v.as_u64()).map(|n| n as u32),
-            function: obj
-                .get("function")
-                .or_else(|| obj.get("func"))
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            thread: obj
-                .get("thread")
-                .or_else(|| obj.get("thread_id"))
-                .and_then(|v| v.as_str())
-                .map(String::from),
+            file,
+            line: line_number,
+            function,
+            thread,
             extra,
         };
 
@@ -122,17 +416,23 @@ impl LogParser for JsonParser {
             message,
             metadata,
             raw: line.to_string(),
+            source: None,
         }))
     }
 
     fn can_parse(&self, sample: &str) -> bool {
         sample.trim_start().starts_with('{') && serde_json::from_str::<Value>(sample).is_ok()
     }
+
+    fn name(&self) -> &str {
+        self.name
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Timelike;
 
     #[test]
     fn test_parse_json_log() {
@@ -147,4 +447,107 @@ mod tests {
         assert_eq!(entry.message, "Connection failed");
         assert_eq!(entry.severity, Severity::Error);
     }
+
+    #[test]
+    fn test_nested_fields_and_flattening() {
+        let parser = JsonParser::new();
+        let line = r#"{"@timestamp":"2025-11-17T10:30:00Z","log":{"level":"warn","origin":{"file":{"name":"Db.java","line":42}}},"message":"Slow query","http":{"status":503,"retry":true},"tags":["db","slow"],"trace":null}"#;
+
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(entry.severity, Severity::Warning);
+        assert_eq!(entry.timestamp.unwrap().minute(), 30);
+        assert_eq!(entry.metadata.file.as_deref(), Some("Db.java"));
+        assert_eq!(entry.metadata.line, Some(42));
+
+        let extra = &entry.metadata.extra;
+        assert_eq!(extra["http.status"], "503");
+        assert_eq!(extra["http.retry"], "true");
+        assert_eq!(extra["tags"], r#"["db","slow"]"#);
+        assert!(!extra.contains_key("log.level"));
+        assert!(!extra.contains_key("message"));
+        assert!(!extra.contains_key("trace"));
+    }
+
+    #[test]
+    fn test_ecs_preset_appends_stack_trace() {
+        let parser = JsonParser::with_preset("ecs").unwrap();
+        let line = r#"{"@timestamp":"2025-11-17T10:30:00Z","log.level":"error","message":"Request failed","error":{"type":"java.lang.NullPointerException","stack_trace":"java.lang.NullPointerException\n\tat com.example.Api.handle(Api.java:10)"},"process":{"thread":{"name":"http-1"}}}"#;
+
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(parser.name(), "ecs");
+        assert_eq!(entry.severity, Severity::Error);
+        assert!(entry
+            .message
+            .starts_with("Request failed\njava.lang.NullPointerException"));
+        assert_eq!(entry.metadata.thread.as_deref(), Some("http-1"));
+        assert_eq!(
+            entry.metadata.extra["error.type"],
+            "java.lang.NullPointerException"
+        );
+    }
+
+    #[test]
+    fn test_bunyan_and_pino_numeric_levels() {
+        let bunyan = JsonParser::with_preset("bunyan").unwrap();
+        let entry = bunyan
+            .parse_line(r#"{"name":"api","hostname":"web-1","pid":42,"level":50,"msg":"db down","time":"2025-11-17T10:30:00.000Z","v":0}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.metadata.extra["pid"], "42");
+
+        let pino = JsonParser::with_preset("pino").unwrap();
+        let entry = pino
+            .parse_line(r#"{"level":40,"time":1763375400000,"msg":"slow","err":{"stack":"Error: slow\n    at run (app.js:1:1)"}}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Warning);
+        assert_eq!(entry.timestamp.unwrap().minute(), 30);
+        assert!(entry.message.contains("at run (app.js:1:1)"));
+    }
+
+    #[test]
+    fn test_clef_and_gcp_presets() {
+        let clef = JsonParser::with_preset("clef").unwrap();
+        let entry = clef
+            .parse_line(
+                r#"{"@t":"2025-11-17T10:30:00Z","@mt":"User {UserId} logged in","UserId":7}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Info);
+        assert_eq!(entry.message, "User {UserId} logged in");
+        assert_eq!(entry.metadata.extra["UserId"], "7");
+
+        let gcp = JsonParser::with_preset("gcp").unwrap();
+        let entry = gcp
+            .parse_line(r#"{"severity":"CRITICAL","message":"quota exceeded","timestamp":{"seconds":1763375400,"nanos":0},"logging.googleapis.com/sourceLocation":{"file":"main.go","line":"88","function":"main.run"}}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.timestamp.unwrap().minute(), 30);
+        assert_eq!(entry.metadata.file.as_deref(), Some("main.go"));
+        assert_eq!(entry.metadata.line, Some(88));
+        assert_eq!(entry.metadata.function.as_deref(), Some("main.run"));
+
+        assert!(JsonParser::with_preset("log4j").is_none());
+    }
+
+    #[test]
+    fn test_custom_field_mapping() {
+        let mapping = JsonFieldMapping::default()
+            .with_field("payload.text", "message")
+            .with_field("ctx.req", "request_id");
+        let parser = JsonParser::with_mapping(mapping);
+
+        let entry = parser
+            .parse_line(
+                r#"{"msg":"ignored","payload":{"text":"checkout failed"},"ctx":{"req":"abc-1"}}"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.message, "checkout failed");
+        assert_eq!(entry.metadata.extra["request_id"], "abc-1");
+        assert_eq!(entry.metadata.extra["msg"], "ignored");
+    }
 }
@@ -0,0 +1,268 @@
+use crate::parser::LogParser;
+use crate::types::{LogEntry, LogMetadata, Severity};
+use crate::Result;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+/// Parser for logfmt logs (`ts=... level=error msg="..." err="..."`)
+pub struct LogfmtParser;
+
+impl Default for LogfmtParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogfmtParser {
+    /// Create a new logfmt parser
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Split a line into key/value pairs, or `None` if it is not logfmt.
+    ///
+    /// Values may be bare, double-quoted with `\"`, `\\`, `\n` and `\t`
+    /// escapes, or missing (`key` alone, stored as an empty value).
+    fn pairs(line: &str) -> Option<Vec<(String, String)>> {
+        let mut pairs = Vec::new();
+        let mut chars = line.chars().peekable();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let mut key = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+                if c == '"' {
+                    return None;
+                }
+                key.push(c);
+            }
+            if key.is_empty() {
+                return None;
+            }
+
+            let mut value = String::new();
+            if chars.next_if_eq(&'=').is_some() {
+                if chars.next_if_eq(&'"').is_some() {
+                    loop {
+                        match chars.next()? {
+                            '"' => break,
+                            '\\' => match chars.next()? {
+                                'n' => value.push('\n'),
+                                't' => value.push('\t'),
+                                other => value.push(other),
+                            },
+                            c => value.push(c),
+                        }
+                    }
+                    // A closing quote must end the value
+                    if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                        return None;
+                    }
+                } else {
+                    while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                        value.push(c);
+                    }
+                }
+            }
+            pairs.push((key, value));
+        }
+
+        Some(pairs)
+    }
+
+    fn parse_severity(level: &str) -> Severity {
+        match level.to_lowercase().as_str() {
+            "error" | "err" | "fatal" | "critical" | "crit" | "panic" => Severity::Error,
+            "warn" | "warning" => Severity::Warning,
+            "info" | "notice" => Severity::Info,
+            "debug" | "dbug" => Severity::Debug,
+            "trace" => Severity::Trace,
+            _ => Severity::Unknown,
+        }
+    }
+
+    fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Some(dt.with_timezone(&Utc));
+        }
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Some(Utc.from_utc_datetime(&dt));
+        }
+
+        // Unix seconds or milliseconds, possibly with a fraction
+        let seconds: f64 = value.parse().ok()?;
+        let millis = if seconds > 1e12 {
+            seconds
+        } else {
+            seconds * 1000.0
+        };
+        Utc.timestamp_millis_opt(millis as i64).single()
+    }
+}
+
+impl LogParser for LogfmtParser {
+    fn parse_line(&self, line: &str) -> Result<Option<LogEntry>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        // Lines joined by `StackTraceParser` carry a trace after the logfmt
+        // line; it is not logfmt, so it goes into the message
+        let (first, trace) = match line.split_once('\n') {
+            Some((first, trace)) => (first.trim_end(), Some(trace)),
+            None => (line, None),
+        };
+        let Some(pairs) = Self::pairs(first) else {
+            return Ok(None);
+        };
+
+        let mut timestamp = None;
+        let mut severity = Severity::Unknown;
+        let mut message = None;
+        let mut metadata = LogMetadata {
+            file: None,
+            line: None,
+            function: None,
+            thread: None,
+            extra: HashMap::new(),
+        };
+
+        for (key, value) in pairs {
+            match key.as_str() {
+                "ts" | "time" | "timestamp" | "t" if timestamp.is_none() => {
+                    timestamp = Self::parse_timestamp(&value);
+                }
+                "level" | "lvl" | "severity" => severity = Self::parse_severity(&value),
+                "msg" | "message" if message.is_none() => message = Some(value),
+                "caller" => {
+                    // caller=main.go:42
+                    match value.rsplit_once(':') {
+                        Some((file, line)) if line.parse::<u32>().is_ok() => {
+                            metadata.file = Some(file.to_string());
+                            metadata.line = line.parse().ok();
+                        }
+                        _ => metadata.file = Some(value),
+                    }
+                }
+                "func" | "function" => metadata.function = Some(value),
+                "thread" | "goroutine" => metadata.thread = Some(value),
+                _ => {
+                    metadata.extra.insert(key, value);
+                }
+            }
+        }
+
+        let mut message = message.unwrap_or_else(|| first.to_string());
+        if let Some(trace) = trace.filter(|trace| !trace.trim().is_empty()) {
+            message.push('\n');
+            message.push_str(trace);
+        }
+
+        Ok(Some(LogEntry {
+            timestamp,
+            severity,
+            message,
+            metadata,
+            raw: line.to_string(),
+            source: None,
+        }))
+    }
+
+    fn can_parse(&self, sample: &str) -> bool {
+        // Require a leading pair and two values so prose with one `a=b` is not logfmt
+        let sample = sample.trim();
+        sample
+            .split_whitespace()
+            .next()
+            .is_some_and(|first| first.contains('='))
+            && Self::pairs(sample).is_some_and(|pairs| {
+                pairs.iter().filter(|(_, value)| !value.is_empty()).count() >= 2
+            })
+    }
+
+    fn name(&self) -> &str {
+        "logfmt"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let parser = LogfmtParser::new();
+        let line = r#"ts=2025-11-17T10:30:00Z level=error caller=db.go:42 msg="query failed: \"users\" timed out" err="context deadline exceeded" attempt=3"#;
+
+        let entry = parser.parse_line(line).unwrap().unwrap();
+        assert_eq!(entry.severity, Severity::Error);
+        assert_eq!(entry.timestamp.unwrap().minute(), 30);
+        assert_eq!(entry.message, r#"query failed: "users" timed out"#);
+        assert_eq!(entry.metadata.file.as_deref(), Some("db.go"));
+        assert_eq!(entry.metadata.line, Some(42));
+        assert_eq!(entry.metadata.extra["err"], "context deadline exceeded");
+        assert_eq!(entry.metadata.extra["attempt"], "3");
+    }
+
+    #[test]
+    fn test_parse_epoch_and_bare_keys() {
+        let parser = LogfmtParser::new();
+
+        let entry = parser
+            .parse_line("t=1700000000.5 lvl=warn msg=retrying cached")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.severity, Severity::Warning);
+        assert_eq!(entry.timestamp.unwrap().timestamp(), 1_700_000_000);
+        assert_eq!(entry.message, "retrying");
+        assert_eq!(entry.metadata.extra["cached"], "");
+    }
+
+    #[test]
+    fn test_trace_after_logfmt_line() {
+        use crate::parser::StackTraceParser;
+        use std::sync::Arc;
+
+        let parser = StackTraceParser::new(Arc::new(LogfmtParser::new()));
+        let lines: Vec<String> = [
+            r#"ts=2025-11-17T10:30:00Z level=error msg="order lookup failed" order=17"#,
+            "Traceback (most recent call last):",
+            r#"  File "app.py", line 3, in <module>"#,
+            "    load(order)",
+            "KeyError: 'customer'",
+            r#"ts=2025-11-17T10:30:01Z level=info msg=recovered"#,
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let entries = parser.parse_lines(&lines).unwrap();
+        assert_eq!(entries.len(), 2);
+        let entry = &entries[0];
+        assert_eq!(entry.severity, Severity::Error);
+        assert!(entry.message.starts_with("order lookup failed\nTraceback"));
+        assert!(entry.message.contains(r#"File "app.py", line 3"#));
+        assert!(entry.message.ends_with("KeyError: 'customer'"));
+        assert_eq!(entry.metadata.extra["order"], "17");
+        assert_eq!(entry.metadata.extra.len(), 1);
+        let trace = crate::analyzer::StackTrace::parse(&entry.message).unwrap();
+        assert_eq!(trace.exception.as_deref(), Some("KeyError"));
+        assert_eq!(entries[1].message, "recovered");
+    }
+
+    #[test]
+    fn test_can_parse_logfmt() {
+        let parser = LogfmtParser::new();
+        assert!(parser.can_parse(r#"level=info msg="server started" port=8080"#));
+        assert!(!parser.can_parse("2025-11-17 ERROR retries=3 exhausted"));
+        assert!(!parser.can_parse("level=info only"));
+        assert!(!parser.can_parse(r#"msg="unterminated level=info"#));
+        assert!(!parser.can_parse(r#"{"level":"error","message":"test"}"#));
+    }
+}
@@ -1,13 +1,21 @@
 //! Format-specific log parsers
 
 pub mod apache;
+pub mod container;
+pub mod custom;
+pub mod journald;
 pub mod json;
+pub mod logfmt;
 pub mod nginx;
 pub mod plain;
 pub mod syslog;
 
 pub use apache::ApacheParser;
-pub use json::JsonParser;
+pub use container::{ContainerParser, ContainerRuntime};
+pub use custom::CustomParser;
+pub use journald::JournaldParser;
+pub use json::{JsonFieldMapping, JsonParser, JSON_PRESETS};
+pub use logfmt::LogfmtParser;
 pub use nginx::NginxParser;
 pub use plain::PlainTextParser;
 pub use syslog::SyslogParser;
@@ -134,6 +134,7 @@ impl LogParser for NginxParser {
                 message: format!("{} {}", status, request),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -167,6 +168,7 @@ impl LogParser for NginxParser {
                 message: message.to_string(),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -177,6 +179,10 @@ impl LogParser for NginxParser {
     fn can_parse(&self, sample: &str) -> bool {
         self.access_pattern.is_match(sample) || self.error_pattern.is_match(sample)
     }
+
+    fn name(&self) -> &str {
+        "nginx"
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +90,7 @@ impl LogParser for PlainTextParser {
             message,
             metadata,
             raw: line.to_string(),
+            source: None,
         }))
     }
 
@@ -97,6 +98,10 @@ impl LogParser for PlainTextParser {
         // Plain text parser is the fallback, always returns true
         true
     }
+
+    fn name(&self) -> &str {
+        "plain"
+    }
 }
 
 #[cfg(test)]
@@ -39,7 +39,7 @@ impl SyslogParser {
 
     /// Calculate severity from syslog priority
     /// Priority = Facility * 8 + Severity
-    fn priority_to_severity(priority: u8) -> Severity {
+    pub(crate) fn priority_to_severity(priority: u8) -> Severity {
         let sev = priority & 0x07; // Last 3 bits
         match sev {
             0..=3 => Severity::Error, // Emergency, Alert, Critical, Error
@@ -120,6 +120,7 @@ impl LogParser for SyslogParser {
                 message: message.to_string(),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -167,6 +168,7 @@ impl LogParser for SyslogParser {
                 message: message.to_string(),
                 metadata,
                 raw: line.to_string(),
+                source: None,
             }));
         }
 
@@ -177,6 +179,10 @@ impl LogParser for SyslogParser {
     fn can_parse(&self, sample: &str) -> bool {
         self.rfc3164_pattern.is_match(sample) || self.rfc5424_pattern.is_match(sample)
     }
+
+    fn name(&self) -> &str {
+        "syslog"
+    }
 }
 
 #[cfg(test)]
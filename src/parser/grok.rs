@@ -0,0 +1,157 @@
+//! Expansion of grok-style patterns (`%{LOGLEVEL:level}`) into regular expressions
+
+use crate::Result;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Nesting depth at which expansion gives up (guards against recursive definitions)
+const MAX_DEPTH: usize = 10;
+
+/// Built-in patterns, a subset of the Logstash grok library
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    ("USERNAME", r"[a-zA-Z0-9._-]+"),
+    ("USER", r"%{USERNAME}"),
+    ("INT", r"[+-]?\d+"),
+    ("POSINT", r"\b[1-9]\d*\b"),
+    ("NONNEGINT", r"\b\d+\b"),
+    ("NUMBER", r"[+-]?(?:\d+(?:\.\d+)?|\.\d+)"),
+    ("BASE16NUM", r"(?:0[xX])?[0-9A-Fa-f]+"),
+    ("WORD", r"\b\w+\b"),
+    ("NOTSPACE", r"\S+"),
+    ("SPACE", r"\s*"),
+    ("DATA", r".*?"),
+    ("GREEDYDATA", r".*"),
+    ("QUOTEDSTRING", r#""(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*'"#),
+    (
+        "UUID",
+        r"[A-Fa-f0-9]{8}-(?:[A-Fa-f0-9]{4}-){3}[A-Fa-f0-9]{12}",
+    ),
+    ("IPV4", r"(?:\d{1,3}\.){3}\d{1,3}"),
+    ("IPV6", r"[0-9A-Fa-f]{0,4}(?::[0-9A-Fa-f]{0,4}){2,7}"),
+    ("IP", r"(?:%{IPV6}|%{IPV4})"),
+    (
+        "HOSTNAME",
+        r"\b[0-9A-Za-z][0-9A-Za-z-]{0,62}(?:\.[0-9A-Za-z][0-9A-Za-z-]{0,62})*\b",
+    ),
+    ("IPORHOST", r"(?:%{IP}|%{HOSTNAME})"),
+    ("UNIXPATH", r"(?:/[\w_%!$@:.,+~-]*)+"),
+    ("PATH", r"%{UNIXPATH}"),
+    ("URIPATH", r"(?:/[A-Za-z0-9$.+!*'(){},~:;=@#%&_\-]*)+"),
+    (
+        "JAVACLASS",
+        r"(?:[a-zA-Z$_][a-zA-Z$_0-9]*\.)*[a-zA-Z$_][a-zA-Z$_0-9]*",
+    ),
+    (
+        "LOGLEVEL",
+        r"(?i:alert|trace|debug|notice|info|warn(?:ing)?|err(?:or)?|crit(?:ical)?|fatal|severe|emerg(?:ency)?)",
+    ),
+    (
+        "TIMESTAMP_ISO8601",
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?",
+    ),
+    (
+        "HTTPDATE",
+        r"\d{2}/[A-Za-z]{3}/\d{4}:\d{2}:\d{2}:\d{2} [+-]\d{4}",
+    ),
+    ("SYSLOGTIMESTAMP", r"[A-Za-z]{3} +\d{1,2} \d{2}:\d{2}:\d{2}"),
+];
+
+fn reference() -> &'static Regex {
+    static REFERENCE: OnceLock<Regex> = OnceLock::new();
+    // %{PATTERN}, %{PATTERN:field} or %{PATTERN:field:type} (the type is ignored)
+    REFERENCE.get_or_init(|| Regex::new(r"%\{(\w+)(?::(\w+))?(?::\w+)?\}").unwrap())
+}
+
+/// Expand the `%{NAME:field}` references in `pattern` into a regex with named
+/// captures, looking names up in `custom` before the built-in patterns
+pub fn expand(pattern: &str, custom: &HashMap<String, String>) -> Result<String> {
+    expand_at_depth(pattern, custom, 0)
+}
+
+fn expand_at_depth(
+    pattern: &str,
+    custom: &HashMap<String, String>,
+    depth: usize,
+) -> Result<String> {
+    if depth > MAX_DEPTH {
+        anyhow::bail!("Grok pattern nests more than {} levels", MAX_DEPTH);
+    }
+
+    let mut error = None;
+    let expanded = reference().replace_all(pattern, |caps: &Captures| {
+        let name = &caps[1];
+        let definition = custom.get(name).map(String::as_str).or_else(|| {
+            BUILTIN_PATTERNS
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, definition)| *definition)
+        });
+
+        let body = match definition {
+            Some(definition) => match expand_at_depth(definition, custom, depth + 1) {
+                Ok(body) => body,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    return String::new();
+                }
+            },
+            None => {
+                error.get_or_insert(anyhow::anyhow!("Unknown grok pattern '{}'", name));
+                return String::new();
+            }
+        };
+
+        match caps.get(2) {
+            Some(field) => format!("(?P<{}>{})", field.as_str(), body),
+            None => format!("(?:{})", body),
+        }
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_builtin_patterns() {
+        let pattern = expand(
+            "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} \\[%{NOTSPACE:thread}\\] %{GREEDYDATA:message}",
+            &HashMap::new(),
+        )
+        .unwrap();
+        let regex = Regex::new(&pattern).unwrap();
+
+        let caps = regex
+            .captures("2025-11-17 10:30:00,123 ERROR [worker-1] Payment failed")
+            .unwrap();
+        assert_eq!(&caps["ts"], "2025-11-17 10:30:00,123");
+        assert_eq!(&caps["level"], "ERROR");
+        assert_eq!(&caps["thread"], "worker-1");
+        assert_eq!(&caps["message"], "Payment failed");
+    }
+
+    #[test]
+    fn test_expand_custom_and_unknown_patterns() {
+        let mut custom = HashMap::new();
+        custom.insert("SERVICE".to_string(), "[a-z]+-%{INT}".to_string());
+
+        let pattern = expand("%{SERVICE:service}", &custom).unwrap();
+        let regex = Regex::new(&pattern).unwrap();
+        assert_eq!(
+            &regex.captures("billing-42").unwrap()["service"],
+            "billing-42"
+        );
+
+        assert!(expand("%{NOPE:x}", &HashMap::new()).is_err());
+
+        let mut recursive = HashMap::new();
+        recursive.insert("LOOP".to_string(), "%{LOOP}".to_string());
+        assert!(expand("%{LOOP}", &recursive).is_err());
+    }
+}
@@ -0,0 +1,527 @@
+//! Opening log inputs: compressed files, archives and rotated file sets
+
+use crate::Result;
+use anyhow::anyhow;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Extensions of compressed files, removed before a file name is examined
+const COMPRESSED_EXTENSIONS: &[&str] = &[".gz", ".zst", ".bz2", ".xz"];
+
+/// Extensions of archives whose log files are read
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    ".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst", ".tar.bz2", ".tbz2", ".tar.xz", ".txz", ".zip",
+];
+
+/// File in a directory listing patterns of paths to skip, like `.gitignore`
+pub const IGNORE_FILE: &str = ".logaiignore";
+
+/// Where a file sits in a set of rotated logs; sorts oldest first
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Rotation {
+    /// `app.log-20251117`, `app.log.2025-11-17`: earlier dates are older
+    Dated(String),
+    /// `app.log.3`: higher numbers are older
+    Numbered(Reverse<u32>),
+    /// The file currently written to
+    Current,
+}
+
+fn rotation_suffix() -> &'static Regex {
+    static SUFFIX: OnceLock<Regex> = OnceLock::new();
+    SUFFIX.get_or_init(|| {
+        Regex::new(
+            r"^(?P<base>.+?)(?:[-._](?P<date>\d{4}-?\d{2}-?\d{2}(?:[-_T]?\d{2,6})?)|\.(?P<n>\d{1,6}))$",
+        )
+        .unwrap()
+    })
+}
+
+/// `name` without its compression extension (`app.log.2.gz` is `app.log.2`)
+fn strip_compression(name: &str) -> &str {
+    COMPRESSED_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
+
+/// The name a rotated file was rotated from, and its place in the rotation
+fn rotation(name: &str) -> (&str, Rotation) {
+    let name = strip_compression(name);
+    let Some(caps) = rotation_suffix().captures(name) else {
+        return (name, Rotation::Current);
+    };
+    let base = caps.name("base").map_or(name, |m| m.as_str());
+    match (caps.name("date"), caps.name("n")) {
+        (Some(date), _) => (
+            base,
+            Rotation::Dated(date.as_str().replace(['-', '_', 'T'], "")),
+        ),
+        (None, Some(n)) => (
+            base,
+            Rotation::Numbered(Reverse(n.as_str().parse().unwrap_or(0))),
+        ),
+        (None, None) => (name, Rotation::Current),
+    }
+}
+
+fn rotation_key(path: &Path) -> (PathBuf, String, Rotation) {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (base, rotation) = rotation(&name);
+    let parent = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    (parent, base.to_string(), rotation)
+}
+
+/// Sort paths so that each set of rotated logs is in chronological order:
+/// `app.log.2.gz`, `app.log.1`, then `app.log`
+pub fn sort_rotated<P: AsRef<Path>>(paths: &mut [P]) {
+    paths.sort_by_cached_key(|path| rotation_key(path.as_ref()));
+}
+
+/// Whether `name` is a log file, possibly rotated or compressed
+/// (`app.log`, `app.log.1`, `app.log-20251117.gz`)
+pub fn is_log_file(name: &str) -> bool {
+    let (base, _) = rotation(name);
+    base.ends_with(".log")
+}
+
+/// Whether `name` is an archive (`.tar`, `.tar.gz`, `.zip`, ...)
+pub fn is_archive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+}
+
+/// Which files are read from directories and archives.
+///
+/// Patterns are globs matched against the path relative to the directory or
+/// archive being read. A pattern without a `/` matches at any depth
+/// (`*.log.*`), one starting with `/` only at the top (`/current/**`).
+/// Without include patterns, log files and archives are read.
+pub struct InputFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    recursive: bool,
+}
+
+impl InputFilter {
+    /// Create a filter from `--include` and `--exclude` globs
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: (!include.is_empty())
+                .then(|| glob_set(include))
+                .transpose()?,
+            exclude: glob_set(exclude)?,
+            recursive: false,
+        })
+    }
+
+    /// Also read files in subdirectories
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Whether the file at `relative` is read
+    pub fn selects(&self, relative: &str) -> bool {
+        if self.excludes(relative) {
+            return false;
+        }
+        match &self.include {
+            Some(include) => include.is_match(relative),
+            None => {
+                let name = relative.rsplit('/').next().unwrap_or(relative);
+                is_log_file(name) || is_archive(name)
+            }
+        }
+    }
+
+    /// Whether the file or directory at `relative` is skipped
+    pub fn excludes(&self, relative: &str) -> bool {
+        self.exclude.is_match(relative)
+    }
+}
+
+impl Default for InputFilter {
+    fn default() -> Self {
+        Self {
+            include: None,
+            exclude: GlobSet::empty(),
+            recursive: false,
+        }
+    }
+}
+
+/// Anchor a `.gitignore`-style pattern for matching relative paths
+fn normalize_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim_end_matches('/');
+    match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{}", pattern),
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(&normalize_pattern(pattern))
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Patterns from the ignore file in `dir`, if it has one
+fn read_ignore_file(dir: &Path) -> Result<Option<GlobSet>> {
+    let path = dir.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let patterns: Vec<String> = fs::read_to_string(&path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    Ok(Some(glob_set(&patterns)?))
+}
+
+/// The files under `dir` chosen by `filter`, oldest rotation first.
+///
+/// Paths matched by a `.logaiignore` file are skipped; its patterns apply to
+/// paths relative to the directory it is in. Symlinked directories are not
+/// followed.
+pub fn walk(dir: &Path, filter: &InputFilter) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut ignores = Vec::new();
+    visit(dir, "", filter, &mut ignores, &mut files)?;
+    sort_rotated(&mut files);
+    Ok(files)
+}
+
+fn visit(
+    dir: &Path,
+    prefix: &str,
+    filter: &InputFilter,
+    ignores: &mut Vec<(String, GlobSet)>,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let ignore = read_ignore_file(dir)?;
+    let has_ignore = ignore.is_some();
+    ignores.extend(ignore.map(|set| (prefix.to_string(), set)));
+
+    let entries = fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read directory '{}': {}", dir.display(), e))?;
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = format!("{}{}", prefix, name);
+        let ignored = ignores.iter().any(|(base, set)| {
+            relative
+                .strip_prefix(base.as_str())
+                .is_some_and(|rest| set.is_match(rest))
+        });
+        if name == IGNORE_FILE || ignored || filter.excludes(&relative) {
+            continue;
+        }
+
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if filter.recursive {
+                let nested = format!("{}/", relative);
+                if let Err(e) = visit(&path, &nested, filter, ignores, files) {
+                    eprintln!("⚠️  Warning: Skipping '{}': {}", path.display(), e);
+                }
+            }
+        } else if path.is_file() && filter.selects(&relative) {
+            files.push(path);
+        }
+    }
+
+    if has_ignore {
+        ignores.pop();
+    }
+    Ok(())
+}
+
+/// Wrap `reader` in a decoder when its content is gzip, zstd, bzip2 or xz
+/// compressed; other content is passed through unchanged
+pub fn decompress<'a, R: Read + 'a>(reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+
+    Ok(if magic.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::MultiGzDecoder::new(reader))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)
+    } else if magic.len() >= 4 && magic.starts_with(b"BZh") && (b'1'..=b'9').contains(&magic[3]) {
+        Box::new(bzip2::read::MultiBzDecoder::new(reader))
+    } else if magic.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+        Box::new(xz2::read::XzDecoder::new_multi_decoder(reader))
+    } else {
+        Box::new(reader)
+    })
+}
+
+/// Open the file at `path`, decompressing it if needed
+pub fn open(path: &Path) -> std::io::Result<Box<dyn Read>> {
+    decompress(File::open(path)?)
+}
+
+/// Read the files in the archive at `path` chosen by `filter`, oldest
+/// rotation first. Nested archives are skipped. Tar members are copied to a
+/// temporary directory first, so reading needs disk space rather than memory.
+///
+/// `read` is called with each file's path inside the archive and its
+/// decompressed content. Returns the number of files read.
+pub fn read_archive<F>(path: &Path, filter: &InputFilter, mut read: F) -> Result<usize>
+where
+    F: FnMut(&str, &mut dyn Read) -> Result<()>,
+{
+    let selects = |name: &str| {
+        let name = name.trim_start_matches("./");
+        !is_archive(name) && filter.selects(name)
+    };
+    let lower = path.to_string_lossy().to_lowercase();
+    if lower.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut names: Vec<String> = archive
+            .file_names()
+            .filter(|name| !name.ends_with('/') && selects(name))
+            .map(String::from)
+            .collect();
+        sort_rotated(&mut names);
+
+        for name in &names {
+            let file = archive.by_name(name)?;
+            read(name, &mut decompress(file)?)?;
+        }
+        return Ok(names.len());
+    }
+
+    // Tar entries can only be read in archive order, so log files are
+    // spilled to a temporary directory to read them oldest first without
+    // holding them in memory
+    let mut archive = tar::Archive::new(open(path)?);
+    let spill = tempfile::tempdir()?;
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        if selects(&name) {
+            let spilled = spill.path().join(files.len().to_string());
+            std::io::copy(&mut entry, &mut File::create(&spilled)?)?;
+            files.push((name, spilled));
+        }
+    }
+    files.sort_by_cached_key(|(name, _)| rotation_key(Path::new(name)));
+
+    for (name, spilled) in &files {
+        read(name, &mut decompress(File::open(spilled)?)?)?;
+    }
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn read_all(compressed: Vec<u8>) -> String {
+        let mut text = String::new();
+        decompress(compressed.as_slice())
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn test_decompress_by_content() {
+        let text = "2025-11-17 ERROR boom\n";
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(text.as_bytes()).unwrap();
+        assert_eq!(read_all(gzip.finish().unwrap()), text);
+
+        assert_eq!(
+            read_all(zstd::encode_all(text.as_bytes(), 0).unwrap()),
+            text
+        );
+
+        let mut bzip = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip.write_all(text.as_bytes()).unwrap();
+        assert_eq!(read_all(bzip.finish().unwrap()), text);
+
+        let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+        xz.write_all(text.as_bytes()).unwrap();
+        assert_eq!(read_all(xz.finish().unwrap()), text);
+
+        assert_eq!(read_all(text.as_bytes().to_vec()), text);
+    }
+
+    #[test]
+    fn test_sort_rotated_oldest_first() {
+        let mut paths = vec![
+            "/var/log/app.log",
+            "/var/log/app.log.1",
+            "/var/log/app.log.10.gz",
+            "/var/log/app.log.2.gz",
+            "/var/log/db.log",
+        ];
+        sort_rotated(&mut paths);
+        assert_eq!(
+            paths,
+            [
+                "/var/log/app.log.10.gz",
+                "/var/log/app.log.2.gz",
+                "/var/log/app.log.1",
+                "/var/log/app.log",
+                "/var/log/db.log",
+            ]
+        );
+
+        let mut dated = vec!["app.log", "app.log-20251118.zst", "app.log-20251117"];
+        sort_rotated(&mut dated);
+        assert_eq!(
+            dated,
+            ["app.log-20251117", "app.log-20251118.zst", "app.log"]
+        );
+    }
+
+    #[test]
+    fn test_is_log_file() {
+        for name in [
+            "app.log",
+            "app.log.1",
+            "app.log.2.gz",
+            "app.log-20251117.xz",
+        ] {
+            assert!(is_log_file(name), "{}", name);
+        }
+        for name in ["app.txt", "app.log.bak", "bundle.tar.gz", "logs/"] {
+            assert!(!is_log_file(name), "{}", name);
+        }
+        assert!(is_archive("bundle.TAR.GZ"));
+        assert!(is_archive("bundle.zip"));
+        assert!(!is_archive("app.log.gz"));
+    }
+
+    #[test]
+    fn test_read_tar_archive_in_rotation_order() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, text) in [
+            ("logs/app.log", "current\n"),
+            ("logs/app.log.1", "older\n"),
+            ("logs/notes.txt", "skipped\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(text.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, text.as_bytes())
+                .unwrap();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.tar");
+        std::fs::write(&path, builder.into_inner().unwrap()).unwrap();
+
+        let mut seen = Vec::new();
+        let count = read_archive(&path, &InputFilter::default(), |name, reader| {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            seen.push((name.to_string(), text));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            seen[0],
+            ("logs/app.log.1".to_string(), "older\n".to_string())
+        );
+        assert_eq!(seen[1].0, "logs/app.log");
+    }
+
+    #[test]
+    fn test_input_filter_globs() {
+        let filter = InputFilter::default();
+        assert!(filter.selects("app.log"));
+        assert!(filter.selects("nested/app.log.1.gz"));
+        assert!(filter.selects("bundle.tar.gz"));
+        assert!(!filter.selects("notes.txt"));
+
+        let filter = InputFilter::new(
+            &["**/*.log*".to_string(), "*.txt".to_string()],
+            &["debug/".to_string(), "/old.log".to_string()],
+        )
+        .unwrap();
+        assert!(filter.selects("api/app.log.2"));
+        assert!(filter.selects("api/notes.txt"));
+        assert!(!filter.selects("api/app.json"));
+        assert!(filter.excludes("api/debug"));
+        assert!(!filter.selects("old.log"));
+        assert!(filter.selects("api/old.log"));
+
+        assert!(InputFilter::new(&["[".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_walk_recursive_with_ignore_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for path in [
+            "app.log",
+            "app.log.1",
+            "notes.txt",
+            "api/api.log",
+            "api/tmp/scratch.log",
+            "vendor/lib.log",
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "ERROR boom\n").unwrap();
+        }
+        fs::write(root.join(IGNORE_FILE), "# generated\nvendor/\n").unwrap();
+        fs::write(root.join("api").join(IGNORE_FILE), "tmp\n").unwrap();
+
+        let relative = |files: Vec<PathBuf>| -> Vec<String> {
+            files
+                .iter()
+                .map(|f| {
+                    f.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+
+        let top = walk(root, &InputFilter::default()).unwrap();
+        assert_eq!(relative(top), ["app.log.1", "app.log"]);
+
+        let all = walk(root, &InputFilter::default().recursive(true)).unwrap();
+        assert_eq!(relative(all), ["app.log.1", "app.log", "api/api.log"]);
+
+        let filter = InputFilter::new(&[], &["app.log.*".to_string()])
+            .unwrap()
+            .recursive(true);
+        assert_eq!(
+            relative(walk(root, &filter).unwrap()),
+            ["app.log", "api/api.log"]
+        );
+    }
+}
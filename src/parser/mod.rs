@@ -1,22 +1,31 @@
 pub mod config;
+pub mod configured;
 pub mod detector;
 pub mod encoding;
 pub mod formats;
+pub mod grok;
+pub mod input;
 pub mod metadata;
 pub mod parallel;
 pub mod stack_trace;
 pub mod statistics;
+pub mod stream;
 pub mod timestamp;
 
 use crate::types::LogEntry;
 use crate::Result;
 
-pub use config::ParserConfig;
-pub use formats::{ApacheParser, JsonParser, NginxParser, PlainTextParser, SyslogParser};
+pub use config::{FormatDefinition, ParserConfig};
+pub use configured::ConfiguredParser;
+pub use formats::{
+    ApacheParser, ContainerParser, ContainerRuntime, CustomParser, JournaldParser, JsonParser,
+    LogfmtParser, NginxParser, PlainTextParser, SyslogParser,
+};
 pub use metadata::MetadataExtractor;
 pub use parallel::ParallelParser;
 pub use stack_trace::StackTraceParser;
 pub use statistics::{ParsingContext, ParsingStatistics};
+pub use stream::EntryStream;
 pub use timestamp::TimestampParser;
 
 /// Trait for log parsers
@@ -47,4 +56,20 @@ pub trait LogParser: Send + Sync {
     fn is_continuation_line(&self, _line: &str) -> bool {
         false
     }
+
+    /// Check if `line` was cut short and continues on the next line (e.g. a
+    /// record the container runtime split), so no entry can end after it
+    fn continues_on_next_line(&self, _line: &str) -> bool {
+        false
+    }
+
+    /// Most lines a multi-line entry keeps, when entries are capped
+    fn max_entry_lines(&self) -> Option<usize> {
+        None
+    }
+
+    /// Short name of the format handled by this parser (e.g., "json")
+    fn name(&self) -> &str {
+        "unknown"
+    }
 }
@@ -1,4 +1,5 @@
 use crate::parser::encoding::LossyLineReader;
+use crate::parser::stream::{parse_chunk, should_cut, split_chunks, EntryStream};
 use crate::parser::{LogParser, ParsingStatistics};
 use crate::types::LogEntry;
 use crate::Result;
@@ -9,12 +10,16 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
-/// Parser that processes log files in parallel using multiple threads
+/// Parser that processes log files in parallel using multiple threads.
+///
+/// Input is split into chunks only where a new entry starts, so multi-line
+/// entries (stack traces) are parsed exactly as a sequential parse would.
 pub struct ParallelParser {
     parser: Arc<dyn LogParser>,
     chunk_size: usize,
     num_threads: Option<usize>,
     streaming_threshold_bytes: u64,
+    multiline: bool,
 }
 
 impl ParallelParser {
@@ -40,28 +45,34 @@ impl ParallelParser {
         }
 
         Self {
+            multiline: parser.supports_multiline(),
             parser,
-            chunk_size,
+            chunk_size: chunk_size.max(1),
             num_threads,
             streaming_threshold_bytes,
         }
     }
 
+    /// Enable or disable merging of multi-line entries (on by default when the
+    /// parser supports it)
+    pub fn with_multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline && self.parser.supports_multiline();
+        self
+    }
+
+    /// Parse `lines` lazily, a batch of chunks at a time, in bounded memory
+    pub fn stream<I>(&self, lines: I) -> EntryStream<I>
+    where
+        I: Iterator<Item = std::io::Result<String>>,
+    {
+        EntryStream::new(Arc::clone(&self.parser), lines, self.multiline)
+            .with_chunk_size(self.chunk_size)
+            .with_parallel_chunks(self.parallel_chunks())
+    }
+
     /// Parse a file in parallel, returning all log entries
     pub fn parse_file(&self, path: &Path) -> Result<Vec<LogEntry>> {
-        let file = File::open(path)?;
-        let metadata = file.metadata()?;
-        let file_size = metadata.len();
-
-        // For large files (above threshold), use streaming approach
-        if file_size > self.streaming_threshold_bytes {
-            self.parse_streaming(file)
-        } else {
-            // For smaller files, load all lines and parse in parallel
-            let reader = LossyLineReader::new(file);
-            let lines: Vec<String> = reader.map_while(Result::ok).collect();
-            self.parse_parallel(&lines)
-        }
+        self.parse_file_with_stats(path).map(|(entries, _)| entries)
     }
 
     /// Parse a file in parallel and return statistics
@@ -74,9 +85,11 @@ impl ParallelParser {
         let metadata = file.metadata()?;
         let file_size = metadata.len();
 
+        // For large files (above threshold), use streaming approach
         let entries = if file_size > self.streaming_threshold_bytes {
             self.parse_streaming_with_tracking(file, &total_lines, &parse_errors)?
         } else {
+            // For smaller files, load all lines and parse in parallel
             let reader = LossyLineReader::new(file);
             let lines: Vec<String> = reader.map_while(Result::ok).collect();
             total_lines.store(lines.len(), Ordering::Relaxed);
@@ -98,19 +111,16 @@ impl ParallelParser {
         Ok((entries, stats))
     }
 
-    /// Parse lines in parallel using rayon
-    fn parse_parallel(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
-        let entries: Vec<LogEntry> = lines
-            .par_chunks(self.chunk_size)
-            .flat_map(|chunk| {
-                chunk
-                    .iter()
-                    .filter_map(|line| self.parser.parse_line(line).ok().flatten())
-                    .collect::<Vec<_>>()
-            })
-            .collect();
+    /// Number of chunks parsed at once: one per thread
+    fn parallel_chunks(&self) -> usize {
+        self.num_threads
+            .unwrap_or_else(rayon::current_num_threads)
+            .max(1)
+    }
 
-        Ok(entries)
+    /// Parse lines already in memory in parallel using rayon
+    pub fn parse_parallel(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
+        self.parse_parallel_with_tracking(lines, &AtomicUsize::new(0))
     }
 
     /// Parse lines in parallel with error tracking
@@ -119,53 +129,28 @@ impl ParallelParser {
         lines: &[String],
         parse_errors: &AtomicUsize,
     ) -> Result<Vec<LogEntry>> {
-        let entries: Vec<LogEntry> = lines
-            .par_chunks(self.chunk_size)
-            .flat_map(|chunk| {
-                chunk
+        let parser = self.parser.as_ref();
+        let entries: Vec<LogEntry> = split_chunks(parser, self.multiline, lines, self.chunk_size)
+            .into_par_iter()
+            .flat_map(|chunk| match parse_chunk(parser, self.multiline, chunk) {
+                Ok(entries) => entries,
+                // Fall back to single lines so one bad line only loses itself
+                Err(_) => chunk
                     .iter()
-                    .filter_map(|line| match self.parser.parse_line(line) {
-                        Ok(Some(entry)) => Some(entry),
-                        Ok(None) => None,
+                    .filter_map(|line| match parser.parse_line(line) {
+                        Ok(entry) => entry,
                         Err(_) => {
                             parse_errors.fetch_add(1, Ordering::Relaxed);
                             None
                         }
                     })
-                    .collect::<Vec<_>>()
+                    .collect(),
             })
             .collect();
 
         Ok(entries)
     }
 
-    /// Parse a large file using streaming to limit memory usage
-    fn parse_streaming(&self, file: File) -> Result<Vec<LogEntry>> {
-        let mut entries = Vec::new();
-        let mut buffer = Vec::with_capacity(self.chunk_size);
-        let reader = LossyLineReader::new(file);
-
-        for line in reader {
-            let line = line?;
-            buffer.push(line);
-
-            if buffer.len() >= self.chunk_size {
-                // Process chunk in parallel
-                let chunk_entries = self.parse_parallel(&buffer)?;
-                entries.extend(chunk_entries);
-                buffer.clear();
-            }
-        }
-
-        // Process remaining lines
-        if !buffer.is_empty() {
-            let chunk_entries = self.parse_parallel(&buffer)?;
-            entries.extend(chunk_entries);
-        }
-
-        Ok(entries)
-    }
-
     /// Parse a large file using streaming with statistics tracking
     fn parse_streaming_with_tracking(
         &self,
@@ -174,26 +159,31 @@ impl ParallelParser {
         parse_errors: &AtomicUsize,
     ) -> Result<Vec<LogEntry>> {
         let mut entries = Vec::new();
-        let mut buffer = Vec::with_capacity(self.chunk_size);
+        let batch_size = self.chunk_size * self.parallel_chunks();
+        let mut buffer = Vec::with_capacity(batch_size);
         let reader = LossyLineReader::new(file);
 
         for line in reader {
             let line = line?;
-            buffer.push(line);
             total_lines.fetch_add(1, Ordering::Relaxed);
 
-            if buffer.len() >= self.chunk_size {
-                // Process chunk in parallel
-                let chunk_entries = self.parse_parallel_with_tracking(&buffer, parse_errors)?;
-                entries.extend(chunk_entries);
+            // Process a batch of chunks in parallel, cut where an entry starts
+            if should_cut(
+                self.parser.as_ref(),
+                self.multiline,
+                &buffer,
+                batch_size,
+                &line,
+            ) {
+                entries.extend(self.parse_parallel_with_tracking(&buffer, parse_errors)?);
                 buffer.clear();
             }
+            buffer.push(line);
         }
 
         // Process remaining lines
         if !buffer.is_empty() {
-            let chunk_entries = self.parse_parallel_with_tracking(&buffer, parse_errors)?;
-            entries.extend(chunk_entries);
+            entries.extend(self.parse_parallel_with_tracking(&buffer, parse_errors)?);
         }
 
         Ok(entries)
@@ -306,4 +296,48 @@ mod tests {
         assert_eq!(stats.total_lines, 10000);
         assert_eq!(stats.parsed_entries, 10000);
     }
+
+    #[test]
+    fn test_parallel_parser_keeps_stack_traces_whole() {
+        use crate::parser::StackTraceParser;
+
+        let parser: Arc<dyn LogParser> =
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new())));
+        let mut lines = Vec::new();
+        for i in 0..300 {
+            lines.push(format!("INFO Processing item {}", i));
+            lines.push("java.lang.NullPointerException: Cannot invoke method".to_string());
+            lines.push("    at com.example.Service.process(Service.java:123)".to_string());
+            lines.push("    at com.example.Main.main(Main.java:10)".to_string());
+        }
+
+        let expected = parser.parse_lines(&lines).unwrap();
+
+        // A chunk size that does not divide the input would tear traces if
+        // chunks were cut at fixed offsets
+        let parallel_parser = ParallelParser::new(Arc::clone(&parser), 7, None);
+        let entries = parallel_parser.parse_parallel(&lines).unwrap();
+        assert_eq!(entries.len(), 600);
+        assert_eq!(
+            entries.iter().map(|e| &e.message).collect::<Vec<_>>(),
+            expected.iter().map(|e| &e.message).collect::<Vec<_>>()
+        );
+
+        // Streaming mode gives the same result
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "{}", lines.join("\n")).unwrap();
+        let streaming = ParallelParser::with_threshold(parser, 7, None, 1024);
+        let (entries, stats) = streaming.parse_file_with_stats(temp_file.path()).unwrap();
+        assert_eq!(entries.len(), 600);
+        assert_eq!(stats.multiline_entries, 300);
+
+        // Disabling multi-line parsing yields one entry per line
+        let per_line = ParallelParser::new(
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new()))),
+            7,
+            None,
+        )
+        .with_multiline(false);
+        assert_eq!(per_line.parse_parallel(&lines).unwrap().len(), 1200);
+    }
 }
@@ -2,16 +2,64 @@ use super::LogParser;
 use crate::types::LogEntry;
 use crate::Result;
 use regex::Regex;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+/// Stack trace line patterns, shared by multi-line parsing and stack trace
+/// fingerprinting (`analyzer::stack`)
+pub struct FramePatterns {
+    /// Java, Kotlin, C#: `at com.shop.Orders.load(Orders.java:42)`
+    pub dotted: Regex,
+    /// JavaScript: `at load (/app/orders.js:42:7)`, `at /app/orders.js:42:7`
+    pub javascript: Regex,
+    /// Python: `File "/app/orders.py", line 42, in load`
+    pub python: Regex,
+    /// Rust backtrace: `  3: shop::orders::load::h0123456789abcdef`
+    pub rust: Regex,
+    /// Go: `main.(*Orders).load(0xc000010000)` after a `goroutine N` line
+    pub go: Regex,
+    /// Go: `goroutine 1 [running]:`
+    pub goroutine: Regex,
+    /// Line that introduces or ends a trace: `Traceback (most recent call
+    /// last):`, `java.lang.NullPointerException: ...`, `KeyError: 'id'`,
+    /// `panic: ...`
+    pub header: Regex,
+}
+
+impl FramePatterns {
+    /// Whether `line` is a stack frame in one of the supported languages
+    pub fn is_frame(&self, line: &str) -> bool {
+        self.dotted.is_match(line)
+            || self.javascript.is_match(line)
+            || self.python.is_match(line)
+            || self.rust.is_match(line)
+            || self.go.is_match(line)
+            || self.goroutine.is_match(line)
+    }
+}
+
+/// The shared [`FramePatterns`]
+pub fn frame_patterns() -> &'static FramePatterns {
+    static PATTERNS: OnceLock<FramePatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| FramePatterns {
+        dotted: Regex::new(r"^\s*at\s+([\w.$<>`\[\]+/-]+)\(").unwrap(),
+        javascript: Regex::new(
+            r"^\s*at\s+(?:(?:async\s+)?(\S.*?)\s+\()?((?:node:)?[^()\s]+?\.(?:[cm]?js|jsx|tsx?)|node:[^()\s]+?):\d+:\d+\)?\s*$",
+        )
+        .unwrap(),
+        python: Regex::new(r#"^\s*File\s+"([^"]+)",\s+line\s+\d+,\s+in\s+(\S+)"#).unwrap(),
+        rust: Regex::new(r"^\s*\d+:\s+(?:0x[0-9a-f]+\s+-\s+)?(\S+::\S+)\s*$").unwrap(),
+        go: Regex::new(r"^([\w./*()-]+\.[\w*()-]+)\(.*\)$").unwrap(),
+        goroutine: Regex::new(r"^goroutine\s+\d+").unwrap(),
+        header: Regex::new(
+            r"^(?:Traceback \(most recent call last\):|panic: |(?:[A-Za-z_$][\w$]*\.)*[\w$]*(?:Exception|Error|Throwable|Fault|Interrupt|Exit|Iteration)(?::|$))",
+        )
+        .unwrap(),
+    })
+}
 
 /// Wrapper parser that handles multi-line stack traces
 pub struct StackTraceParser {
     inner: Arc<dyn LogParser>,
-    java_pattern: Regex,
-    python_pattern: Regex,
-    javascript_pattern: Regex,
-    csharp_pattern: Regex,
-    go_pattern: Regex,
     error_start_pattern: Regex,
 }
 
@@ -20,16 +68,6 @@ impl StackTraceParser {
     pub fn new(inner: Arc<dyn LogParser>) -> Self {
         Self {
             inner,
-            // Java: "at com.example.Class.method(File.java:123)"
-            java_pattern: Regex::new(r"^\s*at\s+[\w.$]+\(").unwrap(),
-            // Python: "  File "script.py", line 123, in function"
-            python_pattern: Regex::new(r#"^\s+File\s+"[^"]+",\s+line\s+\d+"#).unwrap(),
-            // JavaScript: "    at Object.<anonymous> (/path/file.js:123:45)"
-            javascript_pattern: Regex::new(r"^\s+at\s+.*\.js:\d+").unwrap(),
-            // C#: "   at Namespace.Class.Method() in File.cs:line 123"
-            csharp_pattern: Regex::new(r"^\s+at\s+.*\sin\s+.*\.cs:line\s+\d+").unwrap(),
-            // Go: "goroutine 1 [running]:" or "\tmain.function()"
-            go_pattern: Regex::new(r"^(?:goroutine\s+\d+|\t)").unwrap(),
             // Error start patterns: "Exception", "Error", "Traceback"
             error_start_pattern: Regex::new(
                 r"(?i)(?:Exception|Error|Traceback|Panic|Fatal|Caused by:)",
@@ -40,11 +78,7 @@ impl StackTraceParser {
 
     /// Check if a line is a stack trace continuation
     fn is_stack_trace_line(&self, line: &str) -> bool {
-        self.java_pattern.is_match(line)
-            || self.python_pattern.is_match(line)
-            || self.javascript_pattern.is_match(line)
-            || self.csharp_pattern.is_match(line)
-            || self.go_pattern.is_match(line)
+        frame_patterns().is_frame(line)
             || line.trim_start().starts_with("at ")
             || (line.starts_with('\t') || line.starts_with("  ")) && !line.trim().is_empty()
     }
@@ -53,6 +87,17 @@ impl StackTraceParser {
     fn is_error_start(&self, line: &str) -> bool {
         self.error_start_pattern.is_match(line)
     }
+
+    /// Parse the buffered lines as one entry
+    fn flush(&self, buffer: &mut Vec<String>, entries: &mut Vec<LogEntry>) -> Result<()> {
+        if !buffer.is_empty() {
+            if let Some(entry) = self.inner.parse_line(&buffer.join("\n"))? {
+                entries.push(entry);
+            }
+            buffer.clear();
+        }
+        Ok(())
+    }
 }
 
 impl LogParser for StackTraceParser {
@@ -61,9 +106,11 @@ impl LogParser for StackTraceParser {
     }
 
     fn parse_lines(&self, lines: &[String]) -> Result<Vec<LogEntry>> {
+        let patterns = frame_patterns();
         let mut entries = Vec::new();
         let mut buffer = Vec::new();
         let mut in_stack_trace = false;
+        let mut python = false;
 
         for line in lines {
             let trimmed = line.trim();
@@ -78,18 +125,29 @@ impl LogParser for StackTraceParser {
 
             // Check if this is a stack trace continuation
             if in_stack_trace && self.is_stack_trace_line(line) {
+                python |= patterns.python.is_match(line);
                 buffer.push(line.clone());
                 continue;
             }
 
-            // If we were in a stack trace and this isn't a continuation, flush the buffer
-            if in_stack_trace && !buffer.is_empty() {
-                let combined = buffer.join("\n");
-                if let Some(entry) = self.inner.parse_line(&combined)? {
-                    entries.push(entry);
+            if in_stack_trace {
+                // A trace printed right after the log line reporting it
+                if buffer.len() == 1 && patterns.header.is_match(line) {
+                    buffer.push(line.clone());
+                    continue;
+                }
+
+                // Python prints the exception after the frames
+                let exception = python && patterns.header.is_match(line);
+                if exception {
+                    buffer.push(line.clone());
                 }
-                buffer.clear();
+                self.flush(&mut buffer, &mut entries)?;
                 in_stack_trace = false;
+                python = false;
+                if exception {
+                    continue;
+                }
             }
 
             // Check if this line starts a new error/stack trace
@@ -106,12 +164,7 @@ impl LogParser for StackTraceParser {
         }
 
         // Flush any remaining buffered stack trace
-        if !buffer.is_empty() {
-            let combined = buffer.join("\n");
-            if let Some(entry) = self.inner.parse_line(&combined)? {
-                entries.push(entry);
-            }
-        }
+        self.flush(&mut buffer, &mut entries)?;
 
         Ok(entries)
     }
@@ -124,8 +177,18 @@ impl LogParser for StackTraceParser {
         true
     }
 
+    /// Trace headers can belong to the line before them (see `parse_lines`),
+    /// so chunks never start at one either
     fn is_continuation_line(&self, line: &str) -> bool {
-        self.is_stack_trace_line(line)
+        self.is_stack_trace_line(line) || frame_patterns().header.is_match(line)
+    }
+
+    fn continues_on_next_line(&self, line: &str) -> bool {
+        self.inner.continues_on_next_line(line)
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
     }
 }
 
@@ -171,6 +234,35 @@ mod tests {
         assert!(entry.message.contains("script.py"));
     }
 
+    #[test]
+    fn test_trace_after_log_line() {
+        let parser = StackTraceParser::new(Arc::new(PlainTextParser::new()));
+
+        let lines: Vec<String> = [
+            "2025-11-17 10:00:00 ERROR Unhandled exception in /orders",
+            "Traceback (most recent call last):",
+            r#"  File "/app/shop/orders.py", line 42, in load"#,
+            "    return orders[order_id]",
+            "KeyError: 'customer'",
+            "2025-11-17 10:00:01 ERROR Request failed",
+            "java.lang.NullPointerException: order is null",
+            "\tat com.shop.Orders.load(Orders.java:42)",
+            "2025-11-17 10:00:02 INFO Recovered",
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+
+        let result = parser.parse_lines(&lines).unwrap();
+        assert_eq!(result.len(), 3);
+        assert!(result[0].message.starts_with("2025-11-17 10:00:00 ERROR"));
+        assert!(result[0].message.ends_with("KeyError: 'customer'"));
+        assert!(result[1]
+            .message
+            .contains("\njava.lang.NullPointerException: order is null\n"));
+        assert!(result[2].message.contains("Recovered"));
+    }
+
     #[test]
     fn test_javascript_stack() {
         let parser = StackTraceParser::new(Arc::new(PlainTextParser::new()));
@@ -0,0 +1,491 @@
+//! Streaming parse of a line source.
+//!
+//! `EntryStream` reads lines lazily and parses them one chunk at a time, so
+//! memory use is bounded by the chunk size rather than the input size. Chunks
+//! are only cut where a new entry starts, and never after a line that
+//! continues on the next one (such as a record the container runtime split).
+//! This keeps multi-line entries such as stack traces intact and gives the
+//! same entries as parsing all lines at once. With several chunks per batch, the chunks of a batch are parsed in
+//! parallel and their entries returned in input order.
+//!
+//! A chunk grows past the chunk size until the multi-line entry it ends with
+//! is complete, but by no more than the parser's `max_entry_lines`
+//! ([`UNCAPPED_ENTRY_LINES`] for parsers that do not cap entries). Past that
+//! bound a chunk is cut at the next whole line regardless, so input that
+//! never starts an entry (e.g. only indented lines) is not buffered whole. An entry cut this way has already
+//! reached the lines the parser keeps; the rest are parsed on their own.
+
+use super::LogParser;
+use crate::types::LogEntry;
+use crate::Result;
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// Lines parsed per chunk unless configured otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
+/// Lines a chunk may grow past the chunk size to finish an entry, for
+/// parsers that do not cap entries
+pub const UNCAPPED_ENTRY_LINES: usize = 1000;
+
+/// Check if `line` starts a new entry, i.e. a chunk may begin with it
+pub fn is_entry_start(parser: &dyn LogParser, line: &str) -> bool {
+    !line.trim().is_empty() && !parser.is_continuation_line(line)
+}
+
+/// Check if `chunk` should end before `next_line`
+pub(crate) fn should_cut(
+    parser: &dyn LogParser,
+    multiline: bool,
+    chunk: &[String],
+    chunk_size: usize,
+    next_line: &str,
+) -> bool {
+    let len = chunk.len();
+    if len < chunk_size
+        || chunk
+            .last()
+            .is_some_and(|line| parser.continues_on_next_line(line))
+    {
+        return false;
+    }
+    let limit = chunk_size + parser.max_entry_lines().unwrap_or(UNCAPPED_ENTRY_LINES);
+    !multiline || len >= limit || is_entry_start(parser, next_line)
+}
+
+/// Split `lines` into chunks of about `chunk_size` lines without tearing
+/// multi-line entries
+pub(crate) fn split_chunks<'a>(
+    parser: &dyn LogParser,
+    multiline: bool,
+    lines: &'a [String],
+    chunk_size: usize,
+) -> Vec<&'a [String]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        if should_cut(parser, multiline, &lines[start..i], chunk_size, line) {
+            chunks.push(&lines[start..i]);
+            start = i;
+        }
+    }
+    if start < lines.len() {
+        chunks.push(&lines[start..]);
+    }
+
+    chunks
+}
+
+/// Parse one chunk, returning its entries and the number of lines that
+/// should have started an entry but did not parse
+fn parse_counted(
+    parser: &dyn LogParser,
+    multiline: bool,
+    chunk: &[String],
+) -> Result<(Vec<LogEntry>, usize)> {
+    let entries = parse_chunk(parser, multiline, chunk)?;
+    let starts = chunk
+        .iter()
+        .filter(|line| {
+            if multiline {
+                is_entry_start(parser, line)
+            } else {
+                !line.trim().is_empty()
+            }
+        })
+        .count();
+    let invalid = starts.saturating_sub(entries.len());
+    Ok((entries, invalid))
+}
+
+/// Parse one chunk, merging multi-line entries when `multiline` is set
+pub(crate) fn parse_chunk(
+    parser: &dyn LogParser,
+    multiline: bool,
+    chunk: &[String],
+) -> Result<Vec<LogEntry>> {
+    if multiline {
+        return parser.parse_lines(chunk);
+    }
+
+    let mut entries = Vec::with_capacity(chunk.len());
+    for line in chunk {
+        if let Some(entry) = parser.parse_line(line)? {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Iterator of entries parsed lazily from an iterator of lines
+pub struct EntryStream<I> {
+    parser: Arc<dyn LogParser>,
+    lines: I,
+    multiline: bool,
+    chunk_size: usize,
+    /// Chunks read and parsed in parallel at a time
+    parallel_chunks: usize,
+    /// First line of the next chunk, read while looking for a boundary
+    carry: Option<String>,
+    ready: std::vec::IntoIter<LogEntry>,
+    total_lines: usize,
+    parsed_entries: usize,
+    parse_errors: usize,
+    max_parse_errors: Option<usize>,
+    done: bool,
+}
+
+impl<I> EntryStream<I>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    /// Create a stream parsing `lines` with `parser`.
+    ///
+    /// With `multiline` set, parsers that support it group continuation lines
+    /// into a single entry.
+    pub fn new(parser: Arc<dyn LogParser>, lines: I, multiline: bool) -> Self {
+        Self {
+            multiline: multiline && parser.supports_multiline(),
+            parser,
+            lines,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            parallel_chunks: 1,
+            carry: None,
+            ready: Vec::new().into_iter(),
+            total_lines: 0,
+            parsed_entries: 0,
+            parse_errors: 0,
+            max_parse_errors: None,
+            done: false,
+        }
+    }
+
+    /// Set the number of lines parsed at a time
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Parse up to `parallel_chunks` chunks at a time on the rayon pool.
+    ///
+    /// Entries are the same, and in the same order, as with sequential parsing.
+    pub fn with_parallel_chunks(mut self, parallel_chunks: usize) -> Self {
+        self.parallel_chunks = parallel_chunks.max(1);
+        self
+    }
+
+    /// Fail once more than `max_parse_errors` lines could not be parsed
+    pub fn with_max_parse_errors(mut self, max_parse_errors: Option<usize>) -> Self {
+        self.max_parse_errors = max_parse_errors;
+        self
+    }
+
+    /// Lines read so far
+    pub fn total_lines(&self) -> usize {
+        self.total_lines
+    }
+
+    /// Entries produced so far
+    pub fn parsed_entries(&self) -> usize {
+        self.parsed_entries
+    }
+
+    /// Lines so far that should have started an entry but could not be parsed
+    pub fn parse_errors(&self) -> usize {
+        self.parse_errors
+    }
+
+    /// Read and parse the next batch, returning false once the input is exhausted
+    fn fill(&mut self) -> Result<bool> {
+        let batch_size = self.chunk_size * self.parallel_chunks;
+        let mut batch: Vec<String> = self.carry.take().into_iter().collect();
+
+        while !self.done {
+            let Some(line) = self.lines.next() else {
+                self.done = true;
+                break;
+            };
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    self.done = true;
+                    return Err(e.into());
+                }
+            };
+            self.total_lines += 1;
+
+            if should_cut(
+                self.parser.as_ref(),
+                self.multiline,
+                &batch,
+                batch_size,
+                &line,
+            ) {
+                self.carry = Some(line);
+                break;
+            }
+            batch.push(line);
+        }
+
+        if batch.is_empty() {
+            return Ok(false);
+        }
+
+        let parser = self.parser.as_ref();
+        let (entries, invalid) = if self.parallel_chunks > 1 {
+            split_chunks(parser, self.multiline, &batch, self.chunk_size)
+                .into_par_iter()
+                .map(|chunk| parse_counted(parser, self.multiline, chunk))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .fold((Vec::new(), 0), |(mut all, errors), (entries, invalid)| {
+                    all.extend(entries);
+                    (all, errors + invalid)
+                })
+        } else {
+            parse_counted(parser, self.multiline, &batch)?
+        };
+
+        self.parse_errors += invalid;
+        if let Some(max) = self.max_parse_errors {
+            if self.parse_errors > max {
+                self.done = true;
+                self.carry = None;
+                return Err(anyhow::anyhow!(
+                    "Stopped after {} unparseable lines (limit is {})",
+                    self.parse_errors,
+                    max
+                ));
+            }
+        }
+
+        self.parsed_entries += entries.len();
+        self.ready = entries.into_iter();
+        Ok(true)
+    }
+}
+
+impl<I> Iterator for EntryStream<I>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    type Item = Result<LogEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.ready.next() {
+                return Some(Ok(entry));
+            }
+            if self.done && self.carry.is_none() {
+                return None;
+            }
+            match self.fill() {
+                Ok(true) => continue,
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::formats::PlainTextParser;
+    use crate::parser::{ConfiguredParser, ParserConfig, StackTraceParser};
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    fn stream(
+        parser: Arc<dyn LogParser>,
+        lines: &[String],
+        chunk_size: usize,
+    ) -> EntryStream<impl Iterator<Item = std::io::Result<String>> + '_> {
+        EntryStream::new(parser, lines.iter().cloned().map(Ok), true).with_chunk_size(chunk_size)
+    }
+
+    #[test]
+    fn test_stream_matches_whole_input_parse() {
+        let parser: Arc<dyn LogParser> =
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new())));
+        let input = lines(
+            "INFO Starting application\n\
+             ERROR java.lang.IllegalStateException: boom\n    \
+             at com.example.Service.process(Service.java:10)\n    \
+             at com.example.Main.main(Main.java:5)\n\
+             INFO Recovered\n\
+             Traceback (most recent call last):\n  \
+             File \"app.py\", line 3, in main\n\
+             ValueError: bad value\n\
+             INFO Done",
+        );
+        let expected: Vec<String> = parser
+            .parse_lines(&input)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+
+        // Every chunk size must cut around the stack traces, not through them
+        for chunk_size in 1..=input.len() {
+            let messages: Vec<String> = stream(Arc::clone(&parser), &input, chunk_size)
+                .map(|e| e.unwrap().message)
+                .collect();
+            assert_eq!(messages, expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn test_parallel_stream_matches_sequential() {
+        let parser: Arc<dyn LogParser> =
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new())));
+        let mut input = Vec::new();
+        for i in 0..200 {
+            input.push(format!("INFO request {}", i));
+            if i % 3 == 0 {
+                input.push("Error: Connection failed".to_string());
+                input.push("    at Object.<anonymous> (/app/server.js:123:45)".to_string());
+            }
+        }
+
+        let sequential: Vec<String> = stream(Arc::clone(&parser), &input, 7)
+            .map(|e| e.unwrap().message)
+            .collect();
+        let parallel: Vec<String> = stream(parser, &input, 7)
+            .with_parallel_chunks(4)
+            .map(|e| e.unwrap().message)
+            .collect();
+
+        assert_eq!(sequential.len(), 267);
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_split_chunks_at_entry_starts() {
+        let parser = StackTraceParser::new(Arc::new(PlainTextParser::new()));
+        let input = lines(
+            "ERROR one\n    at a()\n    at b()\nINFO two\nINFO three\nERROR four\n    at c()",
+        );
+
+        let chunks = split_chunks(&parser, true, &input, 2);
+        let sizes: Vec<_> = chunks.iter().map(|c| c.len()).collect();
+        assert_eq!(sizes, vec![3, 2, 2]);
+        assert!(chunks.iter().all(|c| is_entry_start(&parser, &c[0])));
+
+        // Without multi-line handling any line can start a chunk
+        let sizes: Vec<_> = split_chunks(&parser, false, &input, 2)
+            .iter()
+            .map(|c| c.len())
+            .collect();
+        assert_eq!(sizes, vec![2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_stream_counts_lines_and_entries() {
+        let input = lines("Line 1\n\nLine 2\nLine 3");
+        let mut stream = EntryStream::new(
+            Arc::new(PlainTextParser::new()),
+            input.iter().cloned().map(Ok),
+            false,
+        )
+        .with_chunk_size(2);
+
+        assert_eq!(stream.by_ref().count(), 3);
+        assert_eq!(stream.total_lines(), 4);
+        assert_eq!(stream.parsed_entries(), 3);
+    }
+
+    #[test]
+    fn test_stream_keeps_long_entries_whole() {
+        let parser: Arc<dyn LogParser> = Arc::new(ConfiguredParser::new(
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new()))),
+            &ParserConfig {
+                max_multiline_lines: 50,
+                ..ParserConfig::default()
+            },
+        ));
+        let mut input = Vec::new();
+        for i in 0..20 {
+            input.push(format!("INFO request {}", i));
+        }
+        input.push("ERROR java.lang.StackOverflowError: deep recursion".to_string());
+        input.extend((0..55).map(|i| format!("    at com.example.Tree.walk{}(Tree.java:1)", i)));
+        input.push("INFO Recovered".to_string());
+
+        let expected: Vec<String> = parser
+            .parse_lines(&input)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(expected.len(), 22);
+
+        // The trace is far longer than a chunk, but not torn; the parser caps
+        // it instead
+        let sequential: Vec<String> = stream(Arc::clone(&parser), &input, 10)
+            .map(|e| e.unwrap().message)
+            .collect();
+        let parallel: Vec<String> = stream(parser, &input, 10)
+            .with_parallel_chunks(4)
+            .map(|e| e.unwrap().message)
+            .collect();
+        assert_eq!(sequential, expected);
+        assert_eq!(parallel, expected);
+        assert_eq!(sequential[20].lines().count(), 50);
+    }
+
+    #[test]
+    fn test_stream_bounds_chunks_without_entry_starts() {
+        let parser: Arc<dyn LogParser> = Arc::new(ConfiguredParser::new(
+            Arc::new(StackTraceParser::new(Arc::new(PlainTextParser::new()))),
+            &ParserConfig {
+                max_multiline_lines: 50,
+                ..ParserConfig::default()
+            },
+        ));
+        let input: Vec<String> = (0..10_000).map(|i| format!("  indented {}", i)).collect();
+
+        // Count the lines read before the first entry comes out
+        let read = std::cell::Cell::new(0);
+        let lines = input.iter().cloned().inspect(|_| read.set(read.get() + 1));
+        let mut stream = EntryStream::new(parser, lines.map(Ok), true).with_chunk_size(10);
+        assert!(stream.next().is_some());
+        assert!(read.get() <= 10 + 50 + 1, "read {} lines", read.get());
+
+        assert_eq!(stream.count() + 1, input.len());
+    }
+
+    #[test]
+    fn test_stream_error_budget() {
+        let parser: Arc<dyn LogParser> = Arc::new(crate::parser::formats::JsonParser::new());
+        let input = lines("{\"message\": \"ok\"}\nnot json\n{\"message\": \"ok\"}\nstill not json");
+
+        let mut unlimited =
+            EntryStream::new(Arc::clone(&parser), input.iter().cloned().map(Ok), true);
+        assert_eq!(unlimited.by_ref().filter(|e| e.is_ok()).count(), 2);
+        assert_eq!(unlimited.parse_errors(), 2);
+
+        let results: Vec<_> = EntryStream::new(parser, input.iter().cloned().map(Ok), true)
+            .with_chunk_size(1)
+            .with_max_parse_errors(Some(1))
+            .collect();
+        let error = results.last().unwrap().as_ref().unwrap_err();
+        assert!(error.to_string().contains("2 unparseable lines"));
+    }
+
+    #[test]
+    fn test_stream_propagates_read_errors() {
+        let input = vec![
+            Ok("Line 1".to_string()),
+            Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "bad")),
+        ];
+        let results: Vec<_> =
+            EntryStream::new(Arc::new(PlainTextParser::new()), input.into_iter(), false).collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+}
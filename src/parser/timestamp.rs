@@ -1,5 +1,5 @@
 use crate::Result;
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use regex::Regex;
 
 /// Parser for various timestamp formats
@@ -37,6 +37,43 @@ impl TimestampParser {
         }
     }
 
+    /// Create a timestamp parser from chrono format strings (e.g. `%d/%m/%Y %H:%M:%S`),
+    /// deriving the pattern that locates each format in a line
+    pub fn with_format_strings(formats: &[String]) -> Self {
+        Self::with_custom_formats(
+            formats
+                .iter()
+                .map(|format| (pattern_for_format(format), format.clone()))
+                .collect(),
+        )
+    }
+
+    /// Check if any custom formats are configured
+    pub fn has_custom_formats(&self) -> bool {
+        !self.custom_patterns.is_empty()
+    }
+
+    /// Find and parse the first custom-format timestamp inside `line`
+    pub fn find(&self, line: &str) -> Option<DateTime<Utc>> {
+        self.custom_patterns.iter().find_map(|(pattern, format)| {
+            let text = pattern.find(line)?.as_str();
+            DateTime::parse_from_str(text, format)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+                .or_else(|| {
+                    NaiveDateTime::parse_from_str(text, format)
+                        .ok()
+                        .map(|dt| Utc.from_utc_datetime(&dt))
+                })
+                .or_else(|| {
+                    NaiveDate::parse_from_str(text, format)
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                        .map(|dt| Utc.from_utc_datetime(&dt))
+                })
+        })
+    }
+
     /// Parse a timestamp string into a DateTime<Utc>
     pub fn parse(&self, text: &str) -> Result<DateTime<Utc>> {
         let text = text.trim();
@@ -116,6 +153,57 @@ impl TimestampParser {
     }
 }
 
+/// Build a regex that matches text produced by a chrono format string
+pub fn pattern_for_format(format: &str) -> String {
+    let mut pattern = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            pattern.push_str(&regex::escape(&c.to_string()));
+            continue;
+        }
+
+        // Skip padding modifiers such as %-d or %_H
+        while matches!(chars.peek(), Some('-' | '_' | '0')) {
+            chars.next();
+        }
+        let piece = match chars.next() {
+            Some('Y') => r"\d{4}",
+            Some('y' | 'C') => r"\d{2}",
+            Some('m' | 'd' | 'H' | 'I' | 'M' | 'S') => r"\d{1,2}",
+            Some('e' | 'k' | 'l') => r"\s?\d{1,2}",
+            Some('j') => r"\d{3}",
+            Some('b' | 'h' | 'a') => r"[A-Za-z]{3}",
+            Some('B' | 'A') => r"[A-Za-z]+",
+            Some('p' | 'P') => r"[AaPp][Mm]",
+            Some('f') => r"\d+",
+            Some('.') => {
+                // %.f, %.3f, ...
+                while chars.next_if(|c| c.is_ascii_digit()).is_some() {}
+                chars.next();
+                r"\.\d+"
+            }
+            Some('z') => r"[+-]\d{2}:?\d{2}",
+            Some(':') => {
+                chars.next();
+                r"[+-]\d{2}:\d{2}"
+            }
+            Some('Z') => r"[A-Za-z]+",
+            Some('T') => r"\d{2}:\d{2}:\d{2}",
+            Some('R') => r"\d{2}:\d{2}",
+            Some('F') => r"\d{4}-\d{2}-\d{2}",
+            Some('D') => r"\d{2}/\d{2}/\d{2}",
+            Some('s') => r"\d+",
+            Some('%') => "%",
+            _ => r"\S+",
+        };
+        pattern.push_str(piece);
+    }
+
+    pattern
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +314,33 @@ mod tests {
         let result = parser.parse("  2025-11-17T10:30:00Z  ");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_find_with_format_strings() {
+        let parser = TimestampParser::with_format_strings(&[
+            "%d/%m/%Y %H:%M:%S".to_string(),
+            "%Y%m%d".to_string(),
+        ]);
+        assert!(parser.has_custom_formats());
+
+        let dt = parser
+            .find("[17/11/2025 10:30:00] ERROR Payment failed")
+            .unwrap();
+        assert_eq!((dt.year(), dt.month(), dt.day()), (2025, 11, 17));
+
+        let dt = parser.find("batch 20240102 done").unwrap();
+        assert_eq!((dt.year(), dt.month(), dt.day()), (2024, 1, 2));
+
+        assert!(parser.find("no timestamp here").is_none());
+        assert!(!TimestampParser::new().has_custom_formats());
+    }
+
+    #[test]
+    fn test_pattern_for_format() {
+        assert_eq!(pattern_for_format("%Y-%m-%d"), r"\d{4}\-\d{1,2}\-\d{1,2}");
+        assert_eq!(
+            pattern_for_format("%H:%M:%S%.3f"),
+            r"\d{1,2}:\d{1,2}:\d{1,2}\.\d+"
+        );
+    }
 }
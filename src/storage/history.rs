@@ -0,0 +1,362 @@
+use crate::parser::ParsingStatistics;
+use crate::types::ErrorGroup;
+use crate::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of example entries stored per group
+const MAX_STORED_ENTRIES: usize = 20;
+
+/// A run about to be persisted
+#[derive(Debug, Clone)]
+pub struct NewRun {
+    pub files: Vec<String>,
+    pub parser: String,
+    pub stats: ParsingStatistics,
+    pub ai_provider: Option<String>,
+    pub model: Option<String>,
+}
+
+/// A persisted investigate run
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<String>,
+    pub parser: String,
+    pub stats: ParsingStatistics,
+    pub ai_provider: Option<String>,
+    pub model: Option<String>,
+    pub group_count: usize,
+    pub total_occurrences: usize,
+}
+
+/// A run in which an error group was seen
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupOccurrence {
+    pub run_id: i64,
+    pub created_at: DateTime<Utc>,
+    pub count: usize,
+}
+
+/// SQLite-backed history of investigate runs
+pub struct RunHistory {
+    conn: Connection,
+}
+
+impl RunHistory {
+    /// Open the default history database (`~/.logai/history.db`)
+    pub fn new() -> Result<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    /// Open (or create) a history database at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at INTEGER NOT NULL,
+                files TEXT NOT NULL,
+                parser TEXT NOT NULL,
+                stats TEXT NOT NULL,
+                ai_provider TEXT,
+                model TEXT,
+                group_count INTEGER NOT NULL,
+                total_occurrences INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS run_groups (
+                run_id INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                group_id TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                PRIMARY KEY (run_id, position)
+            );
+            CREATE INDEX IF NOT EXISTS run_groups_group_id ON run_groups (group_id);",
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    fn default_path() -> Result<PathBuf> {
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .map_err(|_| anyhow::anyhow!("Could not determine home directory"))?;
+        Ok(PathBuf::from(home).join(".logai").join("history.db"))
+    }
+
+    /// Persist a run and its groups, returning the new run id
+    pub fn save_run(&mut self, run: &NewRun, groups: &[ErrorGroup]) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+
+        let total_occurrences: usize = groups.iter().map(|g| g.count).sum();
+        tx.execute(
+            "INSERT INTO runs
+             (created_at, files, parser, stats, ai_provider, model, group_count, total_occurrences)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                Utc::now().timestamp(),
+                serde_json::to_string(&run.files)?,
+                &run.parser,
+                serde_json::to_string(&run.stats)?,
+                &run.ai_provider,
+                &run.model,
+                groups.len() as i64,
+                total_occurrences as i64,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO run_groups (run_id, position, group_id, pattern, count, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+
+            for (position, group) in groups.iter().enumerate() {
+                // Keep only a sample of entries; counts and timestamps are preserved
+                let mut stored = group.clone();
+                stored.entries.truncate(MAX_STORED_ENTRIES);
+
+                stmt.execute(params![
+                    run_id,
+                    position as i64,
+                    &group.id,
+                    &group.pattern,
+                    group.count as i64,
+                    serde_json::to_string(&stored)?,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// List the most recent runs, newest first (0 = all)
+    pub fn list_runs(&self, limit: usize) -> Result<Vec<RunRecord>> {
+        let limit = if limit == 0 { -1 } else { limit as i64 };
+        let mut stmt = self.conn.prepare(
+            "SELECT id, created_at, files, parser, stats, ai_provider, model,
+                    group_count, total_occurrences
+             FROM runs ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit], Self::row_to_record)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Get a single run by id
+    pub fn get_run(&self, id: i64) -> Result<Option<RunRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, created_at, files, parser, stats, ai_provider, model,
+                    group_count, total_occurrences
+             FROM runs WHERE id = ?1",
+        )?;
+
+        Ok(stmt
+            .query_row(params![id], Self::row_to_record)
+            .optional()?)
+    }
+
+    /// Load the error groups stored for a run, in their original order
+    pub fn load_groups(&self, run_id: i64) -> Result<Vec<ErrorGroup>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM run_groups WHERE run_id = ?1 ORDER BY position")?;
+
+        let rows = stmt.query_map(params![run_id], |row| row.get::<_, String>(0))?;
+
+        let mut groups = Vec::new();
+        for data in rows {
+            groups.push(serde_json::from_str(&data?)?);
+        }
+        Ok(groups)
+    }
+
+    /// The runs in which the group with `group_id` was seen, newest first
+    pub fn group_occurrences(&self, group_id: &str) -> Result<Vec<GroupOccurrence>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT runs.id, runs.created_at, run_groups.count
+             FROM run_groups JOIN runs ON runs.id = run_groups.run_id
+             WHERE run_groups.group_id = ?1 ORDER BY runs.id DESC",
+        )?;
+
+        let rows = stmt.query_map(params![group_id], |row| {
+            let created_at: i64 = row.get(1)?;
+            let count: i64 = row.get(2)?;
+            Ok(GroupOccurrence {
+                run_id: row.get(0)?,
+                created_at: Utc
+                    .timestamp_opt(created_at, 0)
+                    .single()
+                    .unwrap_or_else(Utc::now),
+                count: count as usize,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Delete a run and its groups, returning whether it existed
+    pub fn delete_run(&mut self, id: i64) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM run_groups WHERE run_id = ?1", params![id])?;
+        let deleted = tx.execute("DELETE FROM runs WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<RunRecord> {
+        let created_at: i64 = row.get(1)?;
+        let group_count: i64 = row.get(7)?;
+        let total_occurrences: i64 = row.get(8)?;
+
+        Ok(RunRecord {
+            id: row.get(0)?,
+            created_at: Utc
+                .timestamp_opt(created_at, 0)
+                .single()
+                .unwrap_or_else(Utc::now),
+            files: Self::json_column(row, 2)?,
+            parser: row.get(3)?,
+            stats: Self::json_column(row, 4)?,
+            ai_provider: row.get(5)?,
+            model: row.get(6)?,
+            group_count: group_count as usize,
+            total_occurrences: total_occurrences as usize,
+        })
+    }
+
+    fn json_column<T: serde::de::DeserializeOwned>(
+        row: &rusqlite::Row,
+        index: usize,
+    ) -> rusqlite::Result<T> {
+        let text: String = row.get(index)?;
+        serde_json::from_str(&text).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                Box::new(e),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ErrorAnalysis, LogEntry, LogMetadata, Severity};
+    use std::collections::HashMap;
+
+    fn sample_group(id: &str, count: usize) -> ErrorGroup {
+        let entry = LogEntry {
+            timestamp: Some(Utc::now()),
+            severity: Severity::Error,
+            message: format!("{} happened", id),
+            metadata: LogMetadata {
+                file: None,
+                line: None,
+                function: None,
+                thread: None,
+                extra: HashMap::new(),
+            },
+            raw: format!("ERROR {} happened", id),
+            source: None,
+        };
+
+        ErrorGroup {
+            id: id.to_string(),
+            pattern: format!("{} happened", id),
+            count,
+            first_seen: Utc::now(),
+            last_seen: Utc::now(),
+            severity: Severity::Error,
+            entries: vec![entry; count],
+            analysis: Some(ErrorAnalysis {
+                explanation: "Something broke".to_string(),
+                root_cause: None,
+                suggestions: vec![],
+                related_resources: vec![],
+                tool_invocations: vec![],
+            }),
+            sources: Vec::new(),
+        }
+    }
+
+    fn sample_run() -> NewRun {
+        NewRun {
+            files: vec!["app.log".to_string()],
+            parser: "json".to_string(),
+            stats: ParsingStatistics {
+                total_lines: 10,
+                parsed_entries: 9,
+                parse_errors: 1,
+                multiline_entries: 0,
+                duration_ms: 5,
+            },
+            ai_provider: Some("ollama".to_string()),
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = RunHistory::open(dir.path().join("history.db")).unwrap();
+
+        let groups = vec![sample_group("err-a", 50), sample_group("err-b", 2)];
+        let id = history.save_run(&sample_run(), &groups).unwrap();
+
+        let record = history.get_run(id).unwrap().unwrap();
+        assert_eq!(record.files, vec!["app.log"]);
+        assert_eq!(record.parser, "json");
+        assert_eq!(record.stats.parse_errors, 1);
+        assert_eq!(record.group_count, 2);
+        assert_eq!(record.total_occurrences, 52);
+
+        let loaded = history.load_groups(id).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].id, "err-a");
+        assert_eq!(loaded[0].count, 50);
+        assert_eq!(loaded[0].entries.len(), MAX_STORED_ENTRIES);
+        assert_eq!(
+            loaded[0].analysis.as_ref().unwrap().explanation,
+            "Something broke"
+        );
+    }
+
+    #[test]
+    fn test_list_and_delete_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = RunHistory::open(dir.path().join("history.db")).unwrap();
+
+        let first = history.save_run(&sample_run(), &[]).unwrap();
+        let second = history
+            .save_run(&sample_run(), &[sample_group("err-a", 1)])
+            .unwrap();
+
+        let runs = history.list_runs(0).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].id, second);
+        assert_eq!(history.list_runs(1).unwrap().len(), 1);
+
+        let seen = history.group_occurrences("err-a").unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].run_id, second);
+        assert!(history.group_occurrences("err-b").unwrap().is_empty());
+
+        assert!(history.delete_run(second).unwrap());
+        assert!(!history.delete_run(second).unwrap());
+        assert!(history.load_groups(second).unwrap().is_empty());
+        assert_eq!(history.list_runs(0).unwrap()[0].id, first);
+    }
+}
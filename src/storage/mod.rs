@@ -1,2 +1,9 @@
-// Storage module - to be implemented in Phase 2
-// This will contain SQLite implementation for case history and caching
+//! Local persistence for LogAI.
+//!
+//! Investigate runs are stored in a SQLite database under `~/.logai` so they
+//! can be listed and re-rendered later without re-parsing logs or repeating
+//! AI calls.
+
+pub mod history;
+
+pub use history::{GroupOccurrence, NewRun, RunHistory, RunRecord};
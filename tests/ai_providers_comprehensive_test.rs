@@ -38,8 +38,10 @@ fn create_test_error_group() -> ErrorGroup {
                 extra: HashMap::new(),
             },
             raw: "Raw log line".to_string(),
+            source: None,
         }],
         analysis: None,
+        sources: Vec::new(),
     }
 }
 
@@ -148,6 +150,7 @@ async fn test_gemini_provider_with_large_error_group() {
                 extra: HashMap::new(),
             },
             raw: format!("Raw log line {}", i),
+            source: None,
         });
     }
 
@@ -23,6 +23,7 @@ fn create_log_entry(message: &str, severity: Severity) -> LogEntry {
             extra: std::collections::HashMap::new(),
         },
         raw: message.to_string(),
+        source: None,
     }
 }
 
@@ -65,6 +65,7 @@ fn test_error_group_creation_for_bedrock() {
             extra: std::collections::HashMap::new(),
         },
         raw: "Database connection failed".to_string(),
+        source: None,
     };
 
     let group = ErrorGroup {
@@ -76,6 +77,7 @@ fn test_error_group_creation_for_bedrock() {
         last_seen: chrono::Utc::now(),
         severity: Severity::Error,
         analysis: None,
+        sources: Vec::new(),
     };
 
     assert_eq!(group.pattern, "Database connection failed");
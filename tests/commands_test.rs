@@ -24,6 +24,19 @@ async fn test_investigate_with_empty_file() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -51,6 +64,19 @@ async fn test_investigate_with_json_logs() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -78,6 +104,19 @@ async fn test_investigate_with_plain_logs() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -110,6 +149,19 @@ async fn test_investigate_with_multiple_files() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -133,6 +185,19 @@ async fn test_investigate_with_nonexistent_file() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -160,6 +225,19 @@ async fn test_investigate_with_directory() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -187,6 +265,19 @@ async fn test_investigate_with_no_multiline() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
@@ -214,6 +305,19 @@ async fn test_investigate_html_output() {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     };
 
     let result = InvestigateCommand::execute(opts).await;
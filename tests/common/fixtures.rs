@@ -69,6 +69,7 @@ pub fn sample_error_group() -> ErrorGroup {
         last_seen: chrono::Utc::now(),
         entries: vec![sample_log_entry()],
         analysis: None,
+        sources: Vec::new(),
     }
 }
 
@@ -88,6 +89,7 @@ pub fn sample_log_entry() -> LogEntry {
             thread: None,
             extra: HashMap::new(),
         },
+        source: None,
     }
 }
 
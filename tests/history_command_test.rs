@@ -0,0 +1,195 @@
+//! Tests for the history command and run storage
+
+mod common;
+
+use common::fixtures::sample_error_group;
+use logai::analyzer::Fingerprint;
+use logai::commands::HistoryCommand;
+use logai::parser::ParsingStatistics;
+use logai::storage::{NewRun, RunHistory};
+use tempfile::TempDir;
+
+fn sample_run() -> NewRun {
+    NewRun {
+        files: vec!["app.log".to_string()],
+        parser: "plain".to_string(),
+        stats: ParsingStatistics {
+            total_lines: 5,
+            parsed_entries: 5,
+            ..Default::default()
+        },
+        ai_provider: None,
+        model: None,
+    }
+}
+
+#[test]
+fn test_list_runs_empty() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+
+    assert!(HistoryCommand::list_runs(&history, 0).is_ok());
+    let text = HistoryCommand::format_runs(&history.list_runs(0).unwrap());
+    assert!(text.starts_with("No runs recorded yet"));
+}
+
+#[test]
+fn test_list_runs() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+    let first = history
+        .save_run(&sample_run(), &[sample_error_group()])
+        .unwrap();
+    let second = history.save_run(&sample_run(), &[]).unwrap();
+
+    assert!(HistoryCommand::list_runs(&history, 10).is_ok());
+    let text = HistoryCommand::format_runs(&history.list_runs(10).unwrap());
+    let lines: Vec<&str> = text.lines().filter(|l| l.starts_with('#')).collect();
+    assert_eq!(lines.len(), 2);
+
+    // Newest first
+    assert!(lines[0].starts_with(&format!("#{:<4}", second)));
+    assert!(lines[0].contains("0 groups (0 occurrences)"));
+    assert!(lines[1].starts_with(&format!("#{:<4}", first)));
+    assert!(lines[1].contains("app.log | parser: plain | 5 lines, 5 entries"));
+    assert!(lines[1].contains("1 groups (5 occurrences) | ai: none"));
+
+    let text = HistoryCommand::format_runs(&history.list_runs(1).unwrap());
+    assert_eq!(text.lines().filter(|l| l.starts_with('#')).count(), 1);
+}
+
+#[test]
+fn test_show_run_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+    let id = history
+        .save_run(&sample_run(), &[sample_error_group()])
+        .unwrap();
+
+    let json_path = temp_dir.path().join("run.json");
+    HistoryCommand::show_run(&history, id, "json", 10, json_path.to_str()).unwrap();
+    let groups: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["id"], "test-group-1");
+    assert_eq!(groups[0]["pattern"], "Connection failed");
+    assert_eq!(groups[0]["count"], 5);
+
+    let text_path = temp_dir.path().join("run.txt");
+    HistoryCommand::show_run(&history, id, "terminal", 10, text_path.to_str()).unwrap();
+    let text = std::fs::read_to_string(&text_path).unwrap();
+    assert!(text.contains("Connection failed"));
+    assert!(!text.contains('\x1b'));
+}
+
+#[test]
+fn test_show_missing_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+
+    let result = HistoryCommand::show_run(&history, 42, "json", 10, None);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("not found"));
+}
+
+#[test]
+fn test_history_persists_across_connections() {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("history.db");
+
+    let id = {
+        let mut history = RunHistory::open(&db_path).unwrap();
+        history
+            .save_run(&sample_run(), &[sample_error_group()])
+            .unwrap()
+    };
+
+    let history = RunHistory::open(&db_path).unwrap();
+    let groups = history.load_groups(id).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].count, 5);
+}
+
+#[test]
+fn test_group_runs_rejects_drain_ids() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+
+    let id = Fingerprint::template("Job <*> failed").id();
+    let result = HistoryCommand::group_runs(&history, &id);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Drain template id"));
+}
+
+#[test]
+fn test_delete_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut history = RunHistory::open(temp_dir.path().join("history.db")).unwrap();
+    let kept = history
+        .save_run(&sample_run(), &[sample_error_group()])
+        .unwrap();
+    let deleted = history
+        .save_run(&sample_run(), &[sample_error_group()])
+        .unwrap();
+
+    HistoryCommand::delete_run(&mut history, deleted).unwrap();
+    assert!(history.get_run(deleted).unwrap().is_none());
+    assert!(history.load_groups(deleted).unwrap().is_empty());
+    let occurrences = history.group_occurrences("test-group-1").unwrap();
+    assert_eq!(occurrences.len(), 1);
+    assert_eq!(occurrences[0].run_id, kept);
+    assert_eq!(history.list_runs(0).unwrap().len(), 1);
+
+    // Deleting a missing run is reported, not an error
+    assert!(HistoryCommand::delete_run(&mut history, deleted).is_ok());
+}
+
+#[test]
+fn test_history_db_flag() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("project").join("history.db");
+    let log_path = home.path().join("app.log");
+    std::fs::write(&log_path, "2025-11-17 10:00:00 ERROR Connection refused\n").unwrap();
+
+    let logai = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_logai"))
+            .env("HOME", home.path())
+            .current_dir(home.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let db = db_path.to_str().unwrap();
+
+    logai(&[
+        "investigate",
+        log_path.to_str().unwrap(),
+        "-f",
+        "json",
+        "--history-db",
+        db,
+    ]);
+    let runs = RunHistory::open(&db_path).unwrap().list_runs(0).unwrap();
+    assert_eq!(runs.len(), 1);
+    assert!(!home.path().join(".logai").join("history.db").exists());
+
+    let listed = logai(&["history", "list", "--history-db", db]);
+    assert!(listed.contains(&format!("#{}", runs[0].id)));
+
+    logai(&[
+        "history",
+        "--history-db",
+        db,
+        "delete",
+        &runs[0].id.to_string(),
+    ]);
+    assert!(RunHistory::open(&db_path)
+        .unwrap()
+        .list_runs(0)
+        .unwrap()
+        .is_empty());
+}
@@ -218,5 +218,6 @@ fn create_test_entry(message: &str) -> LogEntry {
             extra: HashMap::new(),
         },
         raw: message.to_string(),
+        source: None,
     }
 }
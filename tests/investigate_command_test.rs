@@ -15,6 +15,7 @@ use common::fixtures::{
     sample_syslog,
 };
 use logai::commands::investigate::{InvestigateCommand, InvestigateOptions};
+use logai::storage::RunHistory;
 use std::fs;
 use tempfile::TempDir;
 
@@ -34,6 +35,19 @@ fn default_options() -> InvestigateOptions {
         no_mcp: true,
         mcp_config: None,
         concurrency: None,
+        no_history: true,
+        history_db: None,
+        no_cache: false,
+        parser_config: None,
+        recursive: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        severity: None,
+        output: None,
+        since: None,
+        until: None,
+        filters: Vec::new(),
+        grouping: None,
     }
 }
 
@@ -106,6 +120,78 @@ async fn test_investigate_syslog_format() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_investigate_logfmt_format() {
+    let line =
+        r#"ts=2025-11-17T10:30:00Z level=error caller=db.go:42 msg="query failed" err="timeout""#;
+    let content = format!("{}\n{}\n", line, line);
+    let temp_file = create_temp_log_file(&content);
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.log_format = "logfmt".to_string();
+
+    let result = InvestigateCommand::execute(opts).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_investigate_json_presets() {
+    let line = r#"{"@timestamp":"2025-11-17T10:30:00Z","log.level":"error","message":"Charge failed","error":{"stack_trace":"java.lang.IllegalStateException\n\tat com.example.Pay.charge(Pay.java:10)"}}"#;
+    let content = format!("{}\n{}\n", line, line);
+    let temp_file = create_temp_log_file(&content);
+
+    for preset in ["ecs", "bunyan", "pino", "clef", "gcp"] {
+        let mut opts = default_options();
+        opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+        opts.log_format = preset.to_string();
+
+        let result = InvestigateCommand::execute(opts).await;
+        assert!(result.is_ok(), "preset {} failed", preset);
+    }
+}
+
+#[tokio::test]
+async fn test_investigate_container_formats() {
+    let docker = r#"{"log":"{\"level\":\"error\",\"msg\":\"db down\"}\n","stream":"stderr","time":"2025-11-17T10:30:00Z"}"#;
+    let cri = "2025-11-17T10:30:00.123456789Z stderr F ERROR Payment failed";
+
+    for (format, line) in [
+        ("docker", docker),
+        ("cri", cri),
+        ("auto", docker),
+        ("auto", cri),
+    ] {
+        let temp_file = create_temp_log_file(&format!("{}\n{}\n", line, line));
+        let mut opts = default_options();
+        opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+        opts.log_format = format.to_string();
+
+        let result = InvestigateCommand::execute(opts).await;
+        assert!(result.is_ok(), "format {} failed", format);
+    }
+}
+
+#[tokio::test]
+async fn test_investigate_journald_formats() {
+    let json = r#"{"__REALTIME_TIMESTAMP":"1700000000000000","PRIORITY":"3","_SYSTEMD_UNIT":"api.service","MESSAGE":"Connection refused"}"#;
+    let export = "__CURSOR=s=1;i=1\n__REALTIME_TIMESTAMP=1700000000000000\nPRIORITY=3\nMESSAGE=Connection refused\n";
+
+    for (format, content) in [
+        ("journald", format!("{}\n{}\n", json, json)),
+        ("auto", format!("{}\n{}\n", json, json)),
+        ("auto", format!("{}\n{}", export, export)),
+    ] {
+        let temp_file = create_temp_log_file(&content);
+        let mut opts = default_options();
+        opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+        opts.log_format = format.to_string();
+
+        let result = InvestigateCommand::execute(opts).await;
+        assert!(result.is_ok(), "format {} failed", format);
+    }
+}
+
 #[tokio::test]
 async fn test_investigate_auto_format_detection() {
     let content = sample_json_log();
@@ -162,6 +248,148 @@ async fn test_investigate_html_output() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_investigate_output_file() {
+    let temp_file = create_temp_log_file(&sample_plain_log());
+    let out_dir = TempDir::new().unwrap();
+
+    for format in ["json", "terminal", "html"] {
+        let path = out_dir
+            .path()
+            .join("reports")
+            .join(format!("out.{}", format));
+        let mut opts = default_options();
+        opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+        opts.format = format.to_string();
+        opts.output = Some(path.to_str().unwrap().to_string());
+
+        InvestigateCommand::execute(opts).await.unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(!written.is_empty(), "{} output is empty", format);
+        assert!(
+            !written.contains('\x1b'),
+            "{} output has escape codes",
+            format
+        );
+    }
+
+    let json = fs::read_to_string(out_dir.path().join("reports").join("out.json")).unwrap();
+    let groups: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+    assert!(!groups.is_empty());
+}
+
+/// Severities of the groups found in `content` with `--severity`
+async fn group_severities(content: &str, severity: Option<&str>) -> Vec<String> {
+    let temp_file = create_temp_log_file(content);
+    let out_dir = TempDir::new().unwrap();
+    let path = out_dir.path().join("groups.json");
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.severity = severity.map(String::from);
+    opts.output = Some(path.to_str().unwrap().to_string());
+    InvestigateCommand::execute(opts).await.unwrap();
+
+    let groups: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    groups
+        .iter()
+        .map(|group| group["severity"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_investigate_severity_threshold() {
+    let content = "2025-11-17 10:00:00 ERROR Database connection failed\n\
+                   2025-11-17 10:00:01 WARN Slow query detected\n\
+                   2025-11-17 10:00:02 INFO Server started\n";
+
+    assert_eq!(group_severities(content, None).await, ["Error", "Warning"]);
+    assert_eq!(group_severities(content, Some("error")).await, ["Error"]);
+    assert_eq!(
+        group_severities(content, Some("info")).await,
+        ["Error", "Warning", "Info"]
+    );
+
+    let temp_file = create_temp_log_file(content);
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.severity = Some("loud".to_string());
+    assert!(InvestigateCommand::execute(opts).await.is_err());
+}
+
+#[tokio::test]
+async fn test_investigate_time_and_field_filters() {
+    let content = [
+        r#"{"timestamp":"2025-11-17T14:00:00Z","level":"error","message":"Payment declined","service":"checkout","status":402}"#,
+        r#"{"timestamp":"2025-11-17T14:10:00Z","level":"error","message":"Upstream timeout","service":"checkout","status":504}"#,
+        r#"{"timestamp":"2025-11-17T14:20:00Z","level":"error","message":"Cache miss storm","service":"search","status":503}"#,
+        r#"{"timestamp":"2025-11-17T14:40:00Z","level":"error","message":"Disk full","service":"checkout","status":507}"#,
+    ]
+    .join("\n");
+    let temp_file = create_temp_log_file(&content);
+    let out_dir = TempDir::new().unwrap();
+    let path = out_dir.path().join("groups.json");
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.output = Some(path.to_str().unwrap().to_string());
+    opts.since = Some("2025-11-17 14:02".to_string());
+    opts.until = Some("2025-11-17T14:30:00Z".to_string());
+    opts.filters = vec!["service=checkout".to_string(), "status>=500".to_string()];
+    InvestigateCommand::execute(opts).await.unwrap();
+
+    let groups: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["entries"][0]["message"], "Upstream timeout");
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.filters = vec!["status".to_string()];
+    assert!(InvestigateCommand::execute(opts).await.is_err());
+}
+
+#[tokio::test]
+async fn test_investigate_drain_grouping() {
+    let content = "2025-11-17 10:00:00 ERROR Connection to db-primary failed after 30 ms\n\
+                   2025-11-17 10:00:01 ERROR Connection to db-replica failed after 45 ms\n";
+    let temp_file = create_temp_log_file(content);
+    let out_dir = TempDir::new().unwrap();
+    let path = out_dir.path().join("groups.json");
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.output = Some(path.to_str().unwrap().to_string());
+    opts.grouping = Some("drain".to_string());
+    InvestigateCommand::execute(opts).await.unwrap();
+
+    let groups: Vec<serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["count"], 2);
+    assert!(groups[0]["pattern"]
+        .as_str()
+        .unwrap()
+        .contains("Connection to <*> failed after <*> ms"));
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.grouping = Some("tree".to_string());
+    assert!(InvestigateCommand::execute(opts).await.is_err());
+}
+
+#[test]
+fn test_no_cache_disables_response_cache() {
+    let mut config = logai::ai::AIConfig::default();
+    config.analysis.enable_cache = true;
+
+    assert!(InvestigateCommand::analysis_config(&config, None, false).enable_cache);
+    let analysis = InvestigateCommand::analysis_config(&config, Some(3), true);
+    assert!(!analysis.enable_cache);
+    assert_eq!(analysis.max_concurrency, 3);
+}
+
 // ============================================================================
 // Statistics Tests
 // ============================================================================
@@ -293,6 +521,59 @@ async fn test_investigate_directory() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_investigate_rotated_and_compressed_files() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("app.log"), sample_plain_log()).unwrap();
+    fs::write(temp_dir.path().join("app.log.1"), sample_plain_log()).unwrap();
+
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(sample_plain_log().as_bytes()).unwrap();
+    fs::write(temp_dir.path().join("app.log.2.gz"), gzip.finish().unwrap()).unwrap();
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("logs/api.log", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(sample_json_log().as_bytes()).unwrap();
+    let bundle = zip.finish().unwrap().into_inner();
+    fs::write(temp_dir.path().join("bundle.zip"), bundle).unwrap();
+
+    // The directory and each input on its own
+    let mut inputs = vec![temp_dir.path().to_path_buf()];
+    inputs.extend(["app.log.2.gz", "bundle.zip"].map(|name| temp_dir.path().join(name)));
+    for input in inputs {
+        let mut opts = default_options();
+        opts.files = vec![input.to_str().unwrap().to_string()];
+
+        let result = InvestigateCommand::execute(opts).await;
+        assert!(result.is_ok(), "{} failed", input.display());
+    }
+}
+
+#[tokio::test]
+async fn test_investigate_recursive_with_globs() {
+    let temp_dir = TempDir::new().unwrap();
+    let nested = temp_dir.path().join("services").join("api");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(nested.join("api.log"), sample_json_log()).unwrap();
+    fs::write(nested.join("api.out"), sample_plain_log()).unwrap();
+    fs::write(temp_dir.path().join(".logaiignore"), "services/api/*.log\n").unwrap();
+
+    let mut opts = default_options();
+    opts.files = vec![temp_dir.path().to_str().unwrap().to_string()];
+    opts.recursive = true;
+    opts.include = vec!["**/*.log*".to_string(), "*.out".to_string()];
+    opts.exclude = vec!["tmp/".to_string()];
+    assert!(InvestigateCommand::execute(opts).await.is_ok());
+
+    let mut opts = default_options();
+    opts.files = vec![temp_dir.path().to_str().unwrap().to_string()];
+    opts.include = vec!["[".to_string()];
+    assert!(InvestigateCommand::execute(opts).await.is_err());
+}
+
 #[tokio::test]
 async fn test_investigate_mixed_files_and_dirs() {
     let temp_dir = TempDir::new().unwrap();
@@ -348,6 +629,71 @@ async fn test_investigate_malformed_logs() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_investigate_parser_config_error_budget() {
+    let content = format!(
+        "{}\nnot json at all\n{}\n",
+        sample_json_log(),
+        sample_json_log()
+    );
+    let temp_file = create_temp_log_file(&content);
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("parser.toml");
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.log_format = "json".to_string();
+    opts.parser_config = Some(config_path.to_str().unwrap().to_string());
+
+    fs::write(&config_path, "skip_invalid_lines = false\n").unwrap();
+    let result = InvestigateCommand::execute(opts).await;
+    assert!(result.is_err());
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.log_format = "json".to_string();
+    opts.parser_config = Some(config_path.to_str().unwrap().to_string());
+
+    fs::write(&config_path, "max_parse_errors = 1\n").unwrap();
+    let result = InvestigateCommand::execute(opts).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_investigate_custom_format() {
+    let content = "2025-11-17 10:30:00 |ERROR| [worker-1] Payment failed\n\
+                   2025-11-17 10:30:05 |ERROR| [worker-2] Payment failed\n";
+    let temp_file = create_temp_log_file(content);
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("parser.toml");
+    fs::write(
+        &config_path,
+        r#"
+[[formats]]
+name = "inhouse"
+grok = '%{TIMESTAMP_ISO8601:ts} \|%{LOGLEVEL:level}\| \[%{NOTSPACE:thread}\] %{GREEDYDATA:message}'
+"#,
+    )
+    .unwrap();
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.log_format = "inhouse".to_string();
+    opts.parser_config = Some(config_path.to_str().unwrap().to_string());
+    assert!(InvestigateCommand::execute(opts).await.is_ok());
+
+    // Reusing a built-in name is rejected
+    fs::write(
+        &config_path,
+        "[[formats]]\nname = \"json\"\npattern = '.*'\n",
+    )
+    .unwrap();
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.parser_config = Some(config_path.to_str().unwrap().to_string());
+    assert!(InvestigateCommand::execute(opts).await.is_err());
+}
+
 // ============================================================================
 // Concurrency Tests
 // ============================================================================
@@ -397,6 +743,28 @@ async fn test_investigate_large_file() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_investigate_streams_stack_traces_across_chunks() {
+    // Enough lines for several parse chunks, with traces straddling chunk edges
+    let mut content = String::new();
+    for i in 0..3000 {
+        content.push_str(&format!("2024-11-19 10:30:00 INFO Handled request {}\n", i));
+        if i % 7 == 0 {
+            content.push_str("2024-11-19 10:30:01 ERROR java.lang.IllegalStateException: boom\n");
+            content.push_str("    at com.example.Service.process(Service.java:42)\n");
+            content.push_str("    at com.example.Main.main(Main.java:10)\n");
+        }
+    }
+    let temp_file = create_temp_log_file(&content);
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.stats = true;
+
+    let result = InvestigateCommand::execute(opts).await;
+    assert!(result.is_ok());
+}
+
 // ============================================================================
 // Special Characters Tests
 // ============================================================================
@@ -456,3 +824,70 @@ async fn test_investigate_with_mcp_config() {
     // May fail if MCP config doesn't exist, but should handle gracefully
     let _ = result;
 }
+
+// ============================================================================
+// History Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_investigate_with_history_enabled() {
+    let content = "2025-11-17 10:00:00 ERROR Request failed\n\
+                   java.lang.NullPointerException: order is null\n\
+                   \tat com.shop.Orders.load(Orders.java:42)\n\
+                   2025-11-17 10:00:01 ERROR Connection refused\n\
+                   2025-11-17 10:00:02 INFO Recovered\n";
+    let temp_file = create_temp_log_file(content);
+    let history_dir = TempDir::new().unwrap();
+    let db_path = history_dir.path().join("history.db");
+    let file = temp_file.path().to_str().unwrap().to_string();
+
+    let mut opts = default_options();
+    opts.files = vec![file.clone()];
+    opts.no_history = false;
+    opts.history_db = Some(db_path.to_str().unwrap().to_string());
+    opts.format = "json".to_string();
+    InvestigateCommand::execute(opts).await.unwrap();
+
+    let history = RunHistory::open(&db_path).unwrap();
+    let runs = history.list_runs(0).unwrap();
+    assert_eq!(runs.len(), 1);
+    let run = &runs[0];
+    assert_eq!(run.files, vec![file]);
+    assert_eq!(run.stats.total_lines, 5);
+    assert_eq!(run.stats.parsed_entries, 3);
+    assert_eq!(run.stats.multiline_entries, 1);
+    assert_eq!(run.group_count, 2);
+    assert_eq!(run.ai_provider, None);
+
+    let groups = history.load_groups(run.id).unwrap();
+    assert_eq!(groups.len(), 2);
+    assert!(groups
+        .iter()
+        .any(|g| g.entries[0].message.contains("NullPointerException")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_history_records_resolved_model() {
+    let temp_file = create_temp_log_file("2025-11-17 10:00:00 ERROR Connection refused\n");
+    let history_dir = TempDir::new().unwrap();
+    let db_path = history_dir.path().join("history.db");
+    // Nothing listens here, so analysis fails fast without a server
+    let host = "http://127.0.0.1:9".to_string();
+
+    let mut opts = default_options();
+    opts.files = vec![temp_file.path().to_str().unwrap().to_string()];
+    opts.ai_provider = "ollama".to_string();
+    opts.ollama_host = Some(host.clone());
+    opts.concurrency = Some(1);
+    opts.no_cache = true;
+    opts.no_history = false;
+    opts.history_db = Some(db_path.to_str().unwrap().to_string());
+    InvestigateCommand::execute(opts).await.unwrap();
+
+    // No --model: the provider's default (or the configured model) is recorded
+    let provider = logai::ai::create_provider("ollama", None, None, Some(host), None).unwrap();
+    let runs = RunHistory::open(&db_path).unwrap().list_runs(0).unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].ai_provider.as_deref(), Some("ollama"));
+    assert_eq!(runs[0].model.as_deref(), Some(provider.model()));
+}
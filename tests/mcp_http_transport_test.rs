@@ -0,0 +1,157 @@
+//! Streamable HTTP transport tests against a mocked MCP endpoint.
+
+use logai::mcp::{AuthConfig, ConnectionConfig, MCPClient, MCPConfig, ServerConfig};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn http_config(url: String, auth: Option<AuthConfig>) -> MCPConfig {
+    MCPConfig {
+        servers: vec![ServerConfig {
+            name: "remote".to_string(),
+            enabled: true,
+            connection: ConnectionConfig::Http { url },
+            auth,
+        }],
+        default_timeout: 10,
+        tool_timeouts: HashMap::new(),
+        builtin_tools: Default::default(),
+    }
+}
+
+fn bearer(token: &str) -> AuthConfig {
+    AuthConfig {
+        scheme: "Bearer".to_string(),
+        credentials: HashMap::from([("token".to_string(), token.to_string())]),
+    }
+}
+
+#[tokio::test]
+async fn test_http_transport_session_and_sse_responses() {
+    let mut server = Server::new_async().await;
+
+    let initialize = server
+        .mock("POST", "/mcp")
+        .match_header("authorization", "Bearer secret")
+        .match_header("accept", Matcher::Regex("text/event-stream".to_string()))
+        .match_body(Matcher::Regex(r#""method":"initialize""#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("mcp-session-id", "session-1")
+        .with_body(
+            json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "result": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {"tools": {}},
+                    "serverInfo": {"name": "remote", "version": "1.0"}
+                }
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let initialized = server
+        .mock("POST", "/mcp")
+        .match_header("mcp-session-id", "session-1")
+        .match_body(Matcher::Regex("notifications/initialized".to_string()))
+        .with_status(202)
+        .create_async()
+        .await;
+
+    let list_tools = server
+        .mock("POST", "/mcp")
+        .match_header("authorization", "Bearer secret")
+        .match_header("mcp-session-id", "session-1")
+        .match_body(Matcher::Regex(r#""method":"tools/list""#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            ": stream opened\n\nevent: message\ndata: {}\n\n",
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"tools": [{
+                    "name": "search_code",
+                    "description": "Search the code base",
+                    "inputSchema": {"type": "object", "properties": {"query": {"type": "string"}}}
+                }]}
+            })
+        ))
+        .create_async()
+        .await;
+
+    let call_tool = server
+        .mock("POST", "/mcp")
+        .match_header("mcp-session-id", "session-1")
+        .match_body(Matcher::Regex(r#""method":"tools/call""#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(format!(
+            "data: {}\n\n",
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "result": {"content": [{"type": "text", "text": "src/db/pool.rs:42"}]}
+            })
+        ))
+        .create_async()
+        .await;
+
+    let config = http_config(format!("{}/mcp", server.url()), Some(bearer("secret")));
+    let mut client = MCPClient::new(config).unwrap();
+    client.connect().await.unwrap();
+    assert!(client.is_connected());
+
+    let tools = client.discover_tools().await.unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "search_code");
+
+    let result = client
+        .invoke_tool("search_code", json!({"query": "pool"}))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert_eq!(
+        result.data.unwrap()["content"][0]["text"],
+        "src/db/pool.rs:42"
+    );
+
+    initialize.assert_async().await;
+    initialized.assert_async().await;
+    list_tools.assert_async().await;
+    call_tool.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_http_transport_rejected_credentials() {
+    let mut server = Server::new_async().await;
+
+    let unauthorized = server
+        .mock("POST", "/mcp")
+        .with_status(401)
+        .with_body("invalid token")
+        .create_async()
+        .await;
+
+    let config = http_config(format!("{}/mcp", server.url()), Some(bearer("wrong")));
+    let mut client = MCPClient::new(config).unwrap();
+
+    // Connection failures degrade gracefully
+    client.connect().await.unwrap();
+    assert!(!client.is_connected());
+    unauthorized.assert_async().await;
+}
+
+#[test]
+fn test_http_config_requires_credentials() {
+    let auth = AuthConfig {
+        scheme: "Basic".to_string(),
+        credentials: HashMap::new(),
+    };
+
+    let result = MCPClient::new(http_config("http://localhost/mcp".to_string(), Some(auth)));
+    assert!(result.is_err());
+}
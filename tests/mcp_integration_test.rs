@@ -20,6 +20,7 @@ async fn test_mcp_client_with_echo_server() {
         }],
         default_timeout: 30,
         tool_timeouts: std::collections::HashMap::new(),
+        builtin_tools: Default::default(),
     };
 
     // Create client
@@ -104,3 +105,77 @@ async fn test_mcp_client_tool_not_found() {
         _ => panic!("Expected ToolNotFound error"),
     }
 }
+
+/// Stdio server that answers `tools/call` after `delay` seconds, in the
+/// background, so replies can arrive in a different order than requests.
+/// It also announces a tool list change after initialization.
+#[cfg(unix)]
+const CONCURRENT_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"initialize"'*)
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"protocolVersion\":\"2024-11-05\",\"capabilities\":{\"tools\":{}},\"serverInfo\":{\"name\":\"slow\",\"version\":\"1.0\"}}}" ;;
+    *'notifications/initialized'*)
+      echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"ready"}}'
+      echo '{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}' ;;
+    *'"tools/list"'*)
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[{\"name\":\"wait\",\"inputSchema\":{\"type\":\"object\"}}]}}" ;;
+    *'"tools/call"'*)
+      delay=$(printf '%s' "$line" | sed -n 's/.*"delay":\([0-9.]*\).*/\1/p')
+      ( sleep "$delay"; echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"content\":[{\"type\":\"text\",\"text\":\"waited $delay\"}]}}" ) & ;;
+  esac
+done
+wait
+"#;
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_mcp_client_concurrent_tool_calls() {
+    let config = MCPConfig {
+        servers: vec![ServerConfig {
+            name: "slow".to_string(),
+            enabled: true,
+            connection: ConnectionConfig::Stdio {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), CONCURRENT_SERVER.to_string()],
+            },
+            auth: None,
+        }],
+        default_timeout: 10,
+        tool_timeouts: std::collections::HashMap::new(),
+        builtin_tools: Default::default(),
+    };
+
+    let mut client = MCPClient::new(config).unwrap();
+    let mut notifications = client.subscribe_notifications();
+    client.connect().await.unwrap();
+
+    let first = notifications.recv().await.unwrap();
+    assert_eq!(first.server, "slow");
+    assert_eq!(first.method, "notifications/message");
+    assert_eq!(first.params["data"], "ready");
+    let second = notifications.recv().await.unwrap();
+    assert_eq!(second.method, "notifications/tools/list_changed");
+    assert!(client.tools_changed());
+
+    client.discover_tools().await.unwrap();
+    assert!(!client.tools_changed());
+
+    // The slow call is sent first but finishes last
+    let start = std::time::Instant::now();
+    let (slow, fast) = tokio::join!(
+        client.invoke_tool("wait", serde_json::json!({"delay": 0.6})),
+        client.invoke_tool("wait", serde_json::json!({"delay": 0.1})),
+    );
+    let slow = slow.unwrap();
+    let fast = fast.unwrap();
+
+    assert_eq!(slow.data.unwrap()["content"][0]["text"], "waited 0.6");
+    assert_eq!(fast.data.unwrap()["content"][0]["text"], "waited 0.1");
+    assert!(fast.metadata.duration_ms < slow.metadata.duration_ms);
+    assert!(start.elapsed() < std::time::Duration::from_millis(1200));
+
+    client.disconnect().await.unwrap();
+    assert!(!client.is_connected());
+}
@@ -0,0 +1,178 @@
+//! `logai mcp serve` tests, driven through logai's own MCP client.
+
+mod common;
+
+use common::fixtures::sample_error_group;
+use logai::ai::{AnalysisConfig, OllamaProvider};
+use logai::mcp::{ConnectionConfig, MCPClient, MCPConfig, MCPServer, ServerConfig};
+use logai::parser::ParsingStatistics;
+use logai::storage::{NewRun, RunHistory};
+use mockito::Server;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const LOGS: &str = "2024-01-15T10:30:00Z ERROR Database connection failed: timeout for request 100234\n\
+                    2024-01-15T10:30:05Z ERROR Database connection failed: timeout for request 100876\n\
+                    2024-01-15T10:30:10Z WARN Slow query took 1200ms\n";
+
+async fn connect(connection: ConnectionConfig) -> MCPClient {
+    let config = MCPConfig {
+        servers: vec![ServerConfig {
+            name: "logai".to_string(),
+            enabled: true,
+            connection,
+            auth: None,
+        }],
+        default_timeout: 30,
+        tool_timeouts: HashMap::new(),
+        builtin_tools: Default::default(),
+    };
+
+    let mut client = MCPClient::new(config).unwrap();
+    client.connect().await.unwrap();
+    assert!(client.is_connected());
+    client.discover_tools().await.unwrap();
+    client
+}
+
+/// Decode the JSON text content of a successful tool result
+async fn call(client: &MCPClient, tool: &str, arguments: Value) -> Value {
+    let result = client.invoke_tool(tool, arguments).await.unwrap();
+    let data = result.data.unwrap();
+    assert_eq!(data["isError"], false, "{}", data);
+    serde_json::from_str(data["content"][0]["text"].as_str().unwrap()).unwrap()
+}
+
+async fn serve_http(server: MCPServer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+    tokio::spawn(Arc::new(server).serve_http(listener));
+    url
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_serve_stdio_binary() {
+    let home = TempDir::new().unwrap();
+    let log_path = home.path().join("app.log");
+    std::fs::write(&log_path, LOGS).unwrap();
+
+    // Run the real binary with an empty home so no user config is picked up
+    let client = connect(ConnectionConfig::Stdio {
+        command: "env".to_string(),
+        args: vec![
+            format!("HOME={}", home.path().display()),
+            env!("CARGO_BIN_EXE_logai").to_string(),
+            "mcp".to_string(),
+            "serve".to_string(),
+        ],
+    })
+    .await;
+
+    assert!(client.has_tool("parse_logs"));
+    assert!(client.has_tool("group_errors"));
+    assert!(client.has_tool("get_run"));
+    assert!(!client.has_tool("analyze_group"));
+
+    let output = call(
+        &client,
+        "group_errors",
+        json!({"path": log_path.to_str().unwrap()}),
+    )
+    .await;
+    assert_eq!(output["total_groups"], 2);
+    assert_eq!(output["groups"][0]["count"], 2);
+    assert_eq!(output["groups"][1]["severity"], "Warning");
+
+    let output = call(&client, "parse_logs", json!({"content": LOGS})).await;
+    assert_eq!(output["parsed_entries"], 3);
+}
+
+#[tokio::test]
+async fn test_serve_http_get_run() {
+    let dir = TempDir::new().unwrap();
+    let db = dir.path().join("history.db");
+
+    let mut group = sample_error_group();
+    group.entries = vec![group.entries[0].clone(); 8];
+    let run_id = RunHistory::open(&db)
+        .unwrap()
+        .save_run(
+            &NewRun {
+                files: vec!["app.log".to_string()],
+                parser: "plain".to_string(),
+                stats: ParsingStatistics::default(),
+                ai_provider: None,
+                model: None,
+            },
+            &[group],
+        )
+        .unwrap();
+
+    let url = serve_http(MCPServer::new().with_history_path(&db)).await;
+    let client = connect(ConnectionConfig::Http { url }).await;
+
+    let output = call(&client, "get_run", json!({"id": run_id})).await;
+    assert_eq!(output["run"]["id"], run_id);
+    assert_eq!(output["run"]["files"][0], "app.log");
+    assert_eq!(output["groups"][0]["pattern"], "Connection failed");
+    // Only a sample of each group's entries is returned
+    assert_eq!(output["groups"][0]["entries"].as_array().unwrap().len(), 5);
+
+    let result = client
+        .invoke_tool("get_run", json!({"id": run_id + 1}))
+        .await
+        .unwrap();
+    let data = result.data.unwrap();
+    assert_eq!(data["isError"], true);
+    assert!(data["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("not found"));
+}
+
+#[tokio::test]
+async fn test_serve_http_analyze_group() {
+    let mut ollama = Server::new_async().await;
+    let generate = ollama
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_body(
+            json!({
+                "response": json!({
+                    "explanation": "The database is unreachable",
+                    "root_cause": "Connection pool exhausted",
+                    "suggestions": []
+                })
+                .to_string()
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let server = MCPServer::new()
+        .with_provider(Arc::new(OllamaProvider::new(Some(ollama.url()), None)))
+        .with_analysis_config(AnalysisConfig {
+            enable_retry: false,
+            ..Default::default()
+        });
+    let url = serve_http(server).await;
+    let client = connect(ConnectionConfig::Http { url }).await;
+    assert!(client.has_tool("analyze_group"));
+
+    let groups = call(&client, "group_errors", json!({"content": LOGS})).await;
+    let output = call(
+        &client,
+        "analyze_group",
+        json!({"group": groups["groups"][0]}),
+    )
+    .await;
+
+    assert_eq!(output["explanation"], "The database is unreachable");
+    assert_eq!(output["root_cause"], "Connection pool exhausted");
+    generate.assert_async().await;
+}
@@ -0,0 +1,418 @@
+//! Model-driven MCP tool calling tests.
+//!
+//! Each test runs a tiny shell-based MCP server over stdio and a mocked
+//! provider API that first requests a tool call and then returns the final
+//! analysis once the tool result has been sent back.
+#![cfg(unix)]
+
+mod common;
+
+use common::fixtures::{sample_error_group, sample_log_entry};
+use logai::ai::{
+    AIProvider, AnalysisConfig, ClaudeProvider, OllamaProvider, OpenAIProvider, ParallelAnalyzer,
+    ProgressUpdate,
+};
+use logai::mcp::{
+    BuiltinToolsConfig, ConnectionConfig, MCPClient, MCPConfig, ServerConfig, ToolRegistry,
+};
+use mockito::{Matcher, Server};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Minimal MCP server exposing a single `read_file` tool
+const FAKE_SERVER: &str = r#"
+while IFS= read -r line; do
+  case "$line" in
+    *'"initialize"'*)
+      echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1.0"}}}' ;;
+    *'"tools/list"'*)
+      echo '{"jsonrpc":"2.0","id":1,"result":{"tools":[{"name":"read_file","description":"Read a file","inputSchema":{"type":"object","properties":{"path":{"type":"string"}},"required":["path"]}}]}}' ;;
+    *'"tools/call"'*)
+      echo '{"jsonrpc":"2.0","id":2,"result":{"content":[{"type":"text","text":"pool_size = 5"}]}}' ;;
+  esac
+done
+"#;
+
+async fn connect_fake_server() -> MCPClient {
+    let config = MCPConfig {
+        servers: vec![ServerConfig {
+            name: "fake".to_string(),
+            enabled: true,
+            connection: ConnectionConfig::Stdio {
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), FAKE_SERVER.to_string()],
+            },
+            auth: None,
+        }],
+        default_timeout: 10,
+        tool_timeouts: HashMap::new(),
+        builtin_tools: Default::default(),
+    };
+
+    let mut client = MCPClient::new(config).unwrap();
+    client.connect().await.unwrap();
+    client.discover_tools().await.unwrap();
+    assert!(client.has_tool("read_file"));
+    client
+}
+
+fn final_analysis() -> String {
+    json!({
+        "explanation": "The connection pool is exhausted",
+        "root_cause": "pool_size is set to 5",
+        "suggestions": [{"description": "Increase pool_size", "priority": 1}]
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_openai_tool_loop_through_parallel_analyzer() {
+    let mut server = Server::new_async().await;
+
+    let tool_request = server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::Regex(r#""name":"read_file""#.to_string()))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {
+                                "name": "read_file",
+                                "arguments": "{\"path\":\"config.toml\"}"
+                            }
+                        }]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let answer = server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""role":"tool""#.to_string()),
+            Matcher::Regex(r#""tool_call_id":"call_1""#.to_string()),
+            Matcher::Regex("pool_size = 5".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": final_analysis()}}]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OpenAIProvider::new("test-key".to_string(), None).with_base_url(server.url());
+    let client = Arc::new(connect_fake_server().await);
+
+    let config = AnalysisConfig {
+        enable_retry: false,
+        ..Default::default()
+    };
+    let analyzer = ParallelAnalyzer::new(Arc::new(provider), config).with_mcp_client(client);
+
+    let mut groups = vec![sample_error_group()];
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+
+    assert_eq!(stats.successful, 1);
+    let analysis = groups[0].analysis.as_ref().unwrap();
+    assert_eq!(analysis.explanation, "The connection pool is exhausted");
+    assert_eq!(analysis.tool_invocations.len(), 1);
+    assert_eq!(analysis.tool_invocations[0].tool, "read_file");
+    assert_eq!(analysis.tool_invocations[0].status, "success");
+
+    tool_request.assert_async().await;
+    answer.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_openai_tool_errors_are_fed_back() {
+    let mut server = Server::new_async().await;
+
+    server
+        .mock("POST", "/chat/completions")
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {"name": "delete_everything", "arguments": "{}"}
+                        }]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let answer = server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::Regex("not found".to_string()))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": final_analysis()}}]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OpenAIProvider::new("test-key".to_string(), None).with_base_url(server.url());
+    let client = connect_fake_server().await;
+
+    let analysis = provider
+        .analyze_with_tools(&sample_error_group(), Some(&client))
+        .await
+        .unwrap();
+
+    assert_eq!(analysis.tool_invocations.len(), 1);
+    assert_eq!(analysis.tool_invocations[0].tool, "delete_everything");
+    assert_eq!(analysis.tool_invocations[0].status, "failure");
+    answer.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_claude_tool_loop() {
+    let mut server = Server::new_async().await;
+
+    server
+        .mock("POST", "/messages")
+        .match_body(Matcher::Regex(r#""input_schema""#.to_string()))
+        .with_status(200)
+        .with_body(
+            json!({
+                "content": [
+                    {"type": "text", "text": "Let me check the configuration."},
+                    {
+                        "type": "tool_use",
+                        "id": "toolu_1",
+                        "name": "read_file",
+                        "input": {"path": "config.toml"}
+                    }
+                ],
+                "stop_reason": "tool_use"
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let answer = server
+        .mock("POST", "/messages")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""type":"tool_result""#.to_string()),
+            Matcher::Regex(r#""tool_use_id":"toolu_1""#.to_string()),
+        ]))
+        .with_status(200)
+        .with_body(
+            json!({
+                "content": [{"type": "text", "text": final_analysis()}],
+                "stop_reason": "end_turn"
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = ClaudeProvider::new("test-key".to_string(), None).with_base_url(server.url());
+    let client = connect_fake_server().await;
+
+    let analysis = provider
+        .analyze_with_tools(&sample_error_group(), Some(&client))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        analysis.root_cause.as_deref(),
+        Some("pool_size is set to 5")
+    );
+    assert_eq!(analysis.tool_invocations.len(), 1);
+    assert_eq!(analysis.tool_invocations[0].status, "success");
+    answer.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_ollama_tool_loop() {
+    let mut server = Server::new_async().await;
+
+    server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::Regex(r#""tools""#.to_string()))
+        .with_status(200)
+        .with_body(
+            json!({
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "function": {"name": "read_file", "arguments": {"path": "config.toml"}}
+                    }]
+                },
+                "done": true
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let answer = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""role":"tool""#.to_string()),
+            Matcher::Regex("pool_size = 5".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(
+            json!({
+                "message": {"role": "assistant", "content": final_analysis()},
+                "done": true
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OllamaProvider::new(Some(server.url()), None);
+    let client = connect_fake_server().await;
+
+    let analysis = provider
+        .analyze_with_tools(&sample_error_group(), Some(&client))
+        .await
+        .unwrap();
+
+    assert_eq!(analysis.suggestions.len(), 1);
+    assert_eq!(analysis.tool_invocations.len(), 1);
+    assert_eq!(analysis.tool_invocations[0].tool, "read_file");
+    answer.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_provider_without_tools_skips_tool_loop() {
+    let mut server = Server::new_async().await;
+
+    let generate = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_body(json!({"response": final_analysis()}).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OllamaProvider::new(Some(server.url()), None);
+    let client = MCPClient::new(MCPConfig::default()).unwrap();
+
+    let analysis = provider
+        .analyze_with_tools(&sample_error_group(), Some(&client))
+        .await
+        .unwrap();
+
+    assert!(analysis.tool_invocations.is_empty());
+    generate.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_builtin_tools_without_servers() {
+    let source = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        source.path().join("pool.rs"),
+        "fn connect() {\n    let pool_size = 5;\n}\n",
+    )
+    .unwrap();
+
+    let mut server = Server::new_async().await;
+    server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::Regex(
+            r#""name":"read_source_context""#.to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {
+                                "name": "read_source_context",
+                                "arguments": "{\"file\":\"pool.rs:2\"}"
+                            }
+                        }]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let answer = server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::Regex(r">2 \|     let pool_size = 5;".to_string()))
+        .with_status(200)
+        .with_body(
+            json!({
+                "choices": [{"message": {"role": "assistant", "content": final_analysis()}}]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = MCPConfig {
+        builtin_tools: BuiltinToolsConfig {
+            source_root: Some(source.path().display().to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut client = MCPClient::new(config.clone()).unwrap();
+    client.register_local_tools(ToolRegistry::builtin(
+        &config.builtin_tools,
+        vec![sample_log_entry()],
+    ));
+    assert!(client.has_tool("query_logs"));
+    assert!(client.has_tool("search_code"));
+
+    let provider = OpenAIProvider::new("test-key".to_string(), None).with_base_url(server.url());
+    let analysis = provider
+        .analyze_with_tools(&sample_error_group(), Some(&client))
+        .await
+        .unwrap();
+
+    assert_eq!(analysis.tool_invocations.len(), 1);
+    assert_eq!(analysis.tool_invocations[0].tool, "read_source_context");
+    assert_eq!(analysis.tool_invocations[0].status, "success");
+    answer.assert_async().await;
+}
@@ -0,0 +1,165 @@
+//! WebSocket transport tests against a local MCP server.
+
+use futures_util::{SinkExt, StreamExt};
+use logai::mcp::{
+    AuthConfig, ConnectionConfig, MCPClient, MCPConfig, ServerConfig, Transport, WebSocketTransport,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Start a one-connection MCP server; returns its URL and the upgrade's
+/// Authorization header once a client connects
+#[allow(clippy::result_large_err)] // the handshake callback signature is fixed by tungstenite
+async fn start_server(close_after_initialize: bool) -> (String, oneshot::Receiver<Option<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (auth_tx, auth_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut auth_tx = Some(auth_tx);
+        let mut ws =
+            tokio_tungstenite::accept_hdr_async(stream, |req: &Request, resp: Response| {
+                let auth = req
+                    .headers()
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from);
+                let _ = auth_tx.take().unwrap().send(auth);
+                Ok(resp)
+            })
+            .await
+            .unwrap();
+
+        while let Some(Ok(frame)) = ws.next().await {
+            let Message::Text(text) = frame else {
+                continue;
+            };
+            let request: Value = serde_json::from_str(text.as_str()).unwrap();
+            let id = request["id"].clone();
+
+            let result = match request["method"].as_str().unwrap_or_default() {
+                "initialize" => json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {"tools": {}},
+                    "serverInfo": {"name": "ws", "version": "1.0"}
+                }),
+                "tools/list" => json!({"tools": [{
+                    "name": "query_metrics",
+                    "description": "Query metrics",
+                    "inputSchema": {"type": "object", "properties": {}}
+                }]}),
+                "tools/call" => {
+                    // Make sure the client answers pings before replying
+                    ws.send(Message::Ping("are you there".into()))
+                        .await
+                        .unwrap();
+                    loop {
+                        match ws.next().await {
+                            Some(Ok(Message::Pong(data))) => {
+                                assert_eq!(&data[..], b"are you there");
+                                break;
+                            }
+                            Some(Ok(_)) => continue,
+                            _ => return,
+                        }
+                    }
+                    json!({"content": [{"type": "text", "text": "p99 latency 2.3s"}]})
+                }
+                // Notifications get no reply
+                _ => continue,
+            };
+
+            let response = json!({"jsonrpc": "2.0", "id": id, "result": result});
+            ws.send(Message::Text(response.to_string().into()))
+                .await
+                .unwrap();
+
+            if close_after_initialize && request["method"] == "initialize" {
+                ws.send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Away,
+                    reason: "shutting down".into(),
+                })))
+                .await
+                .unwrap();
+            }
+        }
+    });
+
+    (url, auth_rx)
+}
+
+#[tokio::test]
+async fn test_websocket_transport_round_trip_with_auth() {
+    let (url, auth_rx) = start_server(false).await;
+
+    let config = MCPConfig {
+        servers: vec![ServerConfig {
+            name: "metrics".to_string(),
+            enabled: true,
+            connection: ConnectionConfig::WebSocket { url },
+            auth: Some(AuthConfig {
+                scheme: "Bearer".to_string(),
+                credentials: HashMap::from([("token".to_string(), "ws-secret".to_string())]),
+            }),
+        }],
+        default_timeout: 10,
+        tool_timeouts: HashMap::new(),
+        builtin_tools: Default::default(),
+    };
+
+    let mut client = MCPClient::new(config).unwrap();
+    client.connect().await.unwrap();
+    assert!(client.is_connected());
+    assert_eq!(auth_rx.await.unwrap().as_deref(), Some("Bearer ws-secret"));
+
+    let tools = client.discover_tools().await.unwrap();
+    assert_eq!(tools[0].name, "query_metrics");
+
+    let result = client
+        .invoke_tool("query_metrics", json!({}))
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert_eq!(
+        result.data.unwrap()["content"][0]["text"],
+        "p99 latency 2.3s"
+    );
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_websocket_transport_reports_server_close() {
+    let (url, _auth_rx) = start_server(true).await;
+
+    let transport = WebSocketTransport::connect(&url, None).await.unwrap();
+    transport
+        .send(json!({"jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {}}))
+        .await
+        .unwrap();
+
+    let response = transport.receive().await.unwrap();
+    assert_eq!(response["id"], 0);
+
+    let error = transport.receive().await.unwrap_err();
+    assert!(error.to_string().contains("closed by server"));
+    assert!(error.to_string().contains("shutting down"));
+    assert!(!transport.is_alive());
+    assert!(transport.send(json!({})).await.is_err());
+}
+
+#[tokio::test]
+async fn test_websocket_transport_connection_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    assert!(WebSocketTransport::connect(&url, None).await.is_err());
+}
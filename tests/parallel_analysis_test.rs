@@ -1,5 +1,7 @@
 use chrono::Utc;
-use logai::ai::{AnalysisConfig, ParallelAnalyzer, ProgressUpdate};
+use logai::ai::{AnalysisCache, AnalysisConfig, ParallelAnalyzer, ProgressUpdate};
+use logai::analyzer::grouper::ErrorGrouper;
+use logai::analyzer::{GroupingConfig, GroupingStrategy};
 use logai::types::{ErrorAnalysis, ErrorGroup, LogEntry, LogMetadata, Severity, Suggestion};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -9,6 +11,7 @@ struct MockProvider {
     delay_ms: u64,
     failure_rate: f64,
     call_count: Arc<Mutex<usize>>,
+    model: String,
 }
 
 impl MockProvider {
@@ -17,9 +20,15 @@ impl MockProvider {
             delay_ms,
             failure_rate,
             call_count: Arc::new(Mutex::new(0)),
+            model: "mock-model".to_string(),
         }
     }
 
+    fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
     fn get_call_count(&self) -> usize {
         *self.call_count.lock().unwrap()
     }
@@ -59,6 +68,10 @@ impl logai::ai::AIProvider for MockProvider {
     fn name(&self) -> &str {
         "mock"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
 
 fn create_test_groups(count: usize) -> Vec<ErrorGroup> {
@@ -82,8 +95,10 @@ fn create_test_groups(count: usize) -> Vec<ErrorGroup> {
                     extra: std::collections::HashMap::new(),
                 },
                 raw: format!("Error message {}", i),
+                source: None,
             }],
             analysis: None,
+            sources: Vec::new(),
         })
         .collect()
 }
@@ -98,6 +113,7 @@ async fn test_parallel_processing_with_mock_provider() {
         initial_backoff_ms: 100,
         max_backoff_ms: 1000,
         enable_cache: false,
+        cache_ttl_days: 30,
         truncate_length: 2000,
     };
 
@@ -159,6 +175,7 @@ async fn test_concurrency_limits_are_respected() {
         initial_backoff_ms: 100,
         max_backoff_ms: 1000,
         enable_cache: false,
+        cache_ttl_days: 30,
         truncate_length: 2000,
     };
 
@@ -196,6 +213,7 @@ async fn test_mixed_success_failure_scenarios() {
         initial_backoff_ms: 100,
         max_backoff_ms: 1000,
         enable_cache: false,
+        cache_ttl_days: 30,
         truncate_length: 2000,
     };
 
@@ -277,3 +295,289 @@ async fn test_single_group() {
     assert_eq!(groups.len(), 1);
     assert!(groups[0].analysis.is_some());
 }
+
+#[tokio::test]
+async fn test_cache_serves_repeated_analysis() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("cache.db");
+    let provider = Arc::new(MockProvider::new(1, 0.0));
+
+    // First run populates the cache
+    let analyzer = ParallelAnalyzer::new(provider.clone(), AnalysisConfig::default())
+        .with_cache(AnalysisCache::open(&db_path).unwrap());
+    let mut groups = create_test_groups(4);
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+    assert_eq!(stats.cache_misses, 4);
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(provider.get_call_count(), 4);
+
+    // Second run is served entirely from the cache
+    let analyzer = ParallelAnalyzer::new(provider.clone(), AnalysisConfig::default())
+        .with_cache(AnalysisCache::open(&db_path).unwrap());
+    let mut groups = create_test_groups(4);
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+    assert_eq!(stats.cache_hits, 4);
+    assert_eq!(stats.cache_misses, 0);
+    assert_eq!(provider.get_call_count(), 4);
+    assert!(groups.iter().all(|g| g.analysis.is_some()));
+
+    // A provider resolving to a different model does not reuse those entries
+    let other = Arc::new(MockProvider::new(1, 0.0).with_model("other-model"));
+    let analyzer = ParallelAnalyzer::new(other.clone(), AnalysisConfig::default())
+        .with_cache(AnalysisCache::open(&db_path).unwrap());
+    let mut groups = create_test_groups(4);
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+    assert_eq!(stats.cache_misses, 4);
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(other.get_call_count(), 4);
+    assert_eq!(provider.get_call_count(), 4);
+}
+
+#[tokio::test]
+async fn test_cache_keeps_traces_with_shared_first_line_apart() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("cache.db");
+    let provider = Arc::new(MockProvider::new(1, 0.0));
+
+    // Drain mines both patterns from "Request failed"; the traces differ
+    let trace = |exception: &str, frame: &str| LogEntry {
+        timestamp: Some(Utc::now()),
+        severity: Severity::Error,
+        message: format!(
+            "Request failed\n{}: boom\n\tat {}(App.java:1)",
+            exception, frame
+        ),
+        metadata: LogMetadata {
+            file: None,
+            line: None,
+            function: None,
+            thread: None,
+            extra: std::collections::HashMap::new(),
+        },
+        raw: String::new(),
+        source: None,
+    };
+    let grouper = ErrorGrouper::new().with_config(GroupingConfig {
+        strategy: GroupingStrategy::Drain,
+        ..GroupingConfig::default()
+    });
+    let entries = vec![
+        trace("java.lang.NullPointerException", "com.shop.Orders.load"),
+        trace("java.lang.IllegalStateException", "com.shop.Cart.add"),
+    ];
+    let groups = grouper.group(entries).unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].pattern, groups[1].pattern);
+
+    for run in 0..2 {
+        let analyzer = ParallelAnalyzer::new(provider.clone(), AnalysisConfig::default())
+            .with_cache(AnalysisCache::open(&db_path).unwrap());
+        let mut groups = groups.clone();
+        let stats = analyzer
+            .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+            .await
+            .unwrap();
+        if run == 0 {
+            assert_eq!(stats.cache_misses, 2);
+        } else {
+            assert_eq!(stats.cache_hits, 2);
+        }
+        for group in &groups {
+            assert_eq!(
+                group.analysis.as_ref().unwrap().explanation,
+                format!("Analysis for group {}", group.id)
+            );
+        }
+    }
+    assert_eq!(provider.get_call_count(), 2);
+}
+
+#[tokio::test]
+async fn test_cache_ignored_when_disabled() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("cache.db");
+    let provider = Arc::new(MockProvider::new(1, 0.0));
+
+    let config = AnalysisConfig {
+        enable_cache: false,
+        ..Default::default()
+    };
+
+    for _ in 0..2 {
+        let analyzer = ParallelAnalyzer::new(provider.clone(), config.clone())
+            .with_cache(AnalysisCache::open(&db_path).unwrap());
+        let mut groups = create_test_groups(2);
+        let stats = analyzer
+            .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+            .await
+            .unwrap();
+        assert_eq!(stats.cache_hits + stats.cache_misses, 0);
+    }
+
+    assert_eq!(provider.get_call_count(), 4);
+}
+
+/// Provider that fails the first `failures_per_group` attempts for every group
+struct FlakyProvider {
+    failures_per_group: usize,
+    error: String,
+    attempts: Mutex<std::collections::HashMap<String, usize>>,
+}
+
+impl FlakyProvider {
+    fn new(failures_per_group: usize, error: &str) -> Self {
+        Self {
+            failures_per_group,
+            error: error.to_string(),
+            attempts: Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl logai::ai::AIProvider for FlakyProvider {
+    async fn analyze(&self, group: &ErrorGroup) -> anyhow::Result<ErrorAnalysis> {
+        let attempt = {
+            let mut attempts = self.attempts.lock().unwrap();
+            let count = attempts.entry(group.id.clone()).or_insert(0);
+            *count += 1;
+            *count
+        };
+
+        if attempt <= self.failures_per_group {
+            return Err(anyhow::anyhow!("{}", self.error));
+        }
+
+        Ok(ErrorAnalysis {
+            explanation: format!("Analysis for group {}", group.id),
+            root_cause: None,
+            suggestions: vec![],
+            related_resources: vec![],
+            tool_invocations: vec![],
+        })
+    }
+
+    fn name(&self) -> &str {
+        "flaky"
+    }
+}
+
+fn retry_config(enable_retry: bool) -> AnalysisConfig {
+    AnalysisConfig {
+        enable_retry,
+        max_retries: 2,
+        initial_backoff_ms: 1,
+        max_backoff_ms: 5,
+        enable_cache: false,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried() {
+    let provider = Arc::new(FlakyProvider::new(1, "503 service unavailable"));
+    let analyzer = ParallelAnalyzer::new(provider, retry_config(true));
+    let mut groups = create_test_groups(5);
+
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+
+    assert!(groups.iter().all(|g| g.analysis.is_some()));
+    assert_eq!(stats.successful, 5);
+    assert_eq!(stats.failed, 0);
+    assert_eq!(stats.retry_counts.get(&2), Some(&5));
+}
+
+#[tokio::test]
+async fn test_retries_disabled_by_config() {
+    let provider = Arc::new(FlakyProvider::new(1, "503 service unavailable"));
+    let analyzer = ParallelAnalyzer::new(provider, retry_config(false));
+    let mut groups = create_test_groups(3);
+
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+
+    assert!(groups.iter().all(|g| g.analysis.is_none()));
+    assert_eq!(stats.failed, 3);
+    assert_eq!(stats.retry_counts.get(&1), Some(&3));
+}
+
+#[tokio::test]
+async fn test_failure_reasons_are_collected() {
+    let provider = Arc::new(FlakyProvider::new(usize::MAX, "401 unauthorized"));
+    let analyzer = ParallelAnalyzer::new(provider, retry_config(true));
+    let mut groups = create_test_groups(4);
+
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+
+    assert_eq!(stats.failed, 4);
+    assert_eq!(stats.failure_reasons.get("401 unauthorized"), Some(&4));
+    // Authentication errors are not retried
+    assert_eq!(stats.retry_counts.get(&1), Some(&4));
+    assert!(stats.format_summary().contains("401 unauthorized"));
+}
+
+/// Provider that panics while analyzing one group
+struct PanickingProvider {
+    group_id: String,
+}
+
+#[async_trait::async_trait]
+impl logai::ai::AIProvider for PanickingProvider {
+    async fn analyze(&self, group: &ErrorGroup) -> anyhow::Result<ErrorAnalysis> {
+        if group.id == self.group_id {
+            panic!("provider bug");
+        }
+        Ok(ErrorAnalysis {
+            explanation: format!("Analysis for group {}", group.id),
+            root_cause: None,
+            suggestions: vec![],
+            related_resources: vec![],
+            tool_invocations: vec![],
+        })
+    }
+
+    fn name(&self) -> &str {
+        "panicking"
+    }
+}
+
+#[tokio::test]
+async fn test_panicked_tasks_count_as_failures() {
+    let provider = Arc::new(PanickingProvider {
+        group_id: "2".to_string(),
+    });
+    let analyzer = ParallelAnalyzer::new(provider, AnalysisConfig::default());
+    let mut groups = create_test_groups(4);
+
+    let stats = analyzer
+        .analyze_groups(&mut groups, |_: ProgressUpdate| {})
+        .await
+        .unwrap();
+
+    assert_eq!(stats.total_groups, 4);
+    assert_eq!(stats.successful, 3);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(
+        stats.failure_reasons.get("Analysis task panicked"),
+        Some(&1)
+    );
+    assert!(groups[2].analysis.is_none());
+    assert!(stats.format_summary().contains("Analysis task panicked"));
+}
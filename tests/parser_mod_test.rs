@@ -25,6 +25,7 @@ fn create_log_entry(message: &str) -> LogEntry {
             extra: std::collections::HashMap::new(),
         },
         raw: message.to_string(),
+        source: None,
     }
 }
 